@@ -0,0 +1,158 @@
+//! Benchmarks for the hot paths of the TUI: highlighting, sorting and previews.
+//!
+//! Run with `cargo bench`, see BENCHMARKS.md for the expected timings
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use search_rs::search::sorter::{FileSorter, SortOrder};
+use search_rs::search::SearchResult;
+use search_rs::tui::app::App;
+use search_rs::tui::highlighter::SyntaxHighlighter;
+use search_rs::PreviewHandler;
+use std::fs;
+use tempfile::tempdir;
+
+/// Input sizes: (label, line length in characters)
+const LINE_SIZES: [(&str, usize); 2] = [("small", 40), ("large", 400)];
+
+/// A line of Rust of roughly `length` characters
+fn rust_line(length: usize) -> String {
+    let mut line = String::from("let value = compute(");
+    while line.len() < length {
+        line.push_str("input.field + 42, ");
+    }
+    line.push_str(");");
+    line
+}
+
+fn result(file_path: &str, line_number: usize, line_content: String) -> SearchResult {
+    SearchResult::builder()
+        .file_path(file_path)
+        .line_number(line_number)
+        .line_content(line_content)
+        .matched_text("compute")
+        .build()
+}
+
+/// Results spread over 100 files that don't exist, so sorting doesn't hit the disk
+fn results(count: usize) -> Vec<SearchResult> {
+    (0..count)
+        .map(|i| {
+            result(
+                &format!("src/module_{}.rs", i % 100),
+                count - i,
+                rust_line(40),
+            )
+        })
+        .collect()
+}
+
+fn bench_highlight_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("highlight_line");
+    for (label, length) in LINE_SIZES {
+        let line = rust_line(length);
+        group.bench_with_input(BenchmarkId::new("cold", label), &line, |b, line| {
+            b.iter_batched(
+                SyntaxHighlighter::new,
+                |mut highlighter| highlighter.highlight_line(black_box(line), Some("rs")),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let mut highlighter = SyntaxHighlighter::new();
+        highlighter.highlight_line(&line, Some("rs"));
+        group.bench_with_input(BenchmarkId::new("warm", label), &line, |b, line| {
+            b.iter(|| highlighter.highlight_line(black_box(line), Some("rs")))
+        });
+    }
+    group.finish();
+}
+
+fn bench_cached_highlighted_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_cached_highlighted_line");
+    for (label, length) in LINE_SIZES {
+        let result = result("src/main.rs", 1, rust_line(length));
+        let mut highlighter = SyntaxHighlighter::new();
+
+        let app = App::new();
+        app.get_cached_highlighted_line(&result, &mut highlighter);
+        group.bench_with_input(BenchmarkId::new("hit", label), &result, |b, result| {
+            b.iter(|| app.get_cached_highlighted_line(black_box(result), &mut highlighter))
+        });
+
+        group.bench_with_input(BenchmarkId::new("miss", label), &result, |b, result| {
+            b.iter_batched(
+                App::new,
+                |app| app.get_cached_highlighted_line(black_box(result), &mut highlighter),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_sorter_add_results(c: &mut Criterion) {
+    let mut group = c.benchmark_group("FileSorter::add_results");
+    for (label, count) in [("small", 100), ("large", 10_000)] {
+        let results = results(count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(label),
+            &results,
+            |b, results| {
+                b.iter_batched(
+                    || {
+                        let mut sorter = FileSorter::new();
+                        sorter.set_enabled(true);
+                        sorter.set_sort_order(SortOrder::Alphabetical);
+                        (sorter, results.clone())
+                    },
+                    |(mut sorter, results)| sorter.add_results(results),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_preview_file(c: &mut Criterion) {
+    let dir = tempdir().unwrap();
+    let handler = PreviewHandler::new();
+    let mut group = c.benchmark_group("preview_file");
+    for (label, size) in [("small", 2 * 1024), ("large", 50 * 1024)] {
+        let path = dir.path().join(format!("{}.rs", label));
+        let mut content = String::new();
+        while content.len() < size {
+            content.push_str(&rust_line(60));
+            content.push('\n');
+        }
+        fs::write(&path, &content).unwrap();
+        let middle = content.lines().count() / 2;
+
+        group.bench_function(label, |b| {
+            b.iter(|| handler.preview_file(black_box(&path), Some(middle), Some((120, 40))))
+        });
+    }
+    group.finish();
+}
+
+fn bench_format_for_tui_display(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format_for_tui_display");
+    let mut highlighter = SyntaxHighlighter::new();
+    for (label, length) in LINE_SIZES {
+        let result = result("src/main.rs", 1, rust_line(length));
+        group.bench_with_input(BenchmarkId::from_parameter(label), &result, |b, result| {
+            b.iter(|| black_box(result).format_for_tui_display(&mut highlighter, true))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_highlight_line,
+    bench_cached_highlighted_line,
+    bench_sorter_add_results,
+    bench_preview_file,
+    bench_format_for_tui_display
+);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Non-interactive batch mode
+//!
+//! Used with `--json` or when stdout is not a terminal. Prints the same JSON lines
+//! as `--output json` on stdout and reports progress on stderr.
+
+use crate::cli::Cli;
+use crate::output;
+use crate::search::SearchEngine;
+use crate::Result;
+use std::io;
+
+/// Run the search without the TUI, printing results as JSON lines
+pub fn run_batch_mode(cli: &Cli, engine: &SearchEngine) -> Result<()> {
+    eprintln!(
+        "Searching for '{}' in {}",
+        cli.pattern,
+        cli.search_dir().join(", ")
+    );
+    output::run_json_lines(cli, engine, io::stderr())
+}
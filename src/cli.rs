@@ -3,11 +3,30 @@
 //! Handles command-line argument parsing using clap, supports multiple search modes
 //!
 
-use clap::Parser;
-use std::path::PathBuf;
+use crate::config::Config;
+use crate::logging::LogFormat;
+use crate::preview::PreviewHandler;
+use crate::search::multi::MultiPatternMode;
+use crate::search::sorter::SortOrder;
+use clap::builder::Resettable;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use clap_complete::Shell;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable with the default search pattern
+pub const PATTERN_ENV_VAR: &str = "SEARCH_RS_PATTERN";
+/// Environment variable with the default directories, separated like `PATH`
+pub const DIRECTORY_ENV_VAR: &str = "SEARCH_RS_DIRECTORY";
+/// Environment variable with the default search mode
+pub const MODE_ENV_VAR: &str = "SEARCH_RS_MODE";
+/// Environment variable with the default syntax highlighting theme
+pub const THEME_ENV_VAR: &str = "SEARCH_RS_THEME";
 
 /// Interactive Search Tool - A TUI enhanced code search tool based on rip-grep
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(
     name = "search-rs",
     about = "Interactive Search Tool - A TUI enhanced code search tool based on rip-grep",
@@ -18,7 +37,38 @@ use std::path::PathBuf;
         search-rs -e \"search pattern\" # Case sensitive search (default)
         search-rs -i \"search pattern\" # Case insensitive search
         search-rs -s \"search pattern\" # Substring search
+        search-rs -S \"search pattern\" # Smart case, case sensitive only if the pattern has uppercase
+        search-rs -F \"fnmn\" # Fuzzy search, finds \"fn main\"
         search-rs -d /path/to/dir # Search in a specific directory
+        search-rs -d /path/one -d /path/two # Search in multiple directories
+        search-rs -C 2 \"search pattern\" # Show 2 context lines around each match
+        search-rs --max-count 50 --max-depth 3 \"search pattern\" # Limit matches per file and directory depth
+        search-rs --max-results 200 \"search pattern\" # Stop the search after 200 results in total
+        search-rs --hidden \"search pattern\" # Also search hidden files and directories
+        search-rs -L --no-ignore \"search pattern\" # Follow symlinks and ignore .gitignore files
+        search-rs foo --and bar # Lines containing both foo and bar
+        search-rs foo --or bar # Lines containing foo or bar
+        search-rs -t rs -t toml \"search pattern\" # Only search files with these extensions
+        search-rs -t rust \"search pattern\" # Use a ripgrep file type such as rust or python
+        search-rs --include \"*.rs\" --exclude \"target/**\" \"search pattern\" # Filter files by glob
+        search-rs --sort match-count \"search pattern\" # Show files with the most matches first
+        search-rs --editor \"code --goto {file}:{line}\" \"search pattern\" # Open results in VS Code
+        search-rs --theme InspiredGitHub \"search pattern\" # Use a light syntax highlighting theme, remembered in config.toml
+        search-rs --output json \"search pattern\" | jq # Print results as JSON lines
+        search-rs --json \"search pattern\" # JSON lines with progress on stderr, also used when stdout is not a terminal
+        search-rs --print-config # Show the config resolved from config.toml and flags
+        search-rs --theme-file ~/themes/custom.tmTheme \"search pattern\" # Use a custom syntax highlighting theme
+        search-rs --list-themes # Show the available syntax highlighting themes
+
+    CONFIG:
+        Defaults are read from $XDG_CONFIG_HOME/search-rs/config.toml, flags take precedence
+
+    ENVIRONMENT:
+        SEARCH_RS_PATTERN    Search pattern when none is given
+        SEARCH_RS_DIRECTORY  Directories to search, separated like PATH
+        SEARCH_RS_MODE       Search mode: exact, ignore_case, substring, smart_case or fuzzy
+        SEARCH_RS_THEME      Syntax highlighting theme
+        Flags take precedence over config.toml, which takes precedence over these variables
 
     USAGE TIP:
         Use arrow keys to navigate, press enter to open a search result in a code editor
@@ -27,7 +77,17 @@ use std::path::PathBuf;
 #[command(version)]
 pub struct Cli {
     /// Search pattern to search for
-    #[arg(help = "Search pattern to search for in files")]
+    #[arg(
+        default_value = "",
+        hide_default_value = true,
+        required_unless_present_any = [
+            "print_config",
+            "list_themes",
+            "clear_history",
+            "generate_completion"
+        ],
+        help = "Search pattern to search for in files"
+    )]
     pub pattern: String,
 
     /// Case sensitive search
@@ -42,43 +102,302 @@ pub struct Cli {
     #[arg(short, long, help = "Substring search (case sensitive)")]
     pub substring: bool,
 
-    /// Search in a specific directory
+    /// Smart case search
     #[arg(
-        short,
+        short = 'S',
         long,
-        help = "Search in a specific directory (default: current directory)"
+        help = "Smart case search, case insensitive unless the pattern contains uppercase"
     )]
-    pub directory: Option<PathBuf>,
+    pub smart_case: bool,
 
-    /// debug mode
+    /// Fuzzy search
+    #[arg(
+        short = 'F',
+        long,
+        help = "Fuzzy search, results are re-scored and sorted by match quality (slower on large result sets)"
+    )]
+    pub fuzzy: bool,
+
+    /// Search in one or more directories
     #[arg(
         short,
         long,
-        help = "Debug mode (logging to /tmp file with timestamps)"
+        help = "Search in a specific directory, can be repeated (default: current directory)"
+    )]
+    pub directory: Vec<PathBuf>,
+
+    /// Context lines around each match
+    #[arg(
+        short = 'C',
+        long,
+        default_value_t = 0,
+        help = "Number of context lines to show before and after each match"
+    )]
+    pub context: usize,
+
+    /// File extensions or ripgrep type names to search
+    #[arg(
+        short = 't',
+        long = "type",
+        value_name = "EXT",
+        help = "Only search files with the extension or ripgrep type name (e.g. rs, rust), can be repeated"
+    )]
+    pub file_types: Vec<String>,
+
+    /// Patterns that must also match the line
+    #[arg(
+        long = "and",
+        value_name = "PATTERN",
+        conflicts_with = "or_patterns",
+        help = "Only keep lines that also match PATTERN, can be repeated"
+    )]
+    pub and_patterns: Vec<String>,
+
+    /// Patterns that may match the line instead
+    #[arg(
+        long = "or",
+        value_name = "PATTERN",
+        help = "Also find lines that match PATTERN, can be repeated"
+    )]
+    pub or_patterns: Vec<String>,
+
+    /// Glob patterns of files to include
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Only search files matching the glob, can be repeated"
+    )]
+    pub include: Vec<String>,
+
+    /// Glob patterns of files to exclude
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip files matching the glob, can be repeated"
+    )]
+    pub exclude: Vec<String>,
+
+    /// Maximum number of matches per file
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Stop searching a file after N matching lines (ripgrep --max-count)"
+    )]
+    pub max_count: Option<usize>,
+
+    /// Maximum number of results in total
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Stop the search after N results in total"
     )]
+    pub max_results: Option<usize>,
+
+    /// Maximum directory depth
+    #[arg(long, value_name = "N", help = "Descend at most N directory levels")]
+    pub max_depth: Option<usize>,
+
+    /// Maximum line length
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        help = "Skip lines longer than N bytes, 0 for no limit"
+    )]
+    pub max_line_length: usize,
+
+    /// Search timeout
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = 0,
+        help = "Stop the search after MS milliseconds and keep the results found so far, 0 for no limit"
+    )]
+    pub search_timeout_ms: u64,
+
+    /// Search hidden files
+    #[arg(long, help = "Search hidden files and directories")]
+    pub hidden: bool,
+
+    /// Follow symbolic links
+    #[arg(
+        short = 'L',
+        long,
+        help = "Follow symbolic links into linked directories"
+    )]
+    pub follow_symlinks: bool,
+
+    /// Don't respect ignore files
+    #[arg(
+        long,
+        help = "Don't skip files listed in .gitignore, .ignore and similar files"
+    )]
+    pub no_ignore: bool,
+
+    /// Additional ignore file
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Skip files matching the globs in PATH, in .gitignore format"
+    )]
+    pub ignore_file: Option<PathBuf>,
+
+    /// Maximum file size
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Skip files larger than BYTES bytes"
+    )]
+    pub max_filesize: Option<u64>,
+
+    /// Sort order of the results
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ORDER",
+        help = "Sort results (default: modification-time inside a git repository, unsorted otherwise)"
+    )]
+    pub sort: Option<SortOrder>,
+
+    /// Editor command
+    #[arg(
+        long,
+        value_name = "CMD",
+        help = "Editor opened on Enter, {file} and {line} are replaced (default: $EDITOR, then vi)"
+    )]
+    pub editor: Option<String>,
+
+    /// Syntax highlighting theme
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Syntax highlighting theme, saved to the config file for later runs (see --list-themes)"
+    )]
+    pub theme: Option<String>,
+
+    /// Syntax highlighting theme file
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Load the syntax highlighting theme from a .tmTheme file, takes precedence over --theme"
+    )]
+    pub theme_file: Option<PathBuf>,
+
+    /// Annotations to load
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Load result annotations from a TSV file exported from the TUI"
+    )]
+    pub import_annotations: Option<PathBuf>,
+
+    /// Hex dump previews
+    #[arg(long, help = "Preview binary files as a hex dump instead of a notice")]
+    pub hex: bool,
+
+    /// Rendered Markdown previews
+    #[arg(
+        long,
+        help = "Render Markdown files in the preview instead of showing their source"
+    )]
+    pub render_markdown: bool,
+
+    /// Text encoding of previewed files
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Text encoding of previewed files, e.g. latin1 or shift_jis (default: detected per file)"
+    )]
+    pub encoding: Option<String>,
+
+    /// Keep duplicate results
+    #[arg(
+        long,
+        help = "Keep results found more than once for the same file and line"
+    )]
+    pub no_dedup: bool,
+
+    /// Output format
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Tui,
+        help = "Output format, json prints newline-delimited results instead of launching the TUI"
+    )]
+    pub output: OutputFormat,
+
+    /// Batch mode
+    #[arg(
+        long,
+        help = "Print results as JSON lines like --output json, with progress on stderr (default when stdout is not a terminal)"
+    )]
+    pub json: bool,
+
+    /// Print the resolved config
+    #[arg(
+        long,
+        help = "Print the config resolved from the config file and flags as TOML"
+    )]
+    pub print_config: bool,
+
+    /// Print the available themes
+    #[arg(long, help = "Print the available syntax highlighting themes")]
+    pub list_themes: bool,
+
+    /// Delete the search history
+    #[arg(long, help = "Delete the saved search history and exit")]
+    pub clear_history: bool,
+
+    /// Shell to print a completion script for
+    #[arg(long, value_enum, value_name = "SHELL", hide = true)]
+    pub generate_completion: Option<Shell>,
+
+    /// debug mode
+    #[arg(long, help = "Debug mode (logging to /tmp file with timestamps)")]
     pub debug: bool,
+
+    /// Debug log format
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Format of the debug log, json writes one JSON record per line"
+    )]
+    pub log_format: Option<LogFormat>,
 }
 
 impl Cli {
     /// Parse command line arguments
+    /// Prints the completion script and exits when `--generate-completion` is given
     pub fn parse_args() -> Self {
-        Self::parse()
+        let mut command = Self::command();
+        // The pattern can come from the environment instead
+        if env::var_os(PATTERN_ENV_VAR).is_some_and(|pattern| !pattern.is_empty()) {
+            command = command.mut_arg("pattern", |arg| {
+                arg.required_unless_present(Resettable::Reset)
+            });
+        }
+        let cli = Self::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit());
+        if let Some(shell) = cli.generate_completion {
+            crate::completions::print_completions(shell);
+            std::process::exit(0);
+        }
+        cli
     }
 
     /// Validate command line arguments
     pub fn validate(&self) -> crate::Result<()> {
         // Ensure only one search mode is selected
-        let modes = [self.exact, self.ignore_case, self.substring];
-        let mode_count = modes.iter().filter(|&&x| x).count();
+        let mode_count = self.mode_flags().iter().filter(|&&x| x).count();
 
         if mode_count > 1 {
             return Err(crate::SearchError::InvalidArguments(
-                "Only one search mode can be selected. Use -e, -i, or -s".to_string(),
+                "Only one search mode can be selected. Use -e, -i, -s, -S, or -F".to_string(),
             ));
         }
 
-        // Validate directory path if provided
-        if let Some(dir) = &self.directory {
+        // Validate every directory path provided
+        for dir in &self.directory {
             if !dir.exists() {
                 eprintln!("Error: Directory path must be an absolute path");
                 return Err(crate::SearchError::InvalidArguments(
@@ -93,6 +412,52 @@ impl Cli {
             }
         }
 
+        if self.max_count == Some(0) {
+            return Err(crate::SearchError::InvalidArguments(
+                "--max-count must be greater than 0".to_string(),
+            ));
+        }
+        if self.max_results == Some(0) {
+            return Err(crate::SearchError::InvalidArguments(
+                "--max-results must be greater than 0".to_string(),
+            ));
+        }
+
+        // Symlink loops on network shares are not always detected
+        if self.follow_symlinks {
+            for dir in self.directory.iter().filter(|dir| is_network_path(dir)) {
+                eprintln!(
+                    "Warning: --follow-symlinks on network path {} may loop",
+                    dir.display()
+                );
+            }
+        }
+
+        if self
+            .and_patterns
+            .iter()
+            .chain(&self.or_patterns)
+            .any(|pattern| pattern.trim().is_empty())
+        {
+            return Err(crate::SearchError::InvalidArguments(
+                "--and and --or patterns cannot be empty".to_string(),
+            ));
+        }
+
+        if let Some(encoding) = &self.encoding {
+            PreviewHandler::encoding_for_label(encoding)?;
+        }
+
+        // Validate include and exclude globs
+        for glob in self.include.iter().chain(&self.exclude) {
+            if let Err(e) = glob::Pattern::new(glob) {
+                return Err(crate::SearchError::InvalidArguments(format!(
+                    "Invalid glob pattern '{}': {}",
+                    glob, e
+                )));
+            }
+        }
+
         // Validate search pattern is not empty
         if self.pattern.trim().is_empty() {
             eprintln!("Error: Search pattern cannot be empty");
@@ -104,40 +469,215 @@ impl Cli {
         Ok(())
     }
 
+    /// Search mode flags in priority order
+    fn mode_flags(&self) -> [bool; 5] {
+        [
+            self.exact,
+            self.ignore_case,
+            self.substring,
+            self.smart_case,
+            self.fuzzy,
+        ]
+    }
+
+    /// Get the search mode selected by a flag, if any
+    pub fn explicit_search_mode(&self) -> Option<SearchMode> {
+        if self.mode_flags().iter().any(|&selected| selected) {
+            Some(self.search_mode())
+        } else {
+            None
+        }
+    }
+
+    /// Patterns given with `--and` or `--or` and how they combine with the primary pattern
+    pub fn multi_pattern(&self) -> Option<(MultiPatternMode, Vec<String>)> {
+        if !self.and_patterns.is_empty() {
+            Some((MultiPatternMode::And, self.and_patterns.clone()))
+        } else if !self.or_patterns.is_empty() {
+            Some((MultiPatternMode::Or, self.or_patterns.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Select a search mode by setting its flag
+    fn set_search_mode(&mut self, mode: SearchMode) {
+        match mode {
+            SearchMode::Exact => self.exact = true,
+            SearchMode::IgnoreCase => self.ignore_case = true,
+            SearchMode::Substring => self.substring = true,
+            SearchMode::SmartCase => self.smart_case = true,
+            SearchMode::Fuzzy => self.fuzzy = true,
+        }
+    }
+
+    /// Read the `SEARCH_RS_*` environment variables into a `Cli` with only those options set
+    pub fn from_env_vars() -> Self {
+        Self::from_env_with(|name| env::var(name).ok())
+    }
+
+    /// Read the `SEARCH_RS_*` variables from `lookup` into a `Cli` with only those options set
+    /// A search mode that isn't known is skipped with a warning
+    pub fn from_env_with(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name| lookup(name).filter(|value| !value.is_empty());
+        let mut cli = Self::default();
+        if let Some(pattern) = var(PATTERN_ENV_VAR) {
+            cli.pattern = pattern;
+        }
+        if let Some(directories) = var(DIRECTORY_ENV_VAR) {
+            cli.directory = env::split_paths(&directories)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect();
+        }
+        if let Some(mode) = var(MODE_ENV_VAR) {
+            match SearchMode::from_name(&mode) {
+                Some(mode) => cli.set_search_mode(mode),
+                None => eprintln!(
+                    "Warning: unknown search mode '{}' in {}",
+                    mode, MODE_ENV_VAR
+                ),
+            }
+        }
+        cli.theme = var(THEME_ENV_VAR);
+        cli
+    }
+
+    /// Fill in options set neither on the command line nor in the config file from
+    /// the environment variables read by `from_env_vars`
+    pub fn apply_env_vars(&mut self, env: &Cli, config: &Config) {
+        if self.pattern.is_empty() {
+            self.pattern = env.pattern.clone();
+        }
+        if self.explicit_search_mode().is_none() && config.search_mode.is_none() {
+            if let Some(mode) = env.explicit_search_mode() {
+                self.set_search_mode(mode);
+            }
+        }
+        if self.directory.is_empty() && config.directories.is_empty() {
+            self.directory = env.directory.clone();
+        }
+        if self.theme.is_none() && config.theme.is_none() {
+            self.theme = env.theme.clone();
+        }
+    }
+
+    /// Fill in options not given on the command line from the resolved config
+    pub fn apply_config(&mut self, config: &Config) {
+        if self.explicit_search_mode().is_none() {
+            if let Some(mode) = config.search_mode {
+                self.set_search_mode(mode);
+            }
+        }
+        if self.directory.is_empty() {
+            self.directory = config.directories.clone();
+        }
+        for glob in &config.exclude {
+            if !self.exclude.contains(glob) {
+                self.exclude.push(glob.clone());
+            }
+        }
+        if self.editor.is_none() {
+            self.editor = config.editor.clone();
+        }
+        if self.theme.is_none() {
+            self.theme = config.theme.clone();
+        }
+        self.hidden |= config.hidden;
+        self.follow_symlinks |= config.follow_symlinks;
+        self.no_ignore |= config.no_ignore;
+        if self.ignore_file.is_none() {
+            self.ignore_file = config.ignore_file.clone();
+        }
+        if self.max_filesize.is_none() {
+            self.max_filesize = config.max_filesize;
+        }
+        self.hex |= config.hex;
+        self.render_markdown |= config.render_markdown;
+        if self.max_line_length == 0 {
+            self.max_line_length = config.max_line_length;
+        }
+        if self.log_format.is_none() {
+            self.log_format = Some(config.logging.format);
+        }
+    }
+
     /// Get the search mode
     pub fn search_mode(&self) -> SearchMode {
-        match (self.exact, self.ignore_case, self.substring) {
-            (true, false, false) => SearchMode::Exact,
-            (false, true, false) => SearchMode::IgnoreCase,
-            (false, false, true) => SearchMode::Substring,
+        match self.mode_flags() {
+            [true, false, false, false, false] => SearchMode::Exact,
+            [false, true, false, false, false] => SearchMode::IgnoreCase,
+            [false, false, true, false, false] => SearchMode::Substring,
+            [false, false, false, true, false] => SearchMode::SmartCase,
+            [false, false, false, false, true] => SearchMode::Fuzzy,
             _ => SearchMode::Exact,
         }
     }
 
-    /// Get the search directory, defaulting to current directory
-    pub fn search_dir(&self) -> String {
-        match &self.directory {
-            Some(dir) => dir.to_str().unwrap().to_string(),
-            None => ".".to_string(),
+    /// Lines longer than this are skipped, `None` when there is no limit
+    pub fn line_length_limit(&self) -> Option<usize> {
+        Some(self.max_line_length).filter(|&limit| limit > 0)
+    }
+
+    /// Time after which the search is stopped, `None` when there is no limit
+    pub fn search_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(self.search_timeout_ms)).filter(|timeout| !timeout.is_zero())
+    }
+
+    /// Get the search directories, defaulting to current directory
+    pub fn search_dir(&self) -> Vec<String> {
+        if self.directory.is_empty() {
+            return vec![".".to_string()];
         }
+        self.directory
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect()
     }
 }
 
+/// Output formats supported by the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Interactive terminal interface
+    #[default]
+    Tui,
+    /// Newline-delimited JSON records on stdout
+    Json,
+}
+
 /// Search modes supported by the application
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SearchMode {
     Exact,
     IgnoreCase,
     Substring,
+    SmartCase,
+    Fuzzy,
 }
 
 impl SearchMode {
+    /// Search mode with the given name, as returned by `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            SearchMode::Exact,
+            SearchMode::IgnoreCase,
+            SearchMode::Substring,
+            SearchMode::SmartCase,
+            SearchMode::Fuzzy,
+        ]
+        .into_iter()
+        .find(|mode| mode.name().eq_ignore_ascii_case(name.trim()))
+    }
+
     /// Get the search mode as a string
     pub fn name(&self) -> &'static str {
         match self {
             SearchMode::Exact => "exact",
             SearchMode::IgnoreCase => "ignore_case",
             SearchMode::Substring => "substring",
+            SearchMode::SmartCase => "smart_case",
+            SearchMode::Fuzzy => "fuzzy",
         }
     }
 
@@ -147,10 +687,18 @@ impl SearchMode {
             SearchMode::Exact => "Exact whole word matches (case sensitive)",
             SearchMode::IgnoreCase => "Case insensitive search (default)",
             SearchMode::Substring => "Substring search (case sensitive)",
+            SearchMode::SmartCase => "Case insensitive unless the pattern contains uppercase",
+            SearchMode::Fuzzy => "Fuzzy search sorted by match score",
         }
     }
 }
 
+/// Checks if the path is a network share such as `\\server\share` or `//server/share`
+fn is_network_path(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.starts_with(r"\\") || path.starts_with("//")
+}
+
 #[cfg(test)]
 mod tests {
     // import everything from above
@@ -161,88 +709,368 @@ mod tests {
         exact: bool,
         ignore_case: bool,
         substring: bool,
-        directory: Option<PathBuf>,
+        directory: Vec<PathBuf>,
     ) -> Cli {
         Cli {
             pattern: pattern.to_string(),
             exact,
             ignore_case,
             substring,
+            smart_case: false,
+            fuzzy: false,
             directory,
+            context: 0,
+            file_types: vec![],
+            and_patterns: vec![],
+            or_patterns: vec![],
+            include: vec![],
+            exclude: vec![],
+            max_count: None,
+            max_results: None,
+            max_depth: None,
+            max_line_length: 0,
+            search_timeout_ms: 0,
+            hidden: false,
+            follow_symlinks: false,
+            no_ignore: false,
+            ignore_file: None,
+            max_filesize: None,
+            sort: None,
+            editor: None,
+            theme: None,
+            theme_file: None,
+            import_annotations: None,
+            hex: false,
+            render_markdown: false,
+            encoding: None,
+            no_dedup: false,
+            output: OutputFormat::Tui,
+            json: false,
+            print_config: false,
+            list_themes: false,
+            clear_history: false,
+            generate_completion: None,
             debug: false,
+            log_format: None,
         }
     }
 
+    #[test]
+    fn test_cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_repeated_directories() {
+        let cli = Cli::parse_from(["search-rs", "pattern", "-d", "src", "--directory", "tests"]);
+        assert_eq!(cli.search_dir(), vec!["src", "tests"]);
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert_eq!(cli.output, OutputFormat::Tui);
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--output", "json"]);
+        assert_eq!(cli.output, OutputFormat::Json);
+
+        assert!(Cli::try_parse_from(["search-rs", "pattern", "--output", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_file_types() {
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert!(cli.file_types.is_empty());
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "-t", "rs", "--type", "python"]);
+        assert_eq!(cli.file_types, vec!["rs", "python"]);
+    }
+
+    #[test]
+    fn test_parse_multi_patterns() {
+        let cli = Cli::parse_from(["search-rs", "foo"]);
+        assert_eq!(cli.multi_pattern(), None);
+
+        let cli = Cli::parse_from(["search-rs", "foo", "--and", "bar"]);
+        assert_eq!(
+            cli.multi_pattern(),
+            Some((MultiPatternMode::And, vec!["bar".to_string()]))
+        );
+
+        let cli = Cli::parse_from(["search-rs", "foo", "--or", "bar", "--or", "baz"]);
+        assert_eq!(
+            cli.multi_pattern(),
+            Some((
+                MultiPatternMode::Or,
+                vec!["bar".to_string(), "baz".to_string()]
+            ))
+        );
+
+        // AND and OR can't be mixed
+        assert!(Cli::try_parse_from(["search-rs", "foo", "--and", "a", "--or", "b"]).is_err());
+
+        let cli = Cli::parse_from(["search-rs", "foo", "--and", " "]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_include_exclude_globs() {
+        let cli = Cli::parse_from([
+            "search-rs",
+            "pattern",
+            "--include",
+            "*.rs",
+            "--include",
+            "*.toml",
+            "--exclude",
+            "target/**",
+        ]);
+        assert_eq!(cli.include, vec!["*.rs", "*.toml"]);
+        assert_eq!(cli.exclude, vec!["target/**"]);
+        assert!(cli.validate().is_ok());
+
+        // Unbalanced brackets are rejected
+        let mut cli = create_test_cli("pattern", false, false, false, vec![]);
+        cli.exclude = vec!["src/[abc.rs".to_string()];
+        let err = cli.validate().unwrap_err();
+        assert!(err.to_string().contains("src/[abc.rs"));
+    }
+
+    #[test]
+    fn test_encoding_validation() {
+        let cli = Cli::parse_from(["search-rs", "pattern", "--encoding", "latin1"]);
+        assert_eq!(cli.encoding.as_deref(), Some("latin1"));
+        assert!(cli.validate().is_ok());
+
+        let mut cli = create_test_cli("pattern", false, false, false, vec![]);
+        cli.encoding = Some("klingon".to_string());
+        let err = cli.validate().unwrap_err();
+        assert!(matches!(err, crate::SearchError::InvalidInput(_)));
+        assert!(err.to_string().contains("klingon"));
+    }
+
+    #[test]
+    fn test_print_config_without_pattern() {
+        let cli = Cli::parse_from(["search-rs", "--print-config"]);
+        assert!(cli.print_config);
+        assert_eq!(cli.pattern, "");
+
+        let cli = Cli::parse_from(["search-rs", "--list-themes"]);
+        assert!(cli.list_themes);
+
+        let cli = Cli::parse_from(["search-rs", "--clear-history"]);
+        assert!(cli.clear_history);
+
+        // Pattern is still required otherwise
+        assert!(Cli::try_parse_from(["search-rs"]).is_err());
+    }
+
+    #[test]
+    fn test_apply_config() {
+        let config = Config {
+            search_mode: Some(SearchMode::Substring),
+            directories: vec![PathBuf::from("/home/user/proj")],
+            exclude: vec!["*.lock".to_string()],
+            editor: Some("hx {file}:{line}".to_string()),
+            theme: Some("InspiredGitHub".to_string()),
+            hidden: true,
+            no_ignore: true,
+            ..Config::default()
+        };
+
+        let mut cli = create_test_cli("pattern", false, false, false, vec![]);
+        cli.apply_config(&config);
+        assert_eq!(cli.editor.as_deref(), Some("hx {file}:{line}"));
+        assert_eq!(cli.theme.as_deref(), Some("InspiredGitHub"));
+        assert!(cli.hidden);
+        assert!(cli.no_ignore);
+        assert!(!cli.follow_symlinks);
+        assert_eq!(cli.search_mode(), SearchMode::Substring);
+        assert_eq!(cli.search_dir(), vec!["/home/user/proj"]);
+        assert_eq!(cli.exclude, vec!["*.lock"]);
+
+        // Flags are kept over config values
+        let mut cli = create_test_cli("pattern", false, true, false, vec![PathBuf::from("src")]);
+        cli.apply_config(&config);
+        assert_eq!(cli.search_mode(), SearchMode::IgnoreCase);
+        assert!(cli.validate().is_ok());
+        assert_eq!(cli.search_dir(), vec!["src"]);
+    }
+
+    #[test]
+    fn test_parse_limits_and_hidden() {
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert_eq!(cli.max_count, None);
+        assert_eq!(cli.max_results, None);
+        assert_eq!(cli.max_depth, None);
+        assert_eq!(cli.search_timeout(), None);
+        assert!(!cli.hidden);
+
+        let cli = Cli::parse_from([
+            "search-rs",
+            "pattern",
+            "--max-count",
+            "50",
+            "--max-results",
+            "200",
+            "--max-depth",
+            "2",
+            "--search-timeout-ms",
+            "1500",
+            "--hidden",
+        ]);
+        assert_eq!(cli.max_count, Some(50));
+        assert_eq!(cli.max_results, Some(200));
+        assert_eq!(cli.max_depth, Some(2));
+        assert_eq!(cli.search_timeout(), Some(Duration::from_millis(1500)));
+        assert!(cli.hidden);
+        assert!(cli.validate().is_ok());
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--max-results", "0"]);
+        assert!(cli.validate().is_err());
+        let cli = Cli::parse_from(["search-rs", "pattern", "--max-count", "0"]);
+        assert!(cli.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_follow_symlinks_and_no_ignore() {
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert!(!cli.follow_symlinks);
+        assert!(!cli.no_ignore);
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "-L", "--no-ignore"]);
+        assert!(cli.follow_symlinks);
+        assert!(cli.no_ignore);
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--ignore-file", "ci/ignore"]);
+        assert_eq!(cli.ignore_file, Some(PathBuf::from("ci/ignore")));
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--max-filesize", "1048576"]);
+        assert_eq!(cli.max_filesize, Some(1_048_576));
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--import-annotations", "notes.tsv"]);
+        assert_eq!(cli.import_annotations, Some(PathBuf::from("notes.tsv")));
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--follow-symlinks"]);
+        assert!(cli.follow_symlinks);
+        assert!(cli.validate().is_ok());
+
+        assert!(is_network_path(Path::new(r"\\server\share")));
+        assert!(is_network_path(Path::new("//server/share")));
+        assert!(!is_network_path(Path::new("/home/user")));
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert!(!Cli::parse_from(["search-rs", "pattern"]).hex);
+        assert!(Cli::parse_from(["search-rs", "pattern", "--hex"]).hex);
+        assert!(Cli::parse_from(["search-rs", "pattern", "--render-markdown"]).render_markdown);
+    }
+
+    #[test]
+    fn test_parse_context_lines() {
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert_eq!(cli.context, 0);
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "-C", "3"]);
+        assert_eq!(cli.context, 3);
+
+        let cli = Cli::parse_from(["search-rs", "pattern", "--context", "2"]);
+        assert_eq!(cli.context, 2);
+    }
+
     #[test]
     fn test_signle_mode_validation() {
         // Single modes should be valid
-        let cli = create_test_cli("search pattern", true, false, false, None);
+        let cli = create_test_cli("search pattern", true, false, false, vec![]);
         assert!(cli.validate().is_ok());
 
-        let cli = create_test_cli("search pattern", false, true, false, None);
+        let cli = create_test_cli("search pattern", false, true, false, vec![]);
         assert!(cli.validate().is_ok());
 
-        let cli = create_test_cli("search pattern", false, false, true, None);
+        let cli = create_test_cli("search pattern", false, false, true, vec![]);
         assert!(cli.validate().is_ok());
 
         // Nothing is specified
-        let cli = create_test_cli("search pattern", false, false, false, None);
+        let cli = create_test_cli("search pattern", false, false, false, vec![]);
         assert!(cli.validate().is_ok());
     }
 
     #[test]
     fn test_multiple_mode_is_invalid() {
         // Multiple modes should not be valid
-        let cli = create_test_cli("search pattern", true, true, true, None);
+        let cli = create_test_cli("search pattern", true, true, true, vec![]);
         assert!(cli.validate().is_err());
 
-        let cli = create_test_cli("search pattern", true, true, false, None);
+        let cli = create_test_cli("search pattern", true, true, false, vec![]);
         assert!(cli.validate().is_err());
 
-        let cli = create_test_cli("search pattern", true, false, true, None);
+        let cli = create_test_cli("search pattern", true, false, true, vec![]);
+        assert!(cli.validate().is_err());
+
+        let mut cli = create_test_cli("search pattern", false, true, false, vec![]);
+        cli.fuzzy = true;
+        assert!(cli.validate().is_err());
+
+        let mut cli = create_test_cli("search pattern", false, false, false, vec![]);
+        cli.smart_case = true;
+        assert!(cli.validate().is_ok());
+        cli.exact = true;
         assert!(cli.validate().is_err());
     }
 
     #[test]
     fn test_empty_search_pattern_is_invalid() {
         // Empty search pattern should not be valid
-        let cli = create_test_cli("", true, false, false, None);
+        let cli = create_test_cli("", true, false, false, vec![]);
         assert!(cli.validate().is_err());
 
-        let cli = create_test_cli(" ", true, false, false, None);
+        let cli = create_test_cli(" ", true, false, false, vec![]);
         assert!(cli.validate().is_err());
 
-        let cli = create_test_cli("\t\n", true, false, false, None);
+        let cli = create_test_cli("\t\n", true, false, false, vec![]);
         assert!(cli.validate().is_err());
     }
 
     #[test]
     fn test_valid_pattern() {
         // Valid search pattern should be valid
-        let cli = create_test_cli("search pattern", true, false, false, None);
+        let cli = create_test_cli("search pattern", true, false, false, vec![]);
         assert!(cli.validate().is_ok());
     }
 
     #[test]
     fn test_get_search_mode() {
         // Exact mode
-        let cli = create_test_cli("search pattern", true, false, false, None);
+        let cli = create_test_cli("search pattern", true, false, false, vec![]);
         assert_eq!(cli.search_mode(), SearchMode::Exact);
 
         // Ignore case mode
-        let cli = create_test_cli("search pattern", false, true, false, None);
+        let cli = create_test_cli("search pattern", false, true, false, vec![]);
         assert_eq!(cli.search_mode(), SearchMode::IgnoreCase);
 
         // Substring mode
-        let cli = create_test_cli("search pattern", false, false, true, None);
+        let cli = create_test_cli("search pattern", false, false, true, vec![]);
         assert_eq!(cli.search_mode(), SearchMode::Substring);
+
+        // Smart case mode
+        let cli = Cli::parse_from(["search-rs", "Pattern", "--smart-case"]);
+        assert_eq!(cli.search_mode(), SearchMode::SmartCase);
+        assert_eq!(cli.search_mode().name(), "smart_case");
+        assert!(Cli::parse_from(["search-rs", "Pattern", "-S"]).smart_case);
+
+        // Fuzzy mode
+        let cli = Cli::parse_from(["search-rs", "fnmn", "-F"]);
+        assert_eq!(cli.search_mode(), SearchMode::Fuzzy);
+        assert!(cli.validate().is_ok());
     }
 
     #[test]
     fn test_searh_dir() {
         // Default directory
-        let cli = create_test_cli("search pattern", false, false, false, None);
-        assert_eq!(cli.search_dir(), ".");
+        let cli = create_test_cli("search pattern", false, false, false, vec![]);
+        assert_eq!(cli.search_dir(), vec!["."]);
 
         // Custom directory
         let cli = create_test_cli(
@@ -250,15 +1078,25 @@ mod tests {
             false,
             false,
             false,
-            Some(PathBuf::from("/path/to/dir")),
+            vec![PathBuf::from("/path/to/dir")],
+        );
+        assert_eq!(cli.search_dir(), vec!["/path/to/dir"]);
+
+        // Multiple directories keep their order
+        let cli = create_test_cli(
+            "search pattern",
+            false,
+            false,
+            false,
+            vec![PathBuf::from("/path/one"), PathBuf::from("/path/two")],
         );
-        assert_eq!(cli.search_dir(), "/path/to/dir");
+        assert_eq!(cli.search_dir(), vec!["/path/one", "/path/two"]);
     }
 
     #[test]
     fn test_search_mode_name_and_description() {
         // Exact mode
-        let cli = create_test_cli("search pattern", true, false, false, None);
+        let cli = create_test_cli("search pattern", true, false, false, vec![]);
         assert_eq!(cli.search_mode().name(), "exact");
         assert_eq!(
             cli.search_mode().description(),
@@ -266,7 +1104,7 @@ mod tests {
         );
 
         // Ignore case mode
-        let cli = create_test_cli("search pattern", false, true, false, None);
+        let cli = create_test_cli("search pattern", false, true, false, vec![]);
         assert_eq!(cli.search_mode().name(), "ignore_case");
         assert_eq!(
             cli.search_mode().description(),
@@ -274,7 +1112,7 @@ mod tests {
         );
 
         // Substring mode
-        let cli = create_test_cli("search pattern", false, false, true, None);
+        let cli = create_test_cli("search pattern", false, false, true, vec![]);
         assert_eq!(cli.search_mode().name(), "substring");
         assert_eq!(
             cli.search_mode().description(),
@@ -290,8 +1128,93 @@ mod tests {
             false,
             false,
             false,
-            Some(PathBuf::from("/path/to/dir/invalid")),
+            vec![PathBuf::from("/path/to/dir/invalid")],
+        );
+        assert!(cli.validate().is_err());
+
+        // One invalid directory among valid ones
+        let cli = create_test_cli(
+            "search pattern",
+            false,
+            false,
+            false,
+            vec![PathBuf::from("src"), PathBuf::from("/path/to/dir/invalid")],
         );
-        assert!(!cli.validate().is_ok());
+        assert!(cli.validate().is_err());
+
+        // All valid directories
+        let cli = create_test_cli(
+            "search pattern",
+            false,
+            false,
+            false,
+            vec![PathBuf::from("src"), PathBuf::from(".")],
+        );
+        assert!(cli.validate().is_ok());
+    }
+}
+
+/// Tests that set the `SEARCH_RS_*` variables, kept apart since the environment is
+/// shared by every test in the process
+#[cfg(test)]
+mod env_tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_env_vars() {
+        let directories = env::join_paths(["src", "tests"]).unwrap();
+        let vars = HashMap::from([
+            (PATTERN_ENV_VAR, "fn main".to_string()),
+            (DIRECTORY_ENV_VAR, directories.into_string().unwrap()),
+            (MODE_ENV_VAR, "smart_case".to_string()),
+            (THEME_ENV_VAR, "InspiredGitHub".to_string()),
+        ]);
+        let from_env = Cli::from_env_with(|name| vars.get(name).cloned());
+
+        assert_eq!(from_env.pattern, "fn main");
+        assert_eq!(from_env.search_dir(), vec!["src", "tests"]);
+        assert_eq!(from_env.explicit_search_mode(), Some(SearchMode::SmartCase));
+        assert_eq!(from_env.theme.as_deref(), Some("InspiredGitHub"));
+
+        // Unset and empty variables leave the defaults
+        for empty in [
+            Cli::from_env_with(|_| None),
+            Cli::from_env_with(|_| Some(String::new())),
+        ] {
+            assert_eq!(empty.pattern, "");
+            assert!(empty.directory.is_empty());
+            assert_eq!(empty.explicit_search_mode(), None);
+            assert_eq!(empty.theme, None);
+        }
+
+        // The environment only fills in what neither the flags nor the config set
+        let mut cli = Cli {
+            directory: vec![PathBuf::from("docs")],
+            ..Cli::default()
+        };
+        let config = Config {
+            theme: Some("Solarized (dark)".to_string()),
+            ..Config::default()
+        };
+        cli.apply_env_vars(&from_env, &config);
+        assert_eq!(cli.pattern, "fn main");
+        assert_eq!(cli.search_dir(), vec!["docs"]);
+        assert_eq!(cli.search_mode(), SearchMode::SmartCase);
+        assert_eq!(cli.theme, None);
+        cli.apply_config(&config);
+        assert_eq!(cli.theme.as_deref(), Some("Solarized (dark)"));
+
+        let mut cli = Cli::parse_from(["search-rs", "-i", "pattern"]);
+        let config = Config {
+            directories: vec![PathBuf::from("lib")],
+            ..Config::default()
+        };
+        cli.apply_env_vars(&from_env, &config);
+        assert_eq!(cli.pattern, "pattern");
+        assert_eq!(cli.search_mode(), SearchMode::IgnoreCase);
+        assert!(cli.directory.is_empty());
+        assert_eq!(cli.theme.as_deref(), Some("InspiredGitHub"));
     }
 }
@@ -0,0 +1,46 @@
+//! Shell completion scripts.
+//!
+//! Generates completion scripts for the command line flags with `clap_complete`.
+
+use crate::cli::Cli;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::{self, Write};
+
+/// Write the completion script for the shell
+pub fn generate_completions<W: Write>(shell: Shell, writer: &mut W) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, writer);
+}
+
+/// Print the completion script for the shell on stdout
+pub fn print_completions(shell: Shell) {
+    generate_completions(shell, &mut io::stdout().lock());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_completions_for_each_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut output = Vec::new();
+            generate_completions(shell, &mut output);
+
+            let script = String::from_utf8(output).unwrap();
+            assert!(!script.is_empty(), "Empty completions for {:?}", shell);
+            assert!(
+                script.contains("search-rs"),
+                "Missing command for {:?}",
+                shell
+            );
+            assert!(
+                script.contains("smart-case"),
+                "Missing flag for {:?}",
+                shell
+            );
+        }
+    }
+}
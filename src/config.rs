@@ -0,0 +1,505 @@
+//! Configuration file support.
+//!
+//! Loads user defaults from `$XDG_CONFIG_HOME/search-rs/config.toml`.
+//! Command line flags always take precedence over the config file.
+
+use crate::cli::{Cli, SearchMode};
+use crate::logging::LoggingConfig;
+use crate::tui::app::{PreviewMode, PreviewPosition};
+use crate::tui::keybindings::KeyBindings;
+use crate::{Result, SearchError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default number of highlighted lines kept in the cache
+pub const DEFAULT_CACHE_SIZE_LIMIT: usize = 1000;
+
+/// Default delay after the last keystroke before the search re-runs
+pub const DEFAULT_SEARCH_DEBOUNCE_MS: u64 = 300;
+
+/// Default minimum time between two searches started while typing
+pub const DEFAULT_MIN_SEARCH_INTERVAL_MS: u64 = 200;
+
+/// Default number of results the TUI takes in per frame
+pub const DEFAULT_LOAD_BATCH_SIZE: usize = 100;
+
+/// Default distance from the end of the results at which streamed results are drawn right away
+pub const DEFAULT_PROGRESSIVE_LOAD_THRESHOLD: usize = 50;
+
+/// Default strftime format of the modification time column
+pub const DEFAULT_MTIME_FORMAT: &str = "%Y-%m-%d";
+
+/// Default share of the results list width taken by the path column, in percent
+pub const DEFAULT_PATH_COLUMN_PERCENT: usize = 25;
+
+/// Default width of the line number column
+pub const DEFAULT_LINE_COLUMN_WIDTH: usize = 6;
+
+/// User defaults loaded from the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Search mode used when no mode flag is given
+    pub search_mode: Option<SearchMode>,
+    /// Directories searched when no `--directory` is given
+    pub directories: Vec<PathBuf>,
+    /// Globs that are always excluded
+    pub exclude: Vec<String>,
+    /// Syntax highlighting theme name
+    pub theme: Option<String>,
+    /// Editor command, `{file}` and `{line}` are replaced with the result location
+    pub editor: Option<String>,
+    /// Search hidden files and directories
+    pub hidden: bool,
+    /// Follow symbolic links
+    pub follow_symlinks: bool,
+    /// Don't respect `.gitignore` and other ignore files
+    pub no_ignore: bool,
+    /// Extra ignore file in `.gitignore` format, passed to ripgrep as `--ignore-file`
+    pub ignore_file: Option<PathBuf>,
+    /// Files larger than this many bytes are skipped
+    pub max_filesize: Option<u64>,
+    /// Preview binary files as a hex dump
+    pub hex: bool,
+    /// Render Markdown files in the preview
+    pub render_markdown: bool,
+    /// Maximum number of highlighted lines kept in the cache
+    pub cache_size_limit: usize,
+    /// Lines longer than this many bytes are skipped, 0 for no limit
+    pub max_line_length: usize,
+    /// Milliseconds to wait after typing stops before searching again
+    pub search_debounce_ms: u64,
+    /// Minimum milliseconds between two searches started while typing, 0 for no limit
+    pub min_search_interval_ms: u64,
+    /// Results added to the results list per frame while a search is running
+    pub load_batch_size: usize,
+    /// Results from a running search are drawn as they arrive once the selection comes
+    /// within this many results of the end of the loaded results
+    pub progressive_load_threshold: usize,
+    /// Show the modification time of each result in the results list
+    pub show_mtime: bool,
+    /// strftime format of the modification time column
+    pub mtime_format: String,
+    /// Show the commit and author that last changed each result line in the results list
+    pub show_git_blame: bool,
+    /// Number the results in the results list
+    pub show_result_numbers: bool,
+    /// Show a file type icon before each result, needs a Nerd Font
+    pub show_icons: bool,
+    /// Show results as aligned path, line number and content columns
+    pub column_layout: bool,
+    /// Share of the results list width taken by the path column, in percent
+    pub path_column_percent: usize,
+    /// Width of the line number column
+    pub line_column_width: usize,
+    /// Preview to the right of the results list or below it, saved when it is toggled
+    pub preview_position: PreviewPosition,
+    /// Preview next to the results, fullscreen or hidden, saved when it is cycled
+    pub preview_mode: PreviewMode,
+    /// Command whose output is previewed instead of the file, `{file}` and `{line}` are
+    /// replaced with the result location. It should print one line per line of the file
+    pub preview_command: Option<String>,
+    /// Keys bound to each action, actions left out keep their default keys
+    /// The single keys of the results list such as `j`, `k` or `]b` are fixed
+    pub keybindings: KeyBindings,
+    /// Debug log rotation
+    pub logging: LoggingConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            search_mode: None,
+            directories: Vec::new(),
+            exclude: Vec::new(),
+            theme: None,
+            editor: None,
+            hidden: false,
+            follow_symlinks: false,
+            no_ignore: false,
+            ignore_file: None,
+            max_filesize: None,
+            hex: false,
+            render_markdown: false,
+            cache_size_limit: DEFAULT_CACHE_SIZE_LIMIT,
+            max_line_length: 0,
+            search_debounce_ms: DEFAULT_SEARCH_DEBOUNCE_MS,
+            min_search_interval_ms: DEFAULT_MIN_SEARCH_INTERVAL_MS,
+            load_batch_size: DEFAULT_LOAD_BATCH_SIZE,
+            progressive_load_threshold: DEFAULT_PROGRESSIVE_LOAD_THRESHOLD,
+            show_mtime: false,
+            mtime_format: DEFAULT_MTIME_FORMAT.to_string(),
+            show_git_blame: false,
+            show_result_numbers: true,
+            show_icons: false,
+            column_layout: false,
+            path_column_percent: DEFAULT_PATH_COLUMN_PERCENT,
+            line_column_width: DEFAULT_LINE_COLUMN_WIDTH,
+            preview_position: PreviewPosition::default(),
+            preview_mode: PreviewMode::default(),
+            preview_command: None,
+            keybindings: KeyBindings::default(),
+            logging: LoggingConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Path of the config file, `None` if there is no config directory
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("search-rs").join("config.toml"))
+    }
+
+    /// Load the config file, using defaults when it does not exist
+    pub fn load() -> Result<Self> {
+        match Self::config_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load the config from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| SearchError::file_access_error(&path.to_string_lossy(), &e.to_string()))?;
+        Self::from_toml_str(&content).map_err(|e| {
+            SearchError::InvalidArguments(format!("Invalid config file {}: {}", path.display(), e))
+        })
+    }
+
+    /// Set top-level string keys of the config file, creating it if needed
+    /// The rest of the file is kept as written, with its comments
+    pub fn save_keys(keys: &[(&str, &str)]) -> Result<()> {
+        let path = Self::config_path().ok_or_else(|| {
+            SearchError::InvalidArguments("No config directory to save the config in".to_string())
+        })?;
+        Self::save_keys_to(&path, keys)
+    }
+
+    /// Set top-level string keys of a specific config file
+    pub fn save_keys_to(path: &Path, keys: &[(&str, &str)]) -> Result<()> {
+        let access_error = |e: std::io::Error| {
+            SearchError::file_access_error(&path.to_string_lossy(), &e.to_string())
+        };
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(access_error(e)),
+        };
+        let mut document = content.parse::<toml_edit::DocumentMut>().map_err(|e| {
+            SearchError::InvalidArguments(format!("Invalid config file {}: {}", path.display(), e))
+        })?;
+        for (key, value) in keys {
+            document[key] = toml_edit::value(*value);
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(access_error)?;
+        }
+        fs::write(path, document.to_string()).map_err(access_error)
+    }
+
+    /// Parse the config from TOML
+    pub fn from_toml_str(content: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Serialize the config to TOML
+    pub fn to_toml_string(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| {
+            SearchError::InvalidArguments(format!("Failed to serialize config: {}", e))
+        })
+    }
+
+    /// Resolve the config with command line flags taking precedence
+    /// Excluded globs from both are combined
+    pub fn merged_with(&self, cli: &Cli) -> Self {
+        let mut merged = self.clone();
+
+        if let Some(mode) = cli.explicit_search_mode() {
+            merged.search_mode = Some(mode);
+        }
+        if !cli.directory.is_empty() {
+            merged.directories = cli.directory.clone();
+        }
+        for glob in &cli.exclude {
+            if !merged.exclude.contains(glob) {
+                merged.exclude.push(glob.clone());
+            }
+        }
+        if cli.editor.is_some() {
+            merged.editor = cli.editor.clone();
+        }
+        if cli.theme.is_some() {
+            merged.theme = cli.theme.clone();
+        }
+        merged.hidden |= cli.hidden;
+        merged.follow_symlinks |= cli.follow_symlinks;
+        merged.no_ignore |= cli.no_ignore;
+        if cli.ignore_file.is_some() {
+            merged.ignore_file = cli.ignore_file.clone();
+        }
+        if cli.max_filesize.is_some() {
+            merged.max_filesize = cli.max_filesize;
+        }
+        merged.hex |= cli.hex;
+        merged.render_markdown |= cli.render_markdown;
+        if cli.max_line_length != 0 {
+            merged.max_line_length = cli.max_line_length;
+        }
+        if let Some(format) = cli.log_format {
+            merged.logging.format = format;
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogFormat;
+    use crate::tui::keybindings::KeySpec;
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::from_toml_str(
+            r#"
+            search_mode = "ignore_case"
+            directories = ["/home/user/proj"]
+            exclude = ["*.lock"]
+            theme = "InspiredGitHub"
+            editor = "code --goto {file}:{line}"
+            follow_symlinks = true
+            cache_size_limit = 500
+            max_line_length = 2000
+            search_debounce_ms = 150
+            min_search_interval_ms = 400
+            load_batch_size = 500
+            progressive_load_threshold = 200
+            show_mtime = true
+            mtime_format = "%d/%m/%Y"
+            show_git_blame = true
+            show_result_numbers = false
+            show_icons = true
+            column_layout = true
+            path_column_percent = 30
+            preview_position = "bottom"
+            preview_mode = "hidden"
+            preview_command = "bat --color=always --style=plain {file}"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.search_mode, Some(SearchMode::IgnoreCase));
+        assert_eq!(config.directories, vec![PathBuf::from("/home/user/proj")]);
+        assert_eq!(config.exclude, vec!["*.lock"]);
+        assert_eq!(config.theme.as_deref(), Some("InspiredGitHub"));
+        assert_eq!(config.editor.as_deref(), Some("code --goto {file}:{line}"));
+        assert!(config.follow_symlinks);
+        assert!(!config.hidden);
+        assert_eq!(config.cache_size_limit, 500);
+        assert_eq!(config.max_line_length, 2000);
+        assert_eq!(config.search_debounce_ms, 150);
+        assert_eq!(config.min_search_interval_ms, 400);
+        assert_eq!(config.load_batch_size, 500);
+        assert_eq!(config.progressive_load_threshold, 200);
+        assert!(config.show_mtime);
+        assert_eq!(config.mtime_format, "%d/%m/%Y");
+        assert!(config.show_git_blame);
+        assert!(!config.show_result_numbers);
+        assert!(config.show_icons);
+        assert!(config.column_layout);
+        assert_eq!(config.path_column_percent, 30);
+        assert_eq!(config.line_column_width, DEFAULT_LINE_COLUMN_WIDTH);
+        assert_eq!(config.preview_position, PreviewPosition::Bottom);
+        assert_eq!(config.preview_mode, PreviewMode::Hidden);
+        assert_eq!(
+            config.preview_command.as_deref(),
+            Some("bat --color=always --style=plain {file}")
+        );
+
+        // Missing keys fall back to defaults
+        let config = Config::from_toml_str("").unwrap();
+        assert_eq!(config, Config::default());
+        assert!(config.show_result_numbers);
+
+        // Unknown search modes are rejected
+        assert!(Config::from_toml_str(r#"search_mode = "telepathic""#).is_err());
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        fs::write(&path, "exclude = [\"*.min.js\"]\n").unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.exclude, vec!["*.min.js"]);
+
+        fs::write(&path, "cache_size_limit = \"lots\"\n").unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(err.to_string().contains("Invalid config file"));
+
+        assert!(Config::load_from(&dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_save_keys_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("search-rs").join("config.toml");
+
+        Config::save_keys_to(&path, &[("theme", "InspiredGitHub")]).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.theme.as_deref(), Some("InspiredGitHub"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "theme = \"InspiredGitHub\"\n"
+        );
+
+        // Only the saved keys change, comments and other keys are kept
+        let content = "# My settings\ntheme = \"base16-ocean.dark\"\nhidden = true\n\n[keybindings]\nquit = [\"Ctrl+Q\"]\n";
+        fs::write(&path, content).unwrap();
+        Config::save_keys_to(
+            &path,
+            &[("theme", "InspiredGitHub"), ("preview_mode", "hidden")],
+        )
+        .unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("# My settings\ntheme = \"InspiredGitHub\"\nhidden = true\n"));
+        assert!(saved.contains("quit = [\"Ctrl+Q\"]"));
+        assert!(!saved.contains("search_debounce_ms"));
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.preview_mode, PreviewMode::Hidden);
+        assert!(config.hidden);
+
+        // The preview layout is written with the names the config file reads
+        for (mode, position) in [
+            (PreviewMode::Normal, PreviewPosition::Right),
+            (PreviewMode::Fullscreen, PreviewPosition::Bottom),
+            (PreviewMode::Hidden, PreviewPosition::Right),
+        ] {
+            let keys = [
+                ("preview_mode", mode.name()),
+                ("preview_position", position.name()),
+            ];
+            Config::save_keys_to(&path, &keys).unwrap();
+            let config = Config::load_from(&path).unwrap();
+            assert_eq!(
+                (config.preview_mode, config.preview_position),
+                (mode, position)
+            );
+        }
+
+        // A file that isn't TOML is left alone
+        fs::write(&path, "theme = ").unwrap();
+        assert!(Config::save_keys_to(&path, &[("theme", "InspiredGitHub")]).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theme = ");
+    }
+
+    #[test]
+    fn test_cli_takes_precedence() {
+        let config = Config {
+            search_mode: Some(SearchMode::IgnoreCase),
+            directories: vec![PathBuf::from("/home/user/proj")],
+            exclude: vec!["*.lock".to_string()],
+            ..Config::default()
+        };
+
+        // Without flags the config is used as is
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert_eq!(config.merged_with(&cli), config);
+
+        let cli = Cli::parse_from([
+            "search-rs",
+            "pattern",
+            "-s",
+            "-d",
+            "src",
+            "--exclude",
+            "target/**",
+            "--editor",
+            "nvim +{line} {file}",
+            "--hidden",
+            "--theme",
+            "Solarized (dark)",
+            "--hex",
+            "--render-markdown",
+            "--ignore-file",
+            "ci/ignore",
+            "--max-filesize",
+            "4096",
+        ]);
+        let merged = config.merged_with(&cli);
+        assert!(merged.hidden);
+        assert_eq!(merged.ignore_file, Some(PathBuf::from("ci/ignore")));
+        assert_eq!(merged.max_filesize, Some(4096));
+        assert!(merged.hex);
+        assert!(merged.render_markdown);
+        assert_eq!(merged.theme.as_deref(), Some("Solarized (dark)"));
+        assert_eq!(merged.editor.as_deref(), Some("nvim +{line} {file}"));
+        assert_eq!(merged.search_mode, Some(SearchMode::Substring));
+        assert_eq!(merged.directories, vec![PathBuf::from("src")]);
+        assert_eq!(merged.exclude, vec!["*.lock", "target/**"]);
+    }
+
+    #[test]
+    fn test_parse_keybindings() {
+        let config = Config::from_toml_str(
+            r#"
+            [keybindings]
+            quit = ["ctrl+q"]
+            toggle_help = ["F1", "ctrl+h"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.keybindings.quit, vec![KeySpec::ctrl('q')]);
+        assert_eq!(config.keybindings.toggle_help.len(), 2);
+        // Actions left out keep their default keys
+        assert_eq!(
+            config.keybindings.move_next,
+            KeyBindings::default().move_next
+        );
+
+        assert!(Config::from_toml_str("[keybindings]\nquit = [\"hyper+q\"]").is_err());
+    }
+
+    #[test]
+    fn test_parse_logging() {
+        let config = Config::from_toml_str(
+            r#"
+            [logging]
+            format = "json"
+            max_log_files = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.logging.format, LogFormat::Json);
+        assert_eq!(config.logging.max_log_files, 2);
+        assert_eq!(
+            config.logging.max_log_size_bytes,
+            LoggingConfig::default().max_log_size_bytes
+        );
+
+        // The flag takes precedence over the config file
+        let cli = Cli::parse_from(["search-rs", "pattern", "--log-format", "text"]);
+        assert_eq!(config.merged_with(&cli).logging.format, LogFormat::Text);
+        let cli = Cli::parse_from(["search-rs", "pattern"]);
+        assert_eq!(config.merged_with(&cli).logging.format, LogFormat::Json);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let config = Config {
+            search_mode: Some(SearchMode::Fuzzy),
+            exclude: vec!["*.lock".to_string()],
+            ..Config::default()
+        };
+        let toml = config.to_toml_string().unwrap();
+        assert!(toml.contains("search_mode = \"fuzzy\""));
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config);
+    }
+}
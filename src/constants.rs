@@ -6,4 +6,20 @@ pub const PATTERN_MAX_LENGTH: usize = 1000;
 pub const MAX_PROBLEM_CHARS: usize = 50;
 pub const MAX_PATH_LENGTH: usize = 4096;
 pub const DEFAULT_TERMINAL_HEIGHT: usize = 100;
-pub const MAX_LINE_NUM_DIGITS: usize = 4;
\ No newline at end of file
+pub const PREVIEW_BORDER_SIZE: u16 = 2;
+pub const MAX_LINE_NUM_DIGITS: usize = 4;
+pub const BINARY_CHECK_BYTES: usize = 8192;
+pub const ENCODING_SAMPLE_BYTES: usize = 4096;
+pub const HEX_DUMP_BYTES_PER_LINE: usize = 16;
+pub const CSV_MAX_COLUMN_WIDTH: usize = 20;
+pub const SEARCH_HISTORY_LIMIT: usize = 100;
+pub const RELEVANCE_MTIME_WEIGHT: f64 = 0.5;
+pub const RELEVANCE_POSITION_WEIGHT: f64 = 0.3;
+pub const RELEVANCE_DEPTH_WEIGHT: f64 = 0.2;
+pub const MOUSE_SCROLL_LINES: usize = 3;
+pub const LARGE_DIRECTORY_FILE_COUNT: usize = 10_000;
+pub const CLIPBOARD_MESSAGE_SECS: u64 = 2;
+pub const MTIME_CHECK_INTERVAL_MS: u64 = 1000;
+pub const MIN_RIPGREP_VERSION: &str = "13.0.0";
+pub const CATASTROPHIC_BACKTRACKING_URL: &str =
+    "https://www.regular-expressions.info/catastrophic.html";
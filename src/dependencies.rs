@@ -3,21 +3,38 @@
 //! Checks for requuired external dependencies and provides
 //! installation instructions.
 
+use crate::constants::{LARGE_DIRECTORY_FILE_COUNT, MIN_RIPGREP_VERSION};
+use crate::logging::{debug_log, warn_log};
+use crate::search::engines::dot_ignore_files;
 use crate::{Result, SearchError};
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
 
 /// External tool dependencies required by the program.
 pub struct Dependencies {
     pub ripgrep: bool,
+    /// Fallback search tool used when ripgrep is missing
+    pub grep: bool,
+    /// Optional, used for syntax preview
+    pub bat: bool,
+    /// Optional, used for blame-based sorting
+    pub git: bool,
 }
 
 // struct
 impl Dependencies {
     /// Check if all required dependencies are installed.
+    /// Tools that are missing but not needed are reported by `warnings`
     pub fn check(&self) -> Result<Self> {
         // Succeed and return self
         let deps = Dependencies {
-            ripgrep: check_tool("rg"),
+            ripgrep: ripgrep_installed(),
+            grep: check_tool("grep"),
+            bat: check_tool("bat"),
+            git: check_tool("git"),
         };
 
         if !deps.all_present() {
@@ -26,18 +43,78 @@ impl Dependencies {
                 install_instructions: deps.install_instructions(),
             });
         }
+        if let Some(version) = deps.ripgrep.then(Self::ripgrep_version).flatten() {
+            check_ripgrep_version(&version)?;
+        }
         Ok(deps)
     }
 
+    /// Problems with the installed tools that don't stop the search, such as a missing
+    /// ripgrep that grep stands in for or a missing optional tool
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.ripgrep {
+            warnings.push("ripgrep (rg) not found, searching with grep instead".to_string());
+        } else if Self::ripgrep_version().is_none() {
+            warnings.push("Could not read the ripgrep version".to_string());
+        }
+        for tool in self.missing_optional_tools() {
+            warnings.push(format!("Optional tool not found: {}", tool));
+        }
+        warnings
+    }
+
+    /// Log the `.ignore` and `.rgignore` files ripgrep reads, and warn about large search
+    /// directories without one, generated directories in them are searched unless they are
+    /// ignored. Counting the files walks the directory, so this only runs with `--debug`
+    pub fn check_ignore_files(directories: &[String]) {
+        for directory in directories {
+            let ignore_files = dot_ignore_files(std::slice::from_ref(directory));
+            if !ignore_files.is_empty() {
+                debug_log(&format!("Ignore files in use: {:?}", ignore_files));
+            } else if is_large_directory(Path::new(directory)) {
+                warn_log(&format!(
+                    "{} has more than {} files and no .ignore file, add one to skip generated directories",
+                    directory, LARGE_DIRECTORY_FILE_COUNT
+                ));
+            }
+        }
+    }
+
+    /// Version of the installed ripgrep, `None` when it is missing or the version can't be read
+    /// Checked once per run
+    pub fn ripgrep_version() -> Option<Version> {
+        static VERSION: OnceLock<Option<Version>> = OnceLock::new();
+        VERSION
+            .get_or_init(|| {
+                let output = Command::new("rg").arg("--version").output().ok()?;
+                parse_ripgrep_version(&String::from_utf8_lossy(&output.stdout))
+            })
+            .clone()
+    }
+
+    /// Get list of missing optional tools, the program works without them
+    pub fn missing_optional_tools(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        if !self.bat {
+            missing.push("bat (syntax preview)".to_string());
+        }
+        if !self.git {
+            missing.push("git (blame-based sorting)".to_string());
+        }
+        missing
+    }
+
     /// Check if all required dependencies are installed.
+    /// grep stands in for ripgrep when only grep is installed
     pub fn all_present(&self) -> bool {
-        self.ripgrep
+        self.ripgrep || self.grep
     }
 
     /// Get list of missing dependencies.
     pub fn missing_tools(&self) -> Vec<String> {
         let mut missing = Vec::new();
-        if !self.ripgrep {
+        if !self.all_present() {
             missing.push("ripgrep (rg)".to_string());
         }
         missing
@@ -46,7 +123,7 @@ impl Dependencies {
     /// Get installation instructions.
     pub fn install_instructions(&self) -> String {
         let mut install = Vec::new();
-        if !self.ripgrep {
+        if !self.all_present() {
             install.push(get_ripgrep_install_instructions());
         }
         
@@ -57,6 +134,64 @@ impl Dependencies {
     }
 }
 
+/// Whether ripgrep is installed, checked once per run
+pub fn ripgrep_installed() -> bool {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+    *INSTALLED.get_or_init(|| check_tool("rg"))
+}
+
+/// Parse the version from the first line of `rg --version`, e.g. `ripgrep 14.1.0 (rev 1234)`
+fn parse_ripgrep_version(output: &str) -> Option<Version> {
+    let version = output.lines().next()?.split_whitespace().nth(1)?;
+    Version::parse(version).ok()
+}
+
+/// Fail when ripgrep is too old to support `--column` and `--pcre2`
+fn check_ripgrep_version(version: &Version) -> Result<()> {
+    let minimum = Version::parse(MIN_RIPGREP_VERSION).expect("valid minimum ripgrep version");
+    if *version < minimum {
+        return Err(SearchError::MissingDependency {
+            tool: format!("ripgrep (rg) {} or newer", MIN_RIPGREP_VERSION),
+            install_instructions: format!(
+                " ripgrep {} is installed, version {} or newer is required.\n\
+                  Upgrade ripgrep (rg) with your package manager or by running:\n\
+                  cargo install ripgrep\n",
+                version, MIN_RIPGREP_VERSION
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Whether the directory holds more than `LARGE_DIRECTORY_FILE_COUNT` files
+/// Stops counting at the limit, `.git` directories are skipped
+fn is_large_directory(directory: &Path) -> bool {
+    let mut pending: Vec<PathBuf> = vec![directory.to_path_buf()];
+    let mut count = 0;
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => {
+                    if entry.file_name() != ".git" {
+                        pending.push(entry.path());
+                    }
+                }
+                Ok(_) => {
+                    count += 1;
+                    if count > LARGE_DIRECTORY_FILE_COUNT {
+                        return true;
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+    false
+}
+
 /// Check if all required external dependencies are installed.
 fn check_tool(tool_name: &str) -> bool {
     Command::new(tool_name).arg("--version").output().is_ok()
@@ -64,11 +199,10 @@ fn check_tool(tool_name: &str) -> bool {
 
 /// Get installation instructions.
 fn get_ripgrep_install_instructions() -> String {
-    format!(
-        " ripgrep (rg) is required to run this program.\n\
-          Install ripgrep (rg) with your package manager or by running:\n\
-          cargo install ripgrep\n"
-    )
+    " ripgrep (rg) is required to run this program.\n\
+      Install ripgrep (rg) with your package manager or by running:\n\
+      cargo install ripgrep\n"
+        .to_string()
 }
 
 #[cfg(test)]
@@ -86,17 +220,22 @@ mod tests {
     fn test_missing_tools() {
         let deps = Dependencies {
             ripgrep: false,
+            grep: false,
+            bat: false,
+            git: false,
         };
         assert!(!deps.all_present());
         let missing = deps.missing_tools();
         assert!(missing.iter().any(|tool| tool.contains("ripgrep")));
-        
     }
 
     #[test]
     fn test_install_instructions() {
         let deps = Dependencies {
             ripgrep: false,
+            grep: false,
+            bat: false,
+            git: false,
         };
         let hints = deps.install_instructions();
         assert!(hints.contains("ripgrep"));
@@ -107,9 +246,87 @@ mod tests {
     fn test_all_present() {
         let deps = Dependencies {
             ripgrep: true,
+            grep: false,
+            bat: true,
+            git: true,
         };
         assert!(deps.all_present());
         let hints = deps.install_instructions();
         assert!(hints.contains("All required tools are installed."));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_grep_fallback() {
+        let deps = Dependencies {
+            ripgrep: false,
+            grep: true,
+            bat: false,
+            git: false,
+        };
+        assert!(deps.all_present());
+        assert!(deps.missing_tools().is_empty());
+    }
+
+    #[test]
+    fn test_parse_ripgrep_version() {
+        assert_eq!(
+            parse_ripgrep_version("ripgrep 14.1.1 (rev f08e57bec0)\n\nfeatures:+pcre2\n"),
+            Some(Version::new(14, 1, 1))
+        );
+        assert_eq!(
+            parse_ripgrep_version("ripgrep 11.0.2\n-SIMD -AVX (compiled)\n"),
+            Some(Version::new(11, 0, 2))
+        );
+        assert_eq!(parse_ripgrep_version(""), None);
+        assert_eq!(parse_ripgrep_version("ripgrep unknown"), None);
+    }
+
+    #[test]
+    fn test_check_ripgrep_version() {
+        assert!(check_ripgrep_version(&Version::new(13, 0, 0)).is_ok());
+        assert!(check_ripgrep_version(&Version::new(14, 1, 1)).is_ok());
+
+        let err = check_ripgrep_version(&Version::new(12, 1, 1)).unwrap_err();
+        let hints = err.get_recovery_suggestion().unwrap();
+        assert!(hints.contains("12.1.1"));
+        assert!(hints.contains("cargo install ripgrep"));
+    }
+
+    #[test]
+    fn test_missing_optional_tools() {
+        let deps = Dependencies {
+            ripgrep: true,
+            grep: true,
+            bat: false,
+            git: true,
+        };
+        assert!(deps.all_present());
+        assert_eq!(deps.missing_optional_tools(), ["bat (syntax preview)"]);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let deps = Dependencies {
+            ripgrep: false,
+            grep: true,
+            bat: true,
+            git: false,
+        };
+        assert_eq!(
+            deps.warnings(),
+            [
+                "ripgrep (rg) not found, searching with grep instead",
+                "Optional tool not found: git (blame-based sorting)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_large_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src").join("main.rs"), "fn main() {}\n").unwrap();
+        assert!(!is_large_directory(dir.path()));
+        assert!(!is_large_directory(&dir.path().join("missing")));
+    }
+}
@@ -0,0 +1,134 @@
+//! Opening search results in an external editor.
+//!
+//! The editor is a command template where `{file}` and `{line}` are replaced
+//! with the result location, e.g. `code --goto {file}:{line}` or `nvim +{line} {file}`.
+
+use crate::search::SearchResult;
+use crate::{Result, SearchError};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither the flags, the config nor `$EDITOR` set one
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// Pick the editor command: the configured one, then `$EDITOR`, then `vi`
+pub fn resolve_editor(configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// Build the editor arguments for a result, program first
+/// A command without a `{file}` placeholder gets vi-style `+{line} {file}` appended
+pub fn editor_args(result: &SearchResult, template: &str) -> Result<Vec<String>> {
+    let mut words: Vec<&str> = template.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(SearchError::editor_error(
+            template,
+            "editor command is empty",
+        ));
+    }
+    if !template.contains("{file}") {
+        words.extend(["+{line}", "{file}"]);
+    }
+
+    let line = result.line_number.to_string();
+    Ok(words
+        .into_iter()
+        .map(|word| {
+            word.replace("{file}", &result.file_path)
+                .replace("{line}", &line)
+        })
+        .collect())
+}
+
+/// Open the result in the editor and wait for it to exit
+pub fn open_in_editor(result: &SearchResult, template: &str) -> Result<()> {
+    let args = editor_args(result, template)?;
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(|e| SearchError::editor_error(template, &e.to_string()))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(SearchError::editor_error(
+            template,
+            &format!("editor exited with {}", status),
+        ))
+    }
+}
+
+/// Open the directory holding the file in the system file manager
+pub fn reveal_in_file_manager(path: &Path) -> Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    open::that(directory).map_err(|e| {
+        SearchError::SearchProcessError(format!(
+            "Failed to open the file manager for {}: {}",
+            directory.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_result() -> SearchResult {
+        SearchResult::builder()
+            .file_path("src/my file.rs")
+            .line_number(42)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build()
+    }
+
+    #[test]
+    fn test_editor_args_placeholders() {
+        let result = create_result();
+
+        assert_eq!(
+            editor_args(&result, "code --goto {file}:{line}").unwrap(),
+            vec!["code", "--goto", "src/my file.rs:42"]
+        );
+        assert_eq!(
+            editor_args(&result, "nvim +{line} {file}").unwrap(),
+            vec!["nvim", "+42", "src/my file.rs"]
+        );
+
+        // Plain commands open vi-style at the line
+        assert_eq!(
+            editor_args(&result, "vim").unwrap(),
+            vec!["vim", "+42", "src/my file.rs"]
+        );
+
+        assert!(editor_args(&result, "  ").is_err());
+    }
+
+    #[test]
+    fn test_resolve_editor() {
+        assert_eq!(resolve_editor(Some("hx {file}:{line}")), "hx {file}:{line}");
+        assert!(!resolve_editor(None).is_empty());
+    }
+
+    #[test]
+    fn test_open_in_editor_errors() {
+        let result = create_result();
+
+        let err = open_in_editor(&result, "search-rs-missing-editor {file}").unwrap_err();
+        assert!(matches!(err, SearchError::EditorError { .. }));
+
+        let err = open_in_editor(&result, "false {file}").unwrap_err();
+        assert!(err.to_string().contains("editor exited"));
+
+        assert!(open_in_editor(&result, "true {file}:{line}").is_ok());
+    }
+}
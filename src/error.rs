@@ -4,6 +4,7 @@
 
 use colored::*;
 use std::fmt;
+use std::time::Duration;
 
 /// Result type alias for the search application.
 pub type Result<T> = std::result::Result<T, SearchError>;
@@ -41,49 +42,76 @@ pub enum SearchError {
 
     /// Search process error.
     SearchProcessError(String),
+
+    /// Editor failed to start or exited with an error.
+    EditorError { command: String, reason: String },
+
+    /// Search was stopped before ripgrep finished.
+    Cancelled,
+
+    /// Search was stopped because it ran longer than `--search-timeout-ms`.
+    Timeout(Duration),
+
+    /// Search was requested too soon after the last one, it can start after the given wait.
+    RateLimit(Duration),
 }
 
 impl fmt::Display for SearchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let warn_msg: String;
-        match self {
+        let warn_msg = match self {
             SearchError::InvalidArguments(msg) => {
-                warn_msg = format!("Invalid arguments: {}", msg).to_string();
+                format!("Invalid arguments: {}", msg)
             }
             SearchError::MissingDependency {
                 tool,
                 install_instructions,
             } => {
-                warn_msg = format!(
+                format!(
                     "Missing dependency: {}\n Install instructions: {}",
                     tool, install_instructions
-                );
+                )
             }
             SearchError::IoError(err) => {
-                warn_msg = format!("IO error: {}", err);
+                format!("IO error: {}", err)
             }
             SearchError::TuiError(err) => {
-                warn_msg = format!("TUI error: {}", err);
+                format!("TUI error: {}", err)
             }
             SearchError::InvalidInput(err) => {
-                warn_msg = format!("Invalid input: {}", err);
+                format!("Invalid input: {}", err)
             }
             SearchError::InvalidPattern { pattern, reason } => {
-                warn_msg = format!("Invalid search pattern: {}\n reason: {}", pattern, reason);
+                format!("Invalid search pattern: {}\n reason: {}", pattern, reason)
             }
             SearchError::TerminalError(err) => {
-                warn_msg = format!(
+                format!(
                     "Terminal error: {}\n Try running in a proper terminal.",
                     err
-                );
+                )
             }
             SearchError::FileAccessError { path, reason } => {
-                warn_msg = format!("File access error: Path: {}\n Reason: {}", path, reason);
+                format!("File access error: Path: {}\n Reason: {}", path, reason)
             }
             SearchError::SearchProcessError(err) => {
-                warn_msg = format!("Search error: {}", err);
+                format!("Search error: {}", err)
             }
-        }
+            SearchError::EditorError { command, reason } => {
+                format!("Editor error: {}\n Reason: {}", command, reason)
+            }
+            SearchError::Cancelled => "Search cancelled".to_string(),
+            SearchError::Timeout(timeout) => {
+                format!(
+                    "Search timed out after {} ms, results are partial",
+                    timeout.as_millis()
+                )
+            }
+            SearchError::RateLimit(wait) => {
+                format!(
+                    "Search rate limited, next search in {} ms",
+                    wait.as_millis()
+                )
+            }
+        };
         write!(f, "{}", warn_msg.red().bold())
     }
 }
@@ -103,6 +131,22 @@ impl From<std::io::Error> for SearchError {
     }
 }
 
+impl From<SearchError> for std::io::Error {
+    fn from(err: SearchError) -> Self {
+        let kind = match err {
+            SearchError::IoError(err) => return err,
+            SearchError::InvalidArguments(_)
+            | SearchError::InvalidInput(_)
+            | SearchError::InvalidPattern { .. } => std::io::ErrorKind::InvalidInput,
+            SearchError::Cancelled => std::io::ErrorKind::Interrupted,
+            SearchError::Timeout(_) => std::io::ErrorKind::TimedOut,
+            SearchError::RateLimit(_) => std::io::ErrorKind::WouldBlock,
+            _ => std::io::ErrorKind::Other,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
 impl Clone for SearchError {
     fn clone(&self) -> Self {
         match self {
@@ -129,6 +173,13 @@ impl Clone for SearchError {
                 reason: reason.clone(),
             },
             SearchError::SearchProcessError(err) => SearchError::SearchProcessError(err.clone()),
+            SearchError::EditorError { command, reason } => SearchError::EditorError {
+                command: command.clone(),
+                reason: reason.clone(),
+            },
+            SearchError::Cancelled => SearchError::Cancelled,
+            SearchError::Timeout(timeout) => SearchError::Timeout(*timeout),
+            SearchError::RateLimit(wait) => SearchError::RateLimit(*wait),
         }
     }
 }
@@ -155,6 +206,14 @@ impl SearchError {
         }
     }
 
+    /// Create an editor error
+    pub fn editor_error(command: &str, reason: &str) -> Self {
+        SearchError::EditorError {
+            command: command.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
     /// Check if this error is recover
     pub fn is_recoverable(&self) -> bool {
         match self {
@@ -162,12 +221,15 @@ impl SearchError {
             SearchError::InvalidPattern { .. } => true,
             SearchError::SearchProcessError(_) => true,
             SearchError::FileAccessError { .. } => true,
+            SearchError::EditorError { .. } => true,
+            SearchError::Cancelled => true,
+            SearchError::Timeout(_) => true,
+            SearchError::RateLimit(_) => true,
             SearchError::MissingDependency { .. } => false,
             SearchError::TerminalError(_) => false,
             SearchError::IoError(_) => false,
             SearchError::TuiError(_) => false,
             SearchError::InvalidArguments(_) => false,
-            _ => true,
         }
     }
 
@@ -188,6 +250,13 @@ impl SearchError {
                 install_instructions,
                 ..
             } => Some(install_instructions.clone()),
+            SearchError::EditorError { .. } => {
+                Some("Set the editor with --editor or the editor key in config.toml.".to_string())
+            }
+            SearchError::Timeout(_) => Some(
+                "Raise --search-timeout-ms or search fewer directories, e.g. skip network mounts."
+                    .to_string(),
+            ),
             _ => None,
         }
     }
@@ -234,6 +303,56 @@ mod tests {
         assert!(err.to_string().contains("File access error:"));
         assert!(err.to_string().contains("Path:"));
         assert!(err.to_string().contains("Reason:"));
+
+        let err = SearchError::editor_error("nvim +{line} {file}", "not found");
+        assert!(err.to_string().contains("Editor error:"));
+        assert!(err.to_string().contains("not found"));
+        assert!(err.is_recoverable());
+        assert!(err.get_recovery_suggestion().is_some());
+
+        let err = SearchError::Cancelled;
+        assert!(err.to_string().contains("Search cancelled"));
+        assert!(err.is_recoverable());
+        assert!(err.get_recovery_suggestion().is_none());
+
+        let err = SearchError::Timeout(Duration::from_millis(1500));
+        assert!(err.to_string().contains("timed out after 1500 ms"));
+        assert!(err.is_recoverable());
+        assert!(err
+            .get_recovery_suggestion()
+            .unwrap()
+            .contains("--search-timeout-ms"));
+
+        let err = SearchError::RateLimit(Duration::from_millis(120));
+        assert!(err.to_string().contains("next search in 120 ms"));
+        assert!(err.is_recoverable());
+        assert_eq!(
+            std::io::Error::from(err).kind(),
+            std::io::ErrorKind::WouldBlock
+        );
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let err = std::io::Error::from(SearchError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "missing",
+        )));
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(err.to_string(), "missing");
+
+        let err = std::io::Error::from(SearchError::invalid_pattern("(", "unclosed group"));
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("unclosed group"));
+
+        // `?` converts in functions returning `std::io::Result`
+        fn cancelled() -> std::io::Result<()> {
+            Err(SearchError::Cancelled)?
+        }
+        assert_eq!(
+            cancelled().unwrap_err().kind(),
+            std::io::ErrorKind::Interrupted
+        );
     }
 
     #[test]
@@ -275,10 +394,7 @@ mod tests {
         }
         .is_recoverable());
         assert!(!SearchError::TerminalError("terminal error".to_string()).is_recoverable());
-        assert!(
-            !SearchError::IoError(std::io::Error::new(std::io::ErrorKind::Other, "io error"))
-                .is_recoverable()
-        );
+        assert!(!SearchError::IoError(std::io::Error::other("io error")).is_recoverable());
         assert!(!SearchError::TuiError("tui error".to_string()).is_recoverable());
     }
 
@@ -333,7 +449,7 @@ mod tests {
         let err = SearchError::InvalidArguments("args error".to_string());
         assert!(err.get_recovery_suggestion().is_none());
 
-        let err = SearchError::IoError(std::io::Error::new(std::io::ErrorKind::Other, "io error"));
+        let err = SearchError::IoError(std::io::Error::other("io error"));
         assert!(err.get_recovery_suggestion().is_none());
 
         let err = SearchError::TuiError("tui error".to_string());
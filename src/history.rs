@@ -0,0 +1,103 @@
+//! Search history file.
+//!
+//! Committed search patterns are kept in `$XDG_DATA_HOME/search-rs/history`,
+//! one pattern per line with the most recent last, so they can be recalled in
+//! later sessions.
+
+use crate::constants::SEARCH_HISTORY_LIMIT;
+use crate::{Result, SearchError};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Path of the history file, `None` if there is no data directory
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("search-rs").join("history"))
+}
+
+/// Read the patterns in the history file, oldest first
+/// A missing file is an empty history
+pub fn load(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Add a pattern at the end of the history file
+/// The file is rewritten with the most recent patterns once it grows past the limit
+pub fn append(path: &Path, pattern: &str) -> io::Result<()> {
+    // A pattern on several lines would be read back as several patterns
+    if pattern.contains('\n') {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", pattern)?;
+
+    let patterns = load(path)?;
+    if patterns.len() > SEARCH_HISTORY_LIMIT {
+        let recent = &patterns[patterns.len() - SEARCH_HISTORY_LIMIT..];
+        fs::write(path, recent.join("\n") + "\n")?;
+    }
+    Ok(())
+}
+
+/// Delete the history file
+pub fn clear(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(SearchError::file_access_error(
+            &path.to_string_lossy(),
+            &e.to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_history_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("search-rs").join("history");
+        assert!(load(&path).unwrap().is_empty());
+
+        append(&path, "fn main").unwrap();
+        append(&path, "two\nlines").unwrap();
+        append(&path, "TODO").unwrap();
+        assert_eq!(load(&path).unwrap(), vec!["fn main", "TODO"]);
+
+        clear(&path).unwrap();
+        assert!(!path.exists());
+        // Clearing twice is fine
+        clear(&path).unwrap();
+    }
+
+    #[test]
+    fn test_history_file_limit() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history");
+        for i in 0..SEARCH_HISTORY_LIMIT + 5 {
+            append(&path, &format!("pattern {}", i)).unwrap();
+        }
+
+        let patterns = load(&path).unwrap();
+        assert_eq!(patterns.len(), SEARCH_HISTORY_LIMIT);
+        assert_eq!(patterns[0], "pattern 5");
+        assert_eq!(
+            patterns.last().unwrap(),
+            &format!("pattern {}", SEARCH_HISTORY_LIMIT + 4)
+        );
+    }
+}
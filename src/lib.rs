@@ -4,9 +4,15 @@
 //! experience by orchestrating rip-grep
 //! while offering superior user control and preview capabilities
 
+pub mod batch;
 pub mod cli;
+pub mod completions;
+pub mod config;
 pub mod dependencies;
+pub mod editor;
+pub mod history;
 pub mod logging;
+pub mod output;
 pub mod error;
 pub mod search;
 pub mod tui;
@@ -16,6 +22,7 @@ pub mod constants;
 
 // Re-export `Cli` for use from `main`
 pub use cli::Cli;
+pub use config::Config;
 pub use dependencies::Dependencies;
 pub use error::{Result, SearchError};
 pub use logging::init_debug_logging;
@@ -1,53 +1,81 @@
 //! Logging module for debug mode
 //!
 //! Provides logging module that writes to /tmp file
-//! with timestamps when --debug is specified
+//! with timestamps when --debug is specified.
+//! Log files over the size limit are rotated to `search-rs-debug.1.log`, `.2` and so on
 
-use log::{debug, error, info, trace, warn};
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::PathBuf;
+use clap::ValueEnum;
+use log::{debug, error, info, trace, warn, Record};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Once;
 
+/// Default number of rotated log files kept
+pub const DEFAULT_MAX_LOG_FILES: usize = 5;
+
+/// Default size of the log file before it is rotated, 10 MB
+pub const DEFAULT_MAX_LOG_SIZE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Layout of the debug log records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Log settings, the `[logging]` table of the config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Layout of the log records
+    pub format: LogFormat,
+    /// Number of rotated log files kept besides the current one
+    pub max_log_files: usize,
+    /// Size in bytes above which the log file is rotated on startup
+    pub max_log_size_bytes: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            max_log_files: DEFAULT_MAX_LOG_FILES,
+            max_log_size_bytes: DEFAULT_MAX_LOG_SIZE_BYTES,
+        }
+    }
+}
+
 // run once in a singke thread. this prevents race conditions
 static INIT: Once = Once::new();
 
 /// Initializes logging module when debug mode is enabled
 /// Creates a file in /tmp directory and sets up logger with timestamps
-pub fn init_debug_logging() -> crate::Result<PathBuf> {
+/// Earlier sessions are appended to until the file is over the size limit
+pub fn init_debug_logging(config: &LoggingConfig) -> crate::Result<PathBuf> {
     let mut log_path = std::env::temp_dir();
     log_path.push("search-rs-debug.log");
 
-    // Create or truncate the log file
-    let log_file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&log_path)
-        .map_err(|e| crate::SearchError::FileAccessError {
+    let log_file =
+        open_log_file(&log_path, config).map_err(|e| crate::SearchError::FileAccessError {
             path: log_path.to_string_lossy().to_string(),
             reason: format!("Failed to create log file: {}", e),
         })?;
 
     // Initialize env_logger to write log file
+    let format = config.format;
     INIT.call_once(move || {
         env_logger::Builder::new()
             .filter_level(log::LevelFilter::Debug) // debug and above
             .filter_module("crossterm", log::LevelFilter::Warn)
             .filter_module("ratatui", log::LevelFilter::Warn)
             .target(env_logger::Target::Pipe(Box::new(log_file))) // pipe console to file
-            .format(|buf, record| {
-                // format log message
-                writeln!(
-                    buf,
-                    "{} [{}] {}:{} - {}",
-                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S.%3f UTC"),
-                    record.level(),
-                    record.file().unwrap_or("unknown"),
-                    record.line().unwrap_or(0),
-                    record.args()
-                )
-            })
+            .format(move |buf, record| write_record(buf, format, record))
             .init();
     });
 
@@ -57,6 +85,81 @@ pub fn init_debug_logging() -> crate::Result<PathBuf> {
     Ok(log_path)
 }
 
+/// Write a log record as a single line in the format
+fn write_record<W: Write>(buf: &mut W, format: LogFormat, record: &Record) -> io::Result<()> {
+    match format {
+        LogFormat::Text => writeln!(
+            buf,
+            "{} [{}] {}:{} - {}",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S.%3f UTC"),
+            record.level(),
+            record.file().unwrap_or("unknown"),
+            record.line().unwrap_or(0),
+            record.args()
+        ),
+        LogFormat::Json => {
+            let json = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                "level": record.level().as_str(),
+                "file": record.file().unwrap_or("unknown"),
+                "line": record.line().unwrap_or(0),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", json)
+        }
+    }
+}
+
+/// Rotate the log file if needed, then open it for appending
+fn open_log_file(log_path: &Path, config: &LoggingConfig) -> io::Result<fs::File> {
+    rotate_log_files(log_path, config)?;
+    OpenOptions::new().create(true).append(true).open(log_path)
+}
+
+/// Path of the `index`th rotated log file, `search-rs-debug.log` becomes `search-rs-debug.1.log`
+pub fn rotated_log_path(log_path: &Path, index: usize) -> PathBuf {
+    let stem = log_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match log_path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, index),
+    };
+    log_path.with_file_name(name)
+}
+
+/// Move the log file to `.1` when it is over the size limit, shifting older files up
+/// The oldest file past `max_log_files` is removed, returns whether the log was rotated
+pub fn rotate_log_files(log_path: &Path, config: &LoggingConfig) -> io::Result<bool> {
+    let size = match fs::metadata(log_path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if size <= config.max_log_size_bytes as u64 {
+        return Ok(false);
+    }
+
+    if config.max_log_files == 0 {
+        fs::remove_file(log_path)?;
+        return Ok(true);
+    }
+
+    let oldest = rotated_log_path(log_path, config.max_log_files);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..config.max_log_files).rev() {
+        let path = rotated_log_path(log_path, index);
+        if path.exists() {
+            fs::rename(&path, rotated_log_path(log_path, index + 1))?;
+        }
+    }
+    fs::rename(log_path, rotated_log_path(log_path, 1))?;
+    Ok(true)
+}
+
 /// Log a debug message if debug mode is enabled
 pub fn debug_log(msg: &str) {
     debug!("{}", msg);
@@ -81,3 +184,139 @@ pub fn error_log(msg: &str) {
 pub fn trace_log(msg: &str) {
     trace!("{}", msg);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn config(max_log_files: usize, max_log_size_bytes: usize) -> LoggingConfig {
+        LoggingConfig {
+            max_log_files,
+            max_log_size_bytes,
+            ..LoggingConfig::default()
+        }
+    }
+
+    fn write_records(log_path: &Path, format: LogFormat) {
+        let mut file = open_log_file(log_path, &LoggingConfig::default()).unwrap();
+        for (level, message) in [
+            (log::Level::Info, "Debug logging initialized"),
+            (log::Level::Warn, "quote \" and\nnewline"),
+        ] {
+            write_record(
+                &mut file,
+                format,
+                &Record::builder()
+                    .args(format_args!("{}", message))
+                    .level(level)
+                    .file(Some("src/main.rs"))
+                    .line(Some(42))
+                    .build(),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_json_log_records() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("search-rs-debug.log");
+        write_records(&log_path, LogFormat::Json);
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let records: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["level"], "INFO");
+        assert_eq!(records[0]["file"], "src/main.rs");
+        assert_eq!(records[0]["line"], 42);
+        assert_eq!(records[0]["message"], "Debug logging initialized");
+        assert!(records[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(records[1]["message"], "quote \" and\nnewline");
+    }
+
+    #[test]
+    fn test_text_log_records() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("search-rs-debug.log");
+        write_records(&log_path, LogFormat::Text);
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let first = content.lines().next().unwrap();
+        assert!(first.ends_with(" UTC [INFO] src/main.rs:42 - Debug logging initialized"));
+    }
+
+    #[test]
+    fn test_rotated_log_path() {
+        let log_path = Path::new("/tmp/search-rs-debug.log");
+        assert_eq!(
+            rotated_log_path(log_path, 2),
+            PathBuf::from("/tmp/search-rs-debug.2.log")
+        );
+        assert_eq!(
+            rotated_log_path(Path::new("/tmp/debug"), 1),
+            PathBuf::from("/tmp/debug.1")
+        );
+    }
+
+    #[test]
+    fn test_small_log_is_appended_to() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("search-rs-debug.log");
+        fs::write(&log_path, "previous session\n").unwrap();
+
+        let mut file = open_log_file(&log_path, &config(5, 1024)).unwrap();
+        writeln!(file, "new session").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&log_path).unwrap(),
+            "previous session\nnew session\n"
+        );
+        assert!(!rotated_log_path(&log_path, 1).exists());
+    }
+
+    #[test]
+    fn test_rotate_log_files() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("search-rs-debug.log");
+        let config = config(2, 4);
+
+        // Nothing to rotate without a log file
+        assert!(!rotate_log_files(&log_path, &config).unwrap());
+
+        for session in ["first", "second", "third"] {
+            fs::write(&log_path, session).unwrap();
+            assert!(rotate_log_files(&log_path, &config).unwrap());
+            assert!(!log_path.exists());
+        }
+
+        // The oldest session was dropped past `max_log_files`
+        assert_eq!(
+            fs::read_to_string(rotated_log_path(&log_path, 1)).unwrap(),
+            "third"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_log_path(&log_path, 2)).unwrap(),
+            "second"
+        );
+        assert!(!rotated_log_path(&log_path, 3).exists());
+
+        // Logs at the limit are kept
+        fs::write(&log_path, "four").unwrap();
+        assert!(!rotate_log_files(&log_path, &config).unwrap());
+    }
+
+    #[test]
+    fn test_rotate_without_kept_files() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("search-rs-debug.log");
+        fs::write(&log_path, "too long").unwrap();
+
+        assert!(rotate_log_files(&log_path, &config(0, 4)).unwrap());
+        assert!(!log_path.exists());
+        assert!(!rotated_log_path(&log_path, 1).exists());
+    }
+}
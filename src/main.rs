@@ -1,5 +1,92 @@
-use search_rs::Cli;
+use search_rs::cli::OutputFormat;
+use search_rs::search::SearchEngine;
+use search_rs::tui::highlighter::SyntaxHighlighter;
+use search_rs::{init_debug_logging, Cli, Config, Dependencies, Result};
+use std::io::{self, IsTerminal};
+use std::process;
 
-fn main() {
-    println!("Hello, world!");
+#[tokio::main]
+async fn main() {
+    let mut cli = Cli::parse_args();
+
+    if let Err(e) = run(&mut cli).await {
+        eprintln!("{}", e);
+        if let Some(suggestion) = e.get_recovery_suggestion() {
+            eprintln!("{}", suggestion);
+        }
+        process::exit(1);
+    }
+}
+
+/// Validate the environment and launch the TUI or print JSON results
+/// Batch mode is used with `--json` or when stdout is not a terminal
+async fn run(cli: &mut Cli) -> Result<()> {
+    if cli.clear_history {
+        if let Some(path) = search_rs::history::history_path() {
+            search_rs::history::clear(&path)?;
+        }
+        println!("Search history cleared");
+        return Ok(());
+    }
+
+    if cli.list_themes {
+        for theme in SyntaxHighlighter::list_themes() {
+            println!("{}", theme);
+        }
+        return Ok(());
+    }
+
+    // Flags take precedence over the config file, which takes precedence over the
+    // environment variables
+    let file_config = Config::load()?;
+    let picked_theme = cli.theme.clone();
+    cli.apply_env_vars(&Cli::from_env_vars(), &file_config);
+    let config = file_config.merged_with(cli);
+    if cli.print_config {
+        print!("{}", config.to_toml_string()?);
+        return Ok(());
+    }
+
+    cli.apply_config(&config);
+    cli.validate()?;
+
+    // Remember a theme picked on the command line for later runs
+    if let Some(theme) = &picked_theme {
+        if file_config.theme.as_ref() != Some(theme) {
+            SyntaxHighlighter::with_theme(theme)?;
+            if let Err(e) = Config::save_keys(&[("theme", theme)]) {
+                eprintln!("Warning: could not save the theme: {}", e);
+            }
+        }
+    }
+
+    if cli.debug {
+        init_debug_logging(&config.logging)?;
+    }
+
+    let dependencies = Dependencies {
+        ripgrep: false,
+        grep: false,
+        bat: false,
+        git: false,
+    }
+    .check()?;
+    if cli.debug && !cli.no_ignore {
+        Dependencies::check_ignore_files(&cli.search_dir());
+    }
+
+    match cli.output {
+        OutputFormat::Json => search_rs::output::run_json_output(cli),
+        OutputFormat::Tui if cli.json || !io::stdout().is_terminal() => {
+            let engine = SearchEngine::from_cli(cli)?;
+            search_rs::batch::run_batch_mode(cli, &engine)
+        }
+        OutputFormat::Tui => {
+            // Left on the terminal when the TUI closes, the other modes keep stderr quiet
+            for warning in dependencies.warnings() {
+                eprintln!("Warning: {}", warning);
+            }
+            search_rs::tui::run(cli, &config).await
+        }
+    }
 }
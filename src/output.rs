@@ -0,0 +1,312 @@
+//! Non-interactive output module
+//!
+//! Streams search results to stdout as newline-delimited JSON for scripting
+//! and exports collected results to files
+
+use crate::cli::Cli;
+use crate::search::backend::SearchStream;
+use crate::search::multi::MultiPatternEngine;
+use crate::search::{SearchEngine, SearchResult};
+use crate::{Result, SearchError};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Results between progress updates
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// File format for exported results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One `path:line content` line per result
+    PlainText,
+    /// A JSON array of results
+    Json,
+}
+
+impl ExportFormat {
+    /// Pick the format from the file extension, JSON for `.json` and plain text otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::PlainText,
+        }
+    }
+}
+
+/// Run the search and print every result as a JSON line on stdout
+pub fn run_json_output(cli: &Cli) -> Result<()> {
+    let engine = SearchEngine::from_cli(cli)?;
+    run_json_lines(cli, &engine, io::sink())
+}
+
+/// Run the search and print every result as a JSON line on stdout
+/// Progress and a final summary go to `progress`
+pub fn run_json_lines<P: Write>(cli: &Cli, engine: &SearchEngine, progress: P) -> Result<()> {
+    let directories = cli.search_dir();
+
+    if let Some(multi) = MultiPatternEngine::from_cli(cli, engine) {
+        let results = multi.search(&cli.pattern, &directories)?;
+        let written = write_json_results(&results, io::stdout().lock(), progress, cli.max_results);
+        return ignore_broken_pipe(written);
+    }
+
+    let mut stream = engine.search(&cli.pattern, &directories)?;
+    stream.watch(engine.config.timeout);
+    let written = write_json_lines(&mut stream, io::stdout().lock(), progress, cli.max_results);
+
+    // Stop the search early if stdout was closed, e.g. piped into `head`,
+    // or the result limit was reached
+    if written.is_err() || written.as_ref().ok() == cli.max_results.as_ref() {
+        stream.stop();
+    } else {
+        stream.wait();
+    }
+    warn_if_timed_out(&stream, engine);
+    ignore_broken_pipe(written)
+}
+
+/// Tell the user the results are partial when the search ran past its timeout
+fn warn_if_timed_out(stream: &SearchStream, engine: &SearchEngine) {
+    if let Some(timeout) = engine.config.timeout.filter(|_| stream.timed_out()) {
+        eprintln!("{}", SearchError::Timeout(timeout));
+    }
+}
+
+/// Treat a closed stdout as success, e.g. when piped into `head`
+fn ignore_broken_pipe(written: Result<usize>) -> Result<()> {
+    match written {
+        Err(SearchError::IoError(e)) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        other => other.map(|_| ()),
+    }
+}
+
+/// Write one JSON record per search result as they arrive
+/// Progress and a final summary go to `progress`, stops after `limit` results
+/// Returns the number of results written
+pub fn write_json_lines<W: Write, P: Write>(
+    results: impl IntoIterator<Item = Result<SearchResult>>,
+    writer: W,
+    progress: P,
+    limit: Option<usize>,
+) -> Result<usize> {
+    let mut lines = JsonLinesWriter::new(writer, progress, limit);
+    for result in results {
+        if lines.is_full() {
+            break;
+        }
+        lines.write(&result?)?;
+    }
+    lines.finish()
+}
+
+/// Write collected results as JSON lines, like `write_json_lines`
+pub fn write_json_results<W: Write, P: Write>(
+    results: &[SearchResult],
+    writer: W,
+    progress: P,
+    limit: Option<usize>,
+) -> Result<usize> {
+    let mut lines = JsonLinesWriter::new(writer, progress, limit);
+    for result in results {
+        if lines.is_full() {
+            break;
+        }
+        lines.write(result)?;
+    }
+    lines.finish()
+}
+
+/// Writes JSON records and reports progress, counting results and files
+struct JsonLinesWriter<W: Write, P: Write> {
+    writer: W,
+    progress: P,
+    limit: usize,
+    count: usize,
+    files: HashSet<String>,
+}
+
+impl<W: Write, P: Write> JsonLinesWriter<W, P> {
+    fn new(writer: W, progress: P, limit: Option<usize>) -> Self {
+        Self {
+            writer,
+            progress,
+            limit: limit.unwrap_or(usize::MAX),
+            count: 0,
+            files: HashSet::new(),
+        }
+    }
+
+    /// Checks if the result limit was reached
+    fn is_full(&self) -> bool {
+        self.count >= self.limit
+    }
+
+    /// Write the record of a result
+    fn write(&mut self, result: &SearchResult) -> Result<()> {
+        write_record(&mut self.writer, result)?;
+        self.count += 1;
+        if !self.files.contains(&result.file_path) {
+            self.files.insert(result.file_path.clone());
+        }
+        if self.count.is_multiple_of(PROGRESS_INTERVAL) {
+            let _ = writeln!(self.progress, "{} results so far", self.count);
+        }
+        Ok(())
+    }
+
+    /// Flush the records and print the summary, returns the number of results written
+    fn finish(mut self) -> Result<usize> {
+        self.writer.flush()?;
+        let _ = writeln!(
+            self.progress,
+            "Found {} results in {} files",
+            self.count,
+            self.files.len()
+        );
+        Ok(self.count)
+    }
+}
+
+/// Write results in the export format
+pub fn write_results<W: Write>(
+    mut writer: W,
+    results: &[SearchResult],
+    format: ExportFormat,
+) -> Result<()> {
+    match format {
+        ExportFormat::PlainText => {
+            for result in results {
+                writeln!(writer, "{}", result.format_for_display(false))?;
+            }
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, results).map_err(io::Error::from)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a single result as a JSON line
+fn write_record<W: Write>(writer: &mut W, result: &SearchResult) -> Result<()> {
+    serde_json::to_writer(&mut *writer, result).map_err(io::Error::from)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::backend::ParsedOutput;
+    use crate::search::parser::RgOutputParser;
+
+    #[test]
+    fn test_write_json_lines() {
+        let input = "src/main.rs\u{0}1:4:fn main() {\n./src/lib.rs\u{0}7:1:main();\nnot a match\n";
+        let parser = RgOutputParser::new("main", &[".".to_string()], 0);
+        let mut output = Vec::new();
+
+        let mut progress = Vec::new();
+
+        let count = write_json_lines(
+            ParsedOutput::new(parser, input.as_bytes()),
+            &mut output,
+            &mut progress,
+            None,
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(progress).unwrap(),
+            "Found 2 results in 2 files\n"
+        );
+
+        let output = String::from_utf8(output).unwrap();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["file_path"], "src/main.rs");
+        assert_eq!(records[0]["line_number"], 1);
+        assert_eq!(records[0]["column"], 4);
+        assert_eq!(records[1]["display_path"], "src/lib.rs");
+
+        // Every line round-trips back into a result
+        let result: SearchResult = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(result.line_content, "fn main() {");
+
+        // The result limit stops writing early
+        let parser = RgOutputParser::new("main", &[".".to_string()], 0);
+        let mut output = Vec::new();
+        let count = write_json_lines(
+            ParsedOutput::new(parser, input.as_bytes()),
+            &mut output,
+            io::sink(),
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let results = vec![
+            SearchResult::builder()
+                .file_path("./src/main.rs")
+                .line_number(1)
+                .line_content("fn main() {")
+                .matched_text("main")
+                .build(),
+            SearchResult::builder()
+                .file_path("src/lib.rs")
+                .line_number(7)
+                .line_content("    main();")
+                .matched_text("main")
+                .build(),
+        ];
+
+        assert_eq!(
+            ExportFormat::from_path(Path::new("/tmp/out.JSON")),
+            ExportFormat::Json
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("/tmp/out.txt")),
+            ExportFormat::PlainText
+        );
+
+        let mut output = Vec::new();
+        write_results(&mut output, &results, ExportFormat::PlainText).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "src/main.rs:1 fn main() {\nsrc/lib.rs:7 main();\n"
+        );
+
+        let mut output = Vec::new();
+        write_results(&mut output, &results, ExportFormat::Json).unwrap();
+        let exported: Vec<SearchResult> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(exported, results);
+    }
+
+    #[test]
+    fn test_write_json_lines_flushes_context_match() {
+        let input = "a.rs\u{0}1:1:main\na.rs\u{0}2-after\n";
+        let parser = RgOutputParser::new("main", &[".".to_string()], 1);
+        let mut output = Vec::new();
+
+        let count = write_json_lines(
+            ParsedOutput::new(parser, input.as_bytes()),
+            &mut output,
+            io::sink(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        let record: serde_json::Value =
+            serde_json::from_str(String::from_utf8(output).unwrap().trim()).unwrap();
+        assert_eq!(record["context_after"][0], "after");
+    }
+}
@@ -3,18 +3,173 @@
 //! Handles file preview functionality using direct file buffer reading
 
 use crate::constants::*;
+use crate::tui::highlighter::SyntaxHighlighter;
+use crate::tui::markdown::render_markdown;
 use crate::{Result, SearchError};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Cell, Row, Table};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
+use std::process::{Command, Stdio};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Start of the notice shown instead of the contents of binary files
+const BINARY_NOTICE_PREFIX: &str = "[Binary file";
+
+/// Notice above the plain preview of a JSON file that failed to parse
+const INVALID_JSON_NOTICE: &str = "[Invalid JSON]";
+
+/// Extensions of files previewed as pretty-printed JSON
+const JSON_EXTENSIONS: [&str; 2] = ["json", "jsonc"];
+
+/// Extensions of Markdown files, rendered when `--render-markdown` is given
+const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+/// Extensions of files previewed as a table
+const TABLE_EXTENSIONS: [&str; 2] = ["csv", "tsv"];
+
+/// Width of the line number column of a table preview, the number and the target marker
+const CSV_GUTTER_WIDTH: usize = MAX_LINE_NUM_DIGITS + 1;
+
+/// Spaces between the columns of a table preview
+const CSV_COLUMN_SPACING: usize = 1;
+
+/// Rows of a CSV or TSV file that fit the table preview
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvTable {
+    /// Line numbers and cells of the rows, the first row is the header
+    pub rows: Vec<(usize, Vec<String>)>,
+    /// Widths of the columns that fit, the others are left out
+    pub widths: Vec<usize>,
+    /// Index into `rows` of the row of the target line
+    pub target: Option<usize>,
+}
+
+impl CsvTable {
+    /// Build the bordered table widget with a line number column, the header stays on top
+    /// and the first `scroll` rows under it are left out
+    /// The target row is highlighted, reversed when colors are off
+    pub fn widget(&self, scroll: usize, use_color: bool) -> Table<'static> {
+        let gutter_style = Style::default().fg(Color::DarkGray);
+        let row = |index: usize| {
+            let (line, cells) = &self.rows[index];
+            let is_target = self.target == Some(index);
+            let marker = if is_target { ">" } else { " " };
+            let gutter = Cell::from(format!(
+                "{:width$}{}",
+                line,
+                marker,
+                width = MAX_LINE_NUM_DIGITS
+            ))
+            .style(gutter_style);
+            let cells = self.widths.iter().enumerate().map(|(column, width)| {
+                let cell = cells.get(column).map_or("", String::as_str);
+                Cell::from(fit_cell(cell, *width))
+            });
+            let row = Row::new(std::iter::once(gutter).chain(cells));
+            match (is_target, use_color) {
+                (false, _) => row,
+                (true, true) => row.style(Style::default().bg(Color::Rgb(64, 64, 64))),
+                (true, false) => row.style(Style::default().add_modifier(Modifier::REVERSED)),
+            }
+        };
+
+        let widths = std::iter::once(CSV_GUTTER_WIDTH)
+            .chain(self.widths.iter().copied())
+            .map(|width| Constraint::Length(width as u16));
+        let body = (1..self.rows.len()).skip(scroll).map(row);
+        let mut table = Table::new(body, widths)
+            .column_spacing(CSV_COLUMN_SPACING as u16)
+            .block(Block::bordered());
+        if !self.rows.is_empty() {
+            let header = row(0);
+            table = table.header(header.add_modifier(Modifier::BOLD));
+        }
+        table
+    }
+
+    /// Line numbers of the first and last row under the header shown in `height` lines
+    pub fn line_range(&self, scroll: usize, height: usize) -> Option<(usize, usize)> {
+        let body = self.rows.get(1..)?.iter().skip(scroll);
+        let mut shown = body.take(height.saturating_sub(1)).map(|(line, _)| *line);
+        let first = shown.next()?;
+        Some((first, shown.next_back().unwrap_or(first)))
+    }
+}
 
 /// File preview handler using direct file buffer reading
-pub struct PreviewHandler;
+#[derive(Debug, Clone)]
+pub struct PreviewHandler {
+    /// Lines shown before the target line (None centers the target line)
+    context_size: Option<usize>,
+    /// Show binary files as a hex dump instead of a notice
+    hex_fallback: bool,
+    /// Encoding used for every file instead of detecting it
+    encoding: Option<&'static Encoding>,
+    /// Lines longer than this many characters are cut short
+    max_line_length: Option<usize>,
+    /// Show Markdown files rendered instead of their source
+    render_markdown: bool,
+    /// External command whose output is previewed instead of the file contents
+    preview_command: Option<String>,
+}
 
 impl PreviewHandler {
     /// Create a new preview handler
     pub fn new() -> Self {
-        Self
+        Self {
+            context_size: None,
+            hex_fallback: false,
+            encoding: None,
+            max_line_length: None,
+            render_markdown: false,
+            preview_command: None,
+        }
+    }
+
+    /// Set the encoding used for every file, `None` detects it per file
+    pub fn with_encoding(mut self, encoding: Option<&'static Encoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the length at which lines are cut short, `None` shows lines in full
+    pub fn with_max_line_length(mut self, max_line_length: Option<usize>) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    /// Set whether binary files are shown as a hex dump
+    pub fn with_hex_fallback(mut self, hex_fallback: bool) -> Self {
+        self.hex_fallback = hex_fallback;
+        self
+    }
+
+    /// Set whether Markdown files are shown rendered
+    pub fn with_render_markdown(mut self, render_markdown: bool) -> Self {
+        self.render_markdown = render_markdown;
+        self
+    }
+
+    /// Set the command whose output is previewed, `{file}` and `{line}` are replaced
+    pub fn with_preview_command(mut self, preview_command: Option<String>) -> Self {
+        self.preview_command = preview_command.filter(|command| !command.trim().is_empty());
+        self
+    }
+
+    /// Whether a preview command replaces the built-in previews
+    pub fn has_preview_command(&self) -> bool {
+        self.preview_command.is_some()
+    }
+
+    /// Set how many lines are shown before the target line
+    pub fn with_context_size(mut self, context_size: Option<usize>) -> Self {
+        self.context_size = context_size;
+        self
     }
 
     /// Generate a preview for a file at specific line number with optional dimensions
@@ -36,73 +191,611 @@ impl PreviewHandler {
 
         // Calculate max lines from terminal dimensions
         let max_lines = terminal_dimensions
-            .map(|(_, height)| height as usize)
+            .map(|(_, height)| height)
             .unwrap_or(DEFAULT_TERMINAL_HEIGHT);
 
+        if let Some(command) = &self.preview_command {
+            let output = Self::run_preview_command(command, file_path, line_number)?;
+            let lines = output.lines().map(|line| Ok(line.to_string()));
+            return self.format_lines(lines, line_number, max_lines);
+        }
+
+        // Binary files can't be shown as text lines, UTF-16 text has null bytes too
+        let encoding = self.encoding.or_else(|| Self::detect_encoding(file_path));
+        let is_utf16 =
+            encoding.is_some_and(|encoding| encoding == UTF_16LE || encoding == UTF_16BE);
+        if encoding.is_none() || (!is_utf16 && Self::is_binary(file_path)) {
+            if self.hex_fallback {
+                return Self::preview_binary(file_path, max_lines);
+            }
+            let size = fs::metadata(file_path)?.len();
+            return Ok(format!(
+                "{} — {} bytes — use --hex to view]\n",
+                BINARY_NOTICE_PREFIX, size
+            ));
+        }
+
+        // Files in another encoding are decoded before they are split into lines
+        if let Some(encoding) = encoding.filter(|&encoding| encoding != UTF_8) {
+            return self.preview_file_with_encoding(
+                file_path,
+                line_number,
+                terminal_dimensions,
+                encoding,
+            );
+        }
+
+        if Self::is_json(file_path) {
+            return self.preview_json(file_path, line_number, terminal_dimensions);
+        }
+        self.preview_text(file_path, line_number, max_lines)
+    }
+
+    /// Run the preview command for a file and return its output without ANSI escape sequences
+    fn run_preview_command(
+        template: &str,
+        file_path: &Path,
+        line_number: Option<usize>,
+    ) -> Result<String> {
+        let file = file_path.to_string_lossy();
+        let line = line_number.unwrap_or(1).to_string();
+        let args: Vec<String> = template
+            .split_whitespace()
+            .map(|word| word.replace("{file}", &file).replace("{line}", &line))
+            .collect();
+
+        let output = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| {
+                SearchError::SearchProcessError(format!(
+                    "Failed to start preview command {}: {}",
+                    args[0], e
+                ))
+            })?;
+        if !output.status.success() {
+            return Err(SearchError::SearchProcessError(format!(
+                "Preview command {} exited with {}",
+                args[0], output.status
+            )));
+        }
+        let stdout = strip_ansi_escapes::strip(&output.stdout);
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    }
+
+    /// Preview the lines of a UTF-8 text file
+    fn preview_text(
+        &self,
+        file_path: &Path,
+        line_number: Option<usize>,
+        max_lines: usize,
+    ) -> Result<String> {
         // Open file and create buffer reader
         let file = File::open(file_path);
         if let Ok(file) = file {
             // this is not a condition, but a pattern matching
             let reader = BufReader::new(file);
+            self.format_lines(reader.lines(), line_number, max_lines)
+        } else {
+            Err(SearchError::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found: {}", file_path.display()),
+            )))
+        }
+    }
+
+    /// Whether the file is previewed as JSON
+    fn is_json(file_path: &Path) -> bool {
+        Self::has_extension(file_path, &JSON_EXTENSIONS)
+    }
+
+    /// Whether the file is previewed as rendered Markdown
+    pub fn renders_markdown(&self, file_path: &Path) -> bool {
+        self.render_markdown && Self::has_extension(file_path, &MARKDOWN_EXTENSIONS)
+    }
+
+    /// Whether the file is previewed as a table
+    pub fn is_table(file_path: &Path) -> bool {
+        Self::has_extension(file_path, &TABLE_EXTENSIONS)
+    }
+
+    /// Whether the extension of the file is one of the extensions, ignoring case
+    fn has_extension(file_path: &Path, extensions: &[&str]) -> bool {
+        file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()))
+    }
+
+    /// Read the rows of a CSV or TSV file shown in the table preview, the first row is the
+    /// header. Columns are at most `CSV_MAX_COLUMN_WIDTH` wide and the ones that don't fit
+    /// the width are left out
+    pub fn preview_csv<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        terminal_dimensions: Option<(usize, usize)>,
+    ) -> Result<CsvTable> {
+        let file_path = file_path.as_ref();
+        let (width, max_lines) =
+            terminal_dimensions.unwrap_or((usize::MAX, DEFAULT_TERMINAL_HEIGHT));
+        let invalid = |e: csv::Error| {
+            SearchError::InvalidInput(format!("Invalid CSV in {}: {}", file_path.display(), e))
+        };
+
+        let delimiter = if Self::has_extension(file_path, &["tsv"]) {
+            b'\t'
+        } else {
+            b','
+        };
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(delimiter)
+            .from_path(file_path)
+            .map_err(invalid)?;
+        // Rows past the last one that can be shown are not read
+        let last_line = line_number.unwrap_or(0) + max_lines;
+        let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(invalid)?;
+            let line = record
+                .position()
+                .map_or(0, |position| position.line() as usize);
+            if line > last_line {
+                break;
+            }
+            rows.push((line, record.iter().map(str::to_string).collect()));
+        }
+        let Some((header, body)) = rows.split_first() else {
+            return Ok(CsvTable::default());
+        };
+
+        // The header takes the first line
+        let body_height = max_lines.saturating_sub(1);
+        let target = line_number
+            .filter(|target_line| *target_line > header.0)
+            .and_then(|target_line| body.iter().position(|(line, _)| *line >= target_line));
+        let start = target.map_or(0, |target| {
+            let context_before = self
+                .context_size
+                .unwrap_or(body_height / 2)
+                .min(body_height.saturating_sub(1));
+            target.saturating_sub(context_before)
+        });
+        let visible = &body[start.min(body.len())..];
+        let visible = &visible[..body_height.min(visible.len())];
+
+        let column_count = std::iter::once(header)
+            .chain(visible)
+            .map(|(_, cells)| cells.len())
+            .max()
+            .unwrap_or(0);
+        let mut total_width = CSV_GUTTER_WIDTH;
+        let mut widths = Vec::new();
+        for column in 0..column_count {
+            let column_width = std::iter::once(header)
+                .chain(visible)
+                .filter_map(|(_, cells)| cells.get(column))
+                .map(|cell| cell.width())
+                .max()
+                .unwrap_or(0)
+                .clamp(1, CSV_MAX_COLUMN_WIDTH);
+            if total_width + CSV_COLUMN_SPACING + column_width > width && !widths.is_empty() {
+                break;
+            }
+            total_width += CSV_COLUMN_SPACING + column_width;
+            widths.push(column_width);
+        }
 
-            if let Some(target_line) = line_number {
-                // When we have a target line, show context around it
-                let context_before = max_lines / 2;
-
-                // max 0 to max 1
-                let start_line = target_line.saturating_sub(context_before).max(1);
-                let required_width = MAX_LINE_NUM_DIGITS;
-
-                // Use iterator chains for efficienct line processing with target line context
-                let results: std::result::Result<String, std::io::Error> = reader
-                    .lines()
-                    .skip(start_line.saturating_sub(1))
-                    .take(max_lines)
-                    .enumerate()
-                    .map(|(line_idx, line_result)| {
-                        let line_num = start_line + line_idx;
-                        let line = line_result?;
-                        let marker = if line_num == target_line { ">" } else { " " };
-                        Ok(format!(
-                            "{:width$}{}| {}\n",
-                            line_num,
-                            marker,
-                            line,
-                            width = required_width
-                        ))
+        let target = match target {
+            _ if line_number == Some(header.0) => Some(0),
+            Some(target) => Some(target - start + 1),
+            None => None,
+        };
+        Ok(CsvTable {
+            rows: std::iter::once(header).chain(visible).cloned().collect(),
+            widths,
+            target,
+        })
+    }
+
+    /// Generate a preview of a Markdown file rendered with styles, keeping the line number
+    /// gutter of the source lines and centering on the rendered line of the target line
+    pub fn preview_markdown<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        terminal_dimensions: Option<(usize, usize)>,
+        highlighter: &mut SyntaxHighlighter,
+    ) -> Result<Text<'static>> {
+        let max_lines = terminal_dimensions
+            .map(|(_, height)| height)
+            .unwrap_or(DEFAULT_TERMINAL_HEIGHT);
+        let source = fs::read_to_string(file_path)?;
+        let rendered = render_markdown(&source, highlighter);
+
+        // The target is the first rendered line at or after the target source line
+        let target = line_number.and_then(|target_line| {
+            rendered
+                .iter()
+                .position(|line| line.source_line.is_some_and(|source| source >= target_line))
+        });
+        let start = target.map_or(0, |target| {
+            let context_before = self
+                .context_size
+                .unwrap_or(max_lines / 2)
+                .min(max_lines.saturating_sub(1));
+            target.saturating_sub(context_before)
+        });
+
+        let gutter_style = Style::default().fg(Color::DarkGray);
+        let lines = rendered
+            .into_iter()
+            .enumerate()
+            .skip(start)
+            .take(max_lines)
+            .map(|(index, rendered)| {
+                let is_target = Some(index) == target;
+                let gutter = match rendered.source_line {
+                    Some(source_line) => format!(
+                        "{:width$}{}| ",
+                        source_line,
+                        if is_target { ">" } else { " " },
+                        width = MAX_LINE_NUM_DIGITS
+                    ),
+                    None => format!("{:width$}| ", "", width = MAX_LINE_NUM_DIGITS + 1),
+                };
+                let mut line = rendered.line;
+                line.spans.insert(0, Span::styled(gutter, gutter_style));
+                if is_target {
+                    for span in &mut line.spans {
+                        span.style = span.style.bg(Color::Rgb(64, 64, 64));
+                    }
+                }
+                line
+            })
+            .collect::<Vec<Line>>();
+        Ok(Text::from(lines))
+    }
+
+    /// Generate a preview of a JSON file pretty-printed, malformed JSON is shown as plain
+    /// text below a notice. The target line is only marked when pretty-printing kept the
+    /// lines of the file, otherwise the preview starts at the top
+    pub fn preview_json<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        terminal_dimensions: Option<(usize, usize)>,
+    ) -> Result<String> {
+        let file_path = file_path.as_ref();
+        let max_lines = terminal_dimensions
+            .map(|(_, height)| height)
+            .unwrap_or(DEFAULT_TERMINAL_HEIGHT);
+
+        let reader = BufReader::new(File::open(file_path)?);
+        let pretty = serde_json::from_reader::<_, serde_json::Value>(reader)
+            .ok()
+            .and_then(|value| serde_json::to_string_pretty(&value).ok());
+        let Some(pretty) = pretty else {
+            let preview = self.preview_text(file_path, line_number, max_lines.saturating_sub(1))?;
+            return Ok(format!("{}\n{}", INVALID_JSON_NOTICE, preview));
+        };
+
+        let line_number = line_number
+            .filter(|_| pretty.lines().count() == Self::count_lines(file_path).unwrap_or(0));
+        let lines = pretty.lines().map(|line| Ok(line.to_string()));
+        self.format_lines(lines, line_number, max_lines)
+    }
+
+    /// Generate a preview that fills the preview pane drawn in the layout area
+    pub fn preview_with_layout<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        layout_rect: Rect,
+    ) -> Result<String> {
+        self.preview_file(
+            file_path,
+            line_number,
+            Some(Self::layout_dimensions(layout_rect)),
+        )
+    }
+
+    /// Width and height of the text inside a bordered preview pane
+    pub fn layout_dimensions(layout_rect: Rect) -> (usize, usize) {
+        (
+            layout_rect.width.saturating_sub(PREVIEW_BORDER_SIZE) as usize,
+            layout_rect.height.saturating_sub(PREVIEW_BORDER_SIZE) as usize,
+        )
+    }
+
+    /// Generate a preview of a file decoded with the given encoding
+    pub fn preview_file_with_encoding<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        terminal_dimensions: Option<(usize, usize)>,
+        encoding: &'static Encoding,
+    ) -> Result<String> {
+        let max_lines = terminal_dimensions
+            .map(|(_, height)| height)
+            .unwrap_or(DEFAULT_TERMINAL_HEIGHT);
+
+        let bytes = fs::read(file_path)?;
+        let (text, _, _) = encoding.decode(&bytes);
+        let lines = text.lines().map(|line| Ok(line.to_string()));
+        self.format_lines(lines, line_number, max_lines)
+    }
+
+    /// Format the lines with a line number gutter, showing context around the target line
+    fn format_lines<I>(
+        &self,
+        lines: I,
+        line_number: Option<usize>,
+        max_lines: usize,
+    ) -> Result<String>
+    where
+        I: Iterator<Item = io::Result<String>>,
+    {
+        if let Some(target_line) = line_number {
+            // When we have a target line, show context around it
+            // The target line must stay within the visible lines
+            let context_before = self
+                .context_size
+                .unwrap_or(max_lines / 2)
+                .min(max_lines.saturating_sub(1));
+
+            // max 0 to max 1
+            let start_line = target_line.saturating_sub(context_before).max(1);
+            let required_width = MAX_LINE_NUM_DIGITS;
+
+            // Use iterator chains for efficienct line processing with target line context
+            let results: std::result::Result<String, std::io::Error> = lines
+                .skip(start_line.saturating_sub(1))
+                .take(max_lines)
+                .enumerate()
+                .map(|(line_idx, line_result)| {
+                    let line_num = start_line + line_idx;
+                    let line = self.truncate_line(line_result?);
+                    let marker = if line_num == target_line { ">" } else { " " };
+                    Ok(format!(
+                        "{:width$}{}| {}\n",
+                        line_num,
+                        marker,
+                        line,
+                        width = required_width
+                    ))
+                })
+                .collect::<std::result::Result<Vec<String>, _>>() //  Collect the results into a single vector
+                .map(|lines| lines.join("")); // Join the lines into a single string
+
+            results.map_err(SearchError::IoError)
+        } else {
+            // No target line, show from beginning
+            let results: std::result::Result<String, std::io::Error> = lines
+                .take(max_lines)
+                .enumerate()
+                .map(|(line_idx, line_result)| {
+                    let line_num = line_idx + 1;
+                    let line = self.truncate_line(line_result?);
+                    Ok(format!(
+                        "{:width$}| {}\n",
+                        line_num,
+                        line,
+                        width = MAX_LINE_NUM_DIGITS
+                    ))
+                })
+                .collect::<std::result::Result<Vec<String>, _>>() //  Collect the results into a single vector
+                .map(|lines| lines.join("")); // Join the lines into a single string
+
+            results.map_err(SearchError::IoError)
+        }
+    }
+
+    /// Cut the line short at the maximum line length, noting where it was cut
+    fn truncate_line(&self, mut line: String) -> String {
+        let Some(max_line_length) = self.max_line_length else {
+            return line;
+        };
+        if let Some((end, _)) = line.char_indices().nth(max_line_length) {
+            line.truncate(end);
+            line.push_str(&format!("… [line truncated at {} chars]", max_line_length));
+        }
+        line
+    }
+
+    /// Look up an encoding by its label, e.g. "latin1", "shift_jis" or "utf-16le"
+    pub fn encoding_for_label(label: &str) -> Result<&'static Encoding> {
+        Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+            SearchError::InvalidInput(format!(
+                "Unknown encoding '{}', expected a label such as utf-8, latin1, shift_jis or utf-16le",
+                label
+            ))
+        })
+    }
+
+    /// Guess the text encoding from the start of a file
+    /// Returns `None` for binary files and files that can't be read
+    pub fn detect_encoding(path: &Path) -> Option<&'static Encoding> {
+        let mut sample = Vec::with_capacity(ENCODING_SAMPLE_BYTES);
+        File::open(path)
+            .ok()?
+            .take(ENCODING_SAMPLE_BYTES as u64)
+            .read_to_end(&mut sample)
+            .ok()?;
+
+        if let Some((encoding, _)) = Encoding::for_bom(&sample) {
+            return Some(encoding);
+        }
+        match std::str::from_utf8(&sample) {
+            Ok(_) => return Some(UTF_8),
+            // The sample may end in the middle of a character
+            Err(e) if e.error_len().is_none() => return Some(UTF_8),
+            Err(_) => {}
+        }
+
+        // Control characters other than whitespace mean binary data
+        let is_control = |byte: &u8| {
+            (*byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || *byte == 0x7f
+        };
+        if sample.iter().any(is_control) {
+            return None;
+        }
+
+        let is_shift_jis = |bytes: &[u8]| {
+            SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .is_some()
+        };
+        // The sample may end in the middle of a two byte character
+        if is_shift_jis(&sample)
+            || (sample.len() == ENCODING_SAMPLE_BYTES && is_shift_jis(&sample[..sample.len() - 1]))
+        {
+            return Some(SHIFT_JIS);
+        }
+        Some(WINDOWS_1252)
+    }
+
+    /// Count the lines of a file, a last line without a newline counts too
+    pub fn count_lines<P: AsRef<Path>>(file_path: P) -> Result<usize> {
+        let mut reader = BufReader::new(File::open(file_path)?);
+        let mut count = 0;
+        let mut ends_with_newline = true;
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+            count += buffer.iter().filter(|&&byte| byte == b'\n').count();
+            ends_with_newline = buffer.last() == Some(&b'\n');
+            let length = buffer.len();
+            reader.consume(length);
+        }
+        if !ends_with_newline {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Whether the start of a file contains a null byte, unreadable files are not binary
+    pub fn is_binary(path: &Path) -> bool {
+        let mut buffer = Vec::with_capacity(BINARY_CHECK_BYTES);
+        File::open(path)
+            .and_then(|file| {
+                file.take(BINARY_CHECK_BYTES as u64)
+                    .read_to_end(&mut buffer)
+            })
+            .is_ok()
+            && buffer.contains(&0)
+    }
+
+    /// Generate a hex dump preview of the start of a file
+    /// Each line shows the offset, 16 hex bytes and their printable ASCII characters
+    pub fn preview_binary<P: AsRef<Path>>(file_path: P, max_lines: usize) -> Result<String> {
+        let mut buffer = Vec::new();
+        File::open(file_path.as_ref())?
+            .take((max_lines * HEX_DUMP_BYTES_PER_LINE) as u64)
+            .read_to_end(&mut buffer)?;
+
+        let lines: Vec<String> = buffer
+            .chunks(HEX_DUMP_BYTES_PER_LINE)
+            .enumerate()
+            .map(|(chunk_idx, chunk)| {
+                let hex: Vec<String> = (0..HEX_DUMP_BYTES_PER_LINE)
+                    .map(|idx| match chunk.get(idx) {
+                        Some(byte) => format!("{:02x}", byte),
+                        None => "  ".to_string(),
                     })
-                    .collect::<std::result::Result<Vec<String>, _>>() //  Collect the results into a single vector
-                    .map(|lines| lines.join("")); // Join the lines into a single string
-
-                results.map_err(SearchError::IoError)
-            } else {
-                // No target line, show from beginning
-                let results: std::result::Result<String, std::io::Error> = reader
-                    .lines()
-                    .take(max_lines)
-                    .enumerate()
-                    .map(|(line_idx, line_result)| {
-                        let line_num = line_idx + 1;
-                        let line = line_result?;
-                        Ok(format!(
-                            "{:width$}| {}\n",
-                            line_num,
-                            line,
-                            width = MAX_LINE_NUM_DIGITS
-                        ))
+                    .collect();
+                let ascii: String = chunk
+                    .iter()
+                    .map(|&byte| {
+                        if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        }
                     })
-                    .collect::<std::result::Result<Vec<String>, _>>() //  Collect the results into a single vector
-                    .map(|lines| lines.join("")); // Join the lines into a single string
+                    .collect();
+                format!(
+                    "{:08x}  {}  {}  |{}|\n",
+                    chunk_idx * HEX_DUMP_BYTES_PER_LINE,
+                    hex[..8].join(" "),
+                    hex[8..].join(" "),
+                    ascii
+                )
+            })
+            .collect();
+
+        Ok(lines.join(""))
+    }
+
+    /// Line number in the gutter of a preview line, `None` for lines without one
+    pub fn gutter_line_number(line: &str) -> Option<usize> {
+        let (gutter, _) = line.split_once('|')?;
+        gutter.trim_end_matches('>').trim().parse().ok()
+    }
+
+    /// Generate a syntax highlighted preview with the target line highlighted
+    pub fn preview_file_highlighted<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        line_number: Option<usize>,
+        terminal_dimensions: Option<(usize, usize)>,
+        highlighter: &mut SyntaxHighlighter,
+    ) -> Result<Text<'static>> {
+        let file_path = file_path.as_ref();
+        let preview = self.preview_file(file_path, line_number, terminal_dimensions)?;
+        self.highlight_preview(file_path, &preview, line_number, highlighter)
+    }
+
+    /// Syntax highlight a preview generated by `preview_file`
+    pub fn highlight_preview<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        preview: &str,
+        line_number: Option<usize>,
+        highlighter: &mut SyntaxHighlighter,
+    ) -> Result<Text<'static>> {
+        let file_path = file_path.as_ref();
+        if preview.starts_with(BINARY_NOTICE_PREFIX) {
+            let style = Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC);
+            return Ok(Text::styled(preview.to_string(), style));
+        }
+        if Self::is_binary(file_path) {
+            return Ok(Text::from(preview.to_string()));
+        }
+        let extension = file_path.extension().and_then(|ext| ext.to_str());
+        Ok(highlighter.highlight_preview_with_target_line(preview, extension, line_number))
+    }
+}
 
-                results.map_err(SearchError::IoError)
+/// Pad or cut a table cell to the column width, a cut cell ends in `…`
+fn fit_cell(cell: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut fitted_width = 0;
+    for c in cell.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if fitted_width + char_width > width {
+            // Make room for the ellipsis
+            while fitted_width + 1 > width {
+                match fitted.pop() {
+                    Some(last) => fitted_width -= last.width().unwrap_or(0),
+                    None => break,
+                }
             }
-        } else {
-            return Err(SearchError::IoError(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("File not found: {}", file_path.display()),
-            )));
+            fitted.push('…');
+            fitted_width += 1;
+            break;
         }
+        fitted.push(c);
+        fitted_width += char_width;
     }
+    fitted.push_str(&" ".repeat(width.saturating_sub(fitted_width)));
+    fitted
 }
 
 impl Default for PreviewHandler {
@@ -114,7 +807,6 @@ impl Default for PreviewHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use log::debug;
     use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
@@ -147,6 +839,55 @@ mod tests {
         let _handler2 = PreviewHandler::default();
     }
 
+    #[test]
+    fn test_preview_configurable_context_size() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("context.txt");
+        create_test_file(&file_path, 100).unwrap();
+
+        // Two lines of context before the target line
+        let handler = PreviewHandler::new().with_context_size(Some(2));
+        let preview = handler
+            .preview_file(&file_path, Some(50), Some((80, 10)))
+            .unwrap();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[0].contains("Line 48"));
+        assert!(lines[2].contains("  50>|"));
+
+        // Context larger than the visible height still shows the target line
+        let handler = PreviewHandler::new().with_context_size(Some(50));
+        let preview = handler
+            .preview_file(&file_path, Some(50), Some((80, 10)))
+            .unwrap();
+        assert!(preview.lines().last().unwrap().contains("  50>|"));
+
+        // Default centers the target line
+        let handler = PreviewHandler::new().with_context_size(None);
+        let preview = handler
+            .preview_file(&file_path, Some(50), Some((80, 10)))
+            .unwrap();
+        assert!(preview.lines().nth(5).unwrap().contains("  50>|"));
+    }
+
+    #[test]
+    fn test_preview_with_layout() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("layout.txt");
+        create_test_file(&file_path, 200).unwrap();
+
+        // The borders take two rows of the pane
+        let handler = PreviewHandler::new();
+        let preview = handler
+            .preview_with_layout(&file_path, Some(100), Rect::new(0, 0, 80, 12))
+            .unwrap();
+        assert_eq!(preview.lines().count(), 10);
+        assert_eq!(
+            PreviewHandler::layout_dimensions(Rect::new(0, 0, 1, 1)),
+            (0, 0)
+        );
+    }
+
     #[test]
     fn test_preview_nonexistent_file() {
         let handler = PreviewHandler::new();
@@ -286,9 +1027,7 @@ mod tests {
 
         // Test different path types
         let dims = Some((80, 24));
-        let preview1 = handler
-            .preview_file(&file_path, None, dims.clone())
-            .unwrap();
+        let preview1 = handler.preview_file(&file_path, None, dims).unwrap();
         let path_buf = file_path.clone();
         let preview2 = handler.preview_file(path_buf, None, dims).unwrap();
         let path_str = file_path.to_string_lossy().to_string();
@@ -339,7 +1078,7 @@ mod tests {
             .preview_file(&file_path, Some(75), Some((100, 100)))
             .unwrap();
         let line_count = preview_square.lines().count();
-        assert!(line_count <= 100 && line_count >= 75);
+        assert!((75..=100).contains(&line_count));
         assert!(preview_square.contains("Line 75"));
 
         // Test no target line with various dimensions
@@ -374,6 +1113,109 @@ mod tests {
         let _preview = handler.preview_file(&file_path, None, Some((80, 24)));
     }
 
+    #[test]
+    fn test_preview_file_highlighted() {
+        let handler = PreviewHandler::new();
+        let mut highlighter = SyntaxHighlighter::new();
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("main.rs");
+        create_test_file_with_content(&file_path, &["fn main() {", "    run();", "}"]).unwrap();
+
+        let text = handler
+            .preview_file_highlighted(&file_path, Some(2), Some((80, 10)), &mut highlighter)
+            .unwrap();
+        assert_eq!(text.lines.len(), 3);
+
+        // Code is split into several colored spans after the gutter
+        assert!(text.lines[0].spans.len() > 2);
+        assert!(text.lines[1]
+            .spans
+            .iter()
+            .all(|span| span.style.bg.is_some()));
+
+        // Highlighting an already generated preview gives the same text
+        let preview = handler
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        let cached = handler
+            .highlight_preview(&file_path, &preview, Some(2), &mut highlighter)
+            .unwrap();
+        assert_eq!(cached, text);
+
+        // Errors are passed through
+        assert!(handler
+            .preview_file_highlighted(
+                temp_dir.path().join("missing.rs"),
+                Some(1),
+                None,
+                &mut highlighter
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_preview_binary_hex_dump() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("data.bin");
+        let mut bytes = b"\x7fELF hello\xff\xfe".to_vec();
+        bytes.extend(std::iter::repeat_n(0u8, 24));
+        std::fs::write(&file_path, &bytes).unwrap();
+
+        let preview = PreviewHandler::new()
+            .with_hex_fallback(true)
+            .preview_file(&file_path, Some(1), Some((80, 10)))
+            .unwrap();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "00000000  7f 45 4c 46 20 68 65 6c  6c 6f ff fe 00 00 00 00  |.ELF hello......|"
+        );
+        assert!(lines[1].starts_with("00000010  00 00"));
+        assert!(lines[2].starts_with("00000020  00 00 00 00                "));
+
+        // Dump is limited to the visible lines
+        let preview = PreviewHandler::preview_binary(&file_path, 1).unwrap();
+        assert_eq!(preview.lines().count(), 1);
+
+        // Without --hex only a notice is shown
+        let handler = PreviewHandler::new();
+        let preview = handler
+            .preview_file(&file_path, Some(1), Some((80, 10)))
+            .unwrap();
+        assert_eq!(preview, "[Binary file — 36 bytes — use --hex to view]\n");
+
+        let mut highlighter = SyntaxHighlighter::new();
+        let text = handler
+            .highlight_preview(&file_path, &preview, Some(1), &mut highlighter)
+            .unwrap();
+        assert_eq!(text.style.fg, Some(Color::DarkGray));
+    }
+
+    #[test]
+    fn test_is_binary() {
+        let dir = tempdir().unwrap();
+        let is_binary = |bytes: &[u8]| {
+            let path = dir.path().join("data");
+            std::fs::write(&path, bytes).unwrap();
+            PreviewHandler::is_binary(&path)
+        };
+
+        assert!(!is_binary(b""));
+        assert!(!is_binary("plain text with ünïcödé".as_bytes()));
+        // Invalid UTF-8 alone does not make a file binary
+        assert!(!is_binary(b"caf\xe9 \xff\xfe"));
+        // A single null byte does
+        assert!(is_binary(b"SQLite format 3\x00"));
+
+        // The check only looks at the start of the file
+        let mut data = vec![b'a'; BINARY_CHECK_BYTES];
+        data.push(0);
+        assert!(!is_binary(&data));
+
+        assert!(!PreviewHandler::is_binary(&dir.path().join("missing")));
+    }
+
     #[test]
     fn test_preview_no_ansi_escape_sequences() {
         let handler = PreviewHandler::new();
@@ -558,6 +1400,273 @@ mod tests {
         // Should have roughly equal context before and after
         let lines: Vec<&str> = middle_preview.lines().collect();
         let target_pos = lines.iter().position(|line| line.contains(">")).unwrap();
-        assert!(target_pos >= 9 && target_pos <= 11);
+        assert!((9..=11).contains(&target_pos));
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        let dir = tempdir().unwrap();
+        let detect = |name: &str, bytes: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            PreviewHandler::detect_encoding(&path)
+        };
+
+        assert_eq!(detect("utf8.txt", "caf\u{e9}\n".as_bytes()), Some(UTF_8));
+        assert_eq!(
+            detect("latin1.txt", b"caf\xe9 au lait\n"),
+            Some(WINDOWS_1252)
+        );
+        // "日本語" in Shift-JIS
+        assert_eq!(
+            detect("sjis.txt", b"\x93\xfa\x96\x7b\x8c\xea\n"),
+            Some(SHIFT_JIS)
+        );
+        assert_eq!(
+            detect("utf16.txt", b"\xff\xfeh\x00i\x00"),
+            Some(encoding_rs::UTF_16LE)
+        );
+        assert_eq!(detect("binary.bin", b"\x7fELF\x00\x01\xff"), None);
+
+        // A character cut off at the end of the sample is still UTF-8
+        let mut text = "a".repeat(ENCODING_SAMPLE_BYTES - 1).into_bytes();
+        text.extend("\u{e9}".as_bytes());
+        assert_eq!(detect("long.txt", &text), Some(UTF_8));
+    }
+
+    #[test]
+    fn test_preview_file_with_encoding() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("latin1.txt");
+        std::fs::write(&file_path, b"first\ncaf\xe9 au lait\nthird\n").unwrap();
+
+        // Detected automatically
+        let preview = PreviewHandler::new()
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        assert!(preview.contains("   2>| caf\u{e9} au lait"));
+        assert_eq!(preview.lines().count(), 3);
+
+        // Forced encoding
+        let preview = PreviewHandler::new()
+            .with_encoding(Some(encoding_rs::ISO_8859_2))
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        assert!(preview.contains("caf\u{e9} au lait"));
+
+        let encoding = PreviewHandler::encoding_for_label("shift_jis").unwrap();
+        assert_eq!(encoding, SHIFT_JIS);
+        let err = PreviewHandler::encoding_for_label("klingon").unwrap_err();
+        assert!(matches!(err, SearchError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("lines.txt");
+        create_test_file(&file_path, 238).unwrap();
+        assert_eq!(PreviewHandler::count_lines(&file_path).unwrap(), 238);
+
+        std::fs::write(&file_path, "one\ntwo").unwrap();
+        assert_eq!(PreviewHandler::count_lines(&file_path).unwrap(), 2);
+
+        std::fs::write(&file_path, "").unwrap();
+        assert_eq!(PreviewHandler::count_lines(&file_path).unwrap(), 0);
+
+        assert!(PreviewHandler::count_lines(dir.path().join("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_preview_truncates_long_lines() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("minified.js");
+        let long_line = "é".repeat(50);
+        create_test_file_with_content(&file_path, &["short", &long_line]).unwrap();
+
+        let handler = PreviewHandler::new().with_max_line_length(Some(10));
+        let preview = handler
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert!(lines[0].ends_with("| short"));
+        assert!(lines[1].ends_with(&format!("{}… [line truncated at 10 chars]", "é".repeat(10))));
+    }
+
+    #[test]
+    fn test_preview_json() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.json");
+        std::fs::write(&file_path, r#"{"name":"search","tags":["a"],"id":1}"#).unwrap();
+
+        let handler = PreviewHandler::new();
+        let preview = handler
+            .preview_file(&file_path, Some(1), Some((80, 10)))
+            .unwrap();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].ends_with("| {"));
+        // Keys keep their order
+        assert!(lines[1].ends_with(r#"|   "name": "search","#));
+        assert!(lines[5].ends_with(r#"|   "id": 1"#));
+
+        std::fs::write(&file_path, "{\"name\": \"search\",\n  broken\n}\n").unwrap();
+        let preview = handler
+            .preview_json(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines[0], INVALID_JSON_NOTICE);
+        assert!(lines[2].ends_with(">|   broken"));
+    }
+
+    #[test]
+    fn test_preview_markdown() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("README.md");
+        create_test_file_with_content(
+            &file_path,
+            &[
+                "# Title",
+                "",
+                "Intro with **bold** text.",
+                "",
+                "- first",
+                "- second",
+            ],
+        )
+        .unwrap();
+
+        let handler = PreviewHandler::new().with_render_markdown(true);
+        assert!(handler.renders_markdown(&file_path));
+        assert!(!PreviewHandler::new().renders_markdown(&file_path));
+
+        let mut highlighter = SyntaxHighlighter::new();
+        let preview = handler
+            .preview_markdown(&file_path, Some(6), Some((80, 10)), &mut highlighter)
+            .unwrap();
+        let lines: Vec<String> = preview
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(lines[0], "   1 | Title");
+        assert_eq!(lines[1], "     | ");
+        assert_eq!(lines[2], "   3 | Intro with bold text.");
+        assert_eq!(lines[5], "   6>| • second");
+        assert_eq!(PreviewHandler::gutter_line_number(&lines[5]), Some(6));
+    }
+
+    #[test]
+    fn test_preview_csv() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("data.csv");
+        create_test_file_with_content(
+            &file_path,
+            &[
+                "name,city,note",
+                "ada,london,first programmer",
+                "\"grace\",\"new york\",\"a note that is longer than twenty\"",
+                "linus,helsinki,kernel",
+            ],
+        )
+        .unwrap();
+
+        let handler = PreviewHandler::new();
+        assert!(PreviewHandler::is_table(&file_path));
+        let table = handler
+            .preview_csv(&file_path, Some(3), Some((80, 10)))
+            .unwrap();
+        assert_eq!(table.rows.len(), 4);
+        assert_eq!(table.widths, vec![5, 8, CSV_MAX_COLUMN_WIDTH]);
+        assert_eq!(table.target, Some(2));
+        assert_eq!(table.line_range(0, 10), Some((2, 4)));
+
+        // Rendered as a bordered table with the header on top
+        let area = Rect::new(0, 0, 48, 6);
+        let mut buffer = ratatui::buffer::Buffer::empty(area);
+        ratatui::widgets::Widget::render(table.widget(0, true), area, &mut buffer);
+        let row = |y: u16| -> String {
+            (0..area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+        assert!(row(0).starts_with('┌'));
+        assert_eq!(row(1), "│   1  name  city     note                     │");
+        assert_eq!(row(3), "│   3> grace new york a note that is long…     │");
+        assert_eq!(buffer[(1, 3)].bg, Color::Rgb(64, 64, 64));
+        assert!(row(5).starts_with('└'));
+
+        // Columns that don't fit the width are left out
+        let table = handler
+            .preview_csv(&file_path, Some(3), Some((30, 10)))
+            .unwrap();
+        assert_eq!(table.widths, vec![5, 8]);
+
+        // A match on the header line highlights the header
+        let table = handler
+            .preview_csv(&file_path, Some(1), Some((80, 10)))
+            .unwrap();
+        assert_eq!(table.target, Some(0));
+
+        // Rows with a different number of fields are not a table
+        std::fs::write(&file_path, "a,b\n1,2,3\n").unwrap();
+        assert!(handler.preview_csv(&file_path, Some(2), None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preview_command() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("main.rs");
+        create_test_file_with_content(&file_path, &["fn main() {", "    run();", "}"]).unwrap();
+        // Colors each line like `bat --color=always --style=plain` does
+        let script = dir.path().join("colorize");
+        std::fs::write(
+            &script,
+            "while IFS= read -r line; do printf '\\033[32m%s\\033[0m\\n' \"$line\"; done < \"$1\"\n",
+        )
+        .unwrap();
+
+        let handler = PreviewHandler::new()
+            .with_preview_command(Some(format!("sh {} {{file}}", script.display())));
+        let preview = handler
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        assert!(!preview.contains('\x1b'));
+        let lines: Vec<&str> = preview.lines().collect();
+        assert_eq!(lines[1], "   2>|     run();");
+
+        for command in ["false {file}", "search-rs-missing-previewer {file}"] {
+            let handler = PreviewHandler::new().with_preview_command(Some(command.to_string()));
+            let err = handler.preview_file(&file_path, Some(2), None).unwrap_err();
+            assert!(matches!(err, SearchError::SearchProcessError(_)));
+        }
+    }
+
+    #[test]
+    fn test_gutter_line_number() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("gutter.txt");
+        create_test_file_with_content(&file_path, &["first", "a | b", "third"]).unwrap();
+
+        let handler = PreviewHandler::new();
+        let preview = handler
+            .preview_file(&file_path, Some(2), Some((80, 10)))
+            .unwrap();
+        let numbers: Vec<Option<usize>> = preview
+            .lines()
+            .map(PreviewHandler::gutter_line_number)
+            .collect();
+        assert_eq!(numbers, [Some(1), Some(2), Some(3)]);
+
+        assert_eq!(PreviewHandler::gutter_line_number("no gutter"), None);
+        assert_eq!(
+            PreviewHandler::gutter_line_number("00000000  48 65 |He|"),
+            None
+        );
     }
 }
@@ -0,0 +1,449 @@
+//! Search backends.
+//!
+//! A backend runs the search for a pattern and yields the results as they are found.
+//! Ripgrep is used when it is installed, POSIX grep otherwise.
+
+use super::engines::{SearchConfig, SearchEngineMode};
+use super::parser::{decode_line, RgOutputParser};
+use super::SearchResult;
+use crate::{Result, SearchError};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Runs searches for the search engine
+pub trait SearchBackend: fmt::Debug + Send + Sync {
+    /// Name of the backend, used in log messages
+    fn name(&self) -> &'static str;
+
+    /// Start searching the directories for the pattern
+    fn search(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Result<SearchStream>;
+
+    /// Command running the search and the parser of its output, for reading it
+    /// asynchronously. `None` when the backend doesn't run a process
+    fn command(
+        &self,
+        _pattern: &str,
+        _directories: &[String],
+        _config: &SearchConfig,
+    ) -> Option<(Command, RgOutputParser)> {
+        None
+    }
+}
+
+/// Results of a search, yielded as they are found
+pub struct SearchStream {
+    /// The search process, `None` when the backend doesn't run one or it is watched
+    pub process: Option<Child>,
+    /// Watchdog the process was handed to by `watch`
+    watchdog: Option<Watchdog>,
+    results: Box<dyn Iterator<Item = Result<SearchResult>> + Send>,
+}
+
+impl SearchStream {
+    /// Stream of results that doesn't need a process
+    pub fn new(results: impl Iterator<Item = Result<SearchResult>> + Send + 'static) -> Self {
+        Self {
+            process: None,
+            watchdog: None,
+            results: Box::new(results),
+        }
+    }
+
+    /// Spawn the command and parse its output as it is printed
+    pub fn spawn(mut command: Command, parser: RgOutputParser) -> Result<Self> {
+        let program = command.get_program().to_string_lossy().into_owned();
+        let mut process = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                SearchError::SearchProcessError(format!("Failed to start {}: {}", program, e))
+            })?;
+        let stdout = process.stdout.take().ok_or_else(|| {
+            SearchError::SearchProcessError(format!("Failed to capture {} output", program))
+        })?;
+
+        Ok(Self {
+            process: Some(process),
+            watchdog: None,
+            results: Box::new(ParsedOutput::new(parser, BufReader::new(stdout))),
+        })
+    }
+
+    /// Kill the search process once it runs longer than the timeout, `None` for no limit
+    pub fn watch(&mut self, timeout: Option<Duration>) {
+        if let (Some(timeout), Some(process)) = (timeout, self.process.take()) {
+            self.watchdog = Some(Watchdog::spawn(process, timeout));
+        }
+    }
+
+    /// Whether the watchdog killed the search process, the results read are partial
+    pub fn timed_out(&self) -> bool {
+        self.watchdog
+            .as_ref()
+            .is_some_and(|watchdog| watchdog.timed_out())
+    }
+
+    /// Kill the search process and reap it
+    pub fn stop(&mut self) {
+        if let Some(process) = self.process.as_mut() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+        if let Some(watchdog) = self.watchdog.as_mut() {
+            watchdog.finish(WatchdogSignal::Kill);
+        }
+    }
+
+    /// Wait for the search process to exit
+    pub fn wait(&mut self) {
+        if let Some(process) = self.process.as_mut() {
+            let _ = process.wait();
+        }
+        if let Some(watchdog) = self.watchdog.as_mut() {
+            watchdog.finish(WatchdogSignal::Wait);
+        }
+    }
+}
+
+impl fmt::Debug for SearchStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchStream")
+            .field("process", &self.process.as_ref().map(Child::id))
+            .field("watchdog", &self.watchdog)
+            .finish()
+    }
+}
+
+impl Iterator for SearchStream {
+    type Item = Result<SearchResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.results.next()
+    }
+}
+
+/// What the watchdog does with the process when the search ends before the deadline
+enum WatchdogSignal {
+    Kill,
+    Wait,
+}
+
+/// Kills a search process that runs past its deadline
+/// Runs on a thread of its own, so a read stalled on e.g. a network mount can't hold it up
+#[derive(Debug)]
+struct Watchdog {
+    signal: Sender<WatchdogSignal>,
+    thread: Option<JoinHandle<()>>,
+    timed_out: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    fn spawn(mut process: Child, timeout: Duration) -> Self {
+        let (signal, receiver) = mpsc::channel();
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&timed_out);
+        let thread = thread::spawn(move || {
+            match receiver.recv_timeout(timeout) {
+                Ok(WatchdogSignal::Wait) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    crate::logging::warn_log(&SearchError::Timeout(timeout).to_string());
+                    flag.store(true, Ordering::SeqCst);
+                    let _ = process.kill();
+                }
+                // Dropped without being told to wait, don't leave the process running
+                Ok(WatchdogSignal::Kill) | Err(RecvTimeoutError::Disconnected) => {
+                    let _ = process.kill();
+                }
+            }
+            let _ = process.wait();
+        });
+
+        Self {
+            signal,
+            thread: Some(thread),
+            timed_out,
+        }
+    }
+
+    fn timed_out(&self) -> bool {
+        self.timed_out.load(Ordering::SeqCst)
+    }
+
+    /// Stop watching and wait until the process was reaped
+    fn finish(&mut self, signal: WatchdogSignal) {
+        let _ = self.signal.send(signal);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Results parsed from the lines of a reader
+pub struct ParsedOutput<R> {
+    reader: R,
+    /// Bytes of the line being read
+    buffer: Vec<u8>,
+    parser: RgOutputParser,
+    /// Results completed by the last line read
+    ready: VecDeque<SearchResult>,
+    finished: bool,
+}
+
+impl<R: BufRead> ParsedOutput<R> {
+    pub fn new(parser: RgOutputParser, reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            parser,
+            ready: VecDeque::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ParsedOutput<R> {
+    type Item = Result<SearchResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.ready.pop_front() {
+                return Some(Ok(result));
+            }
+            if self.finished {
+                return None;
+            }
+            self.buffer.clear();
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => {
+                    self.finished = true;
+                    self.ready.extend(self.parser.finish());
+                }
+                Ok(_) => {
+                    let line = decode_line(&self.buffer);
+                    self.ready.extend(self.parser.push_line(&line));
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(SearchError::IoError(e)));
+                }
+            }
+        }
+    }
+}
+
+/// Searches with POSIX grep, used when ripgrep is not installed
+/// Ignore files, the depth limit, ripgrep type names and match columns are not supported
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrepBackend;
+
+impl GrepBackend {
+    /// Generates the grep command line arguments
+    pub fn generate_args(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Vec<String> {
+        let mut args = vec![
+            if config.follow_symlinks { "-R" } else { "-r" }.to_string(),
+            "-n".to_string(),
+            "-H".to_string(),
+            "-I".to_string(),
+            "-E".to_string(),
+            // NUL after the path, the same layout ripgrep prints with `--null`
+            "--null".to_string(),
+        ];
+
+        let mut search_pattern = pattern.to_string();
+        match config.mode {
+            SearchEngineMode::Exact => args.push("-w".to_string()),
+            SearchEngineMode::CaseInsensitive => args.push("-i".to_string()),
+            SearchEngineMode::Substring => {}
+            SearchEngineMode::SmartCase => {
+                if !pattern.chars().any(char::is_uppercase) {
+                    args.push("-i".to_string());
+                }
+            }
+            SearchEngineMode::Fuzzy => {
+                args.push("-i".to_string());
+                search_pattern = super::fuzzy::FuzzyMatcher::candidate_regex(pattern);
+            }
+        }
+
+        if config.context_lines > 0 {
+            args.push(format!("-C{}", config.context_lines));
+        }
+        if let Some(max_count) = config.max_count {
+            args.push(format!("-m{}", max_count));
+        }
+        for extension in config
+            .file_types
+            .iter()
+            .filter(|file_type| !super::engines::is_rg_type_name(file_type))
+        {
+            args.push(format!("--include=*.{}", extension.trim_start_matches('.')));
+        }
+        for glob in &config.include_globs {
+            args.push(format!("--include={}", glob));
+        }
+        for glob in &config.exclude_globs {
+            args.push(format!("--exclude={}", glob));
+        }
+        if !config.hidden {
+            args.push("--exclude=.*".to_string());
+            args.push("--exclude-dir=.?*".to_string());
+        }
+
+        args.push("-e".to_string());
+        args.push(search_pattern);
+        if directories.is_empty() {
+            args.push(".".to_string());
+        } else {
+            args.extend(directories.iter().cloned());
+        }
+        args
+    }
+
+    /// Builds the grep command and the parser of its output
+    fn search_command(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> (Command, RgOutputParser) {
+        let args = self.generate_args(pattern, directories, config);
+        crate::logging::debug_log(&format!("Spawning grep with args: {:?}", args));
+
+        let mut command = Command::new("grep");
+        command.args(&args);
+        let parser = RgOutputParser::new(pattern, directories, config.context_lines)
+            .without_columns()
+            .with_fuzzy_matcher(config.fuzzy_matcher(pattern));
+        (command, parser)
+    }
+}
+
+impl SearchBackend for GrepBackend {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn search(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Result<SearchStream> {
+        let (command, parser) = self.search_command(pattern, directories, config);
+        SearchStream::spawn(command, parser)
+    }
+
+    fn command(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Option<(Command, RgOutputParser)> {
+        Some(self.search_command(pattern, directories, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn test_parsed_output() {
+        let output = "a.rs\u{0}1:3:fn main() {\nnot a match\nb.rs\u{0}7:1:main();\n";
+        let parser = RgOutputParser::new("main", &[".".to_string()], 0);
+        let results: Vec<SearchResult> = ParsedOutput::new(parser, output.as_bytes())
+            .map(|result| result.unwrap())
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, Some(3));
+        assert_eq!(results[1].file_path, "b.rs");
+
+        // A Latin-1 line doesn't end the search
+        let output = b"a.rs\x001:caf\xe9 main\r\nb.rs\x002:main\n";
+        let parser = RgOutputParser::new("main", &[".".to_string()], 0);
+        let results: Vec<SearchResult> = ParsedOutput::new(parser, &output[..])
+            .map(|result| result.unwrap())
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line_content, "caf\u{FFFD} main");
+        assert_eq!(results[1].file_path, "b.rs");
+    }
+
+    #[test]
+    fn test_grep_args() {
+        let cli = Cli::parse_from([
+            "search-rs",
+            "needle",
+            "-t",
+            "rs",
+            "-C",
+            "2",
+            "--max-count",
+            "5",
+            "--exclude",
+            "*.lock",
+        ]);
+        let config = SearchConfig::from_cli(&cli);
+        let args = GrepBackend.generate_args("needle", &["src".to_string()], &config);
+
+        assert_eq!(args[..6], ["-r", "-n", "-H", "-I", "-E", "--null"]);
+        assert!(args.contains(&"-i".to_string()));
+        assert!(args.contains(&"-C2".to_string()));
+        assert!(args.contains(&"-m5".to_string()));
+        assert!(args.contains(&"--include=*.rs".to_string()));
+        assert!(args.contains(&"--exclude=*.lock".to_string()));
+        assert!(args.contains(&"--exclude-dir=.?*".to_string()));
+        assert_eq!(args[args.len() - 3..], ["-e", "needle", "src"]);
+    }
+
+    #[test]
+    fn test_watchdog_kills_stalled_search() {
+        let mut command = Command::new("sleep");
+        command.arg("10");
+        let parser = RgOutputParser::new("main", &[".".to_string()], 0);
+        let mut stream = SearchStream::spawn(command, parser).unwrap();
+        stream.watch(Some(Duration::from_millis(50)));
+
+        // The read only ends once the watchdog killed the process
+        assert_eq!(stream.by_ref().count(), 0);
+        stream.wait();
+        assert!(stream.timed_out());
+    }
+
+    #[test]
+    fn test_grep_output_without_columns() {
+        // grep prints no column, content that starts with digits stays intact
+        let output = "a.rs\u{0}4:12:30 timeout\n";
+        let parser = RgOutputParser::new("timeout", &[".".to_string()], 0).without_columns();
+        let results: Vec<SearchResult> = ParsedOutput::new(parser, output.as_bytes())
+            .map(|result| result.unwrap())
+            .collect();
+
+        assert_eq!(results[0].line_number, 4);
+        assert_eq!(results[0].column, None);
+        assert_eq!(results[0].line_content, "12:30 timeout");
+    }
+}
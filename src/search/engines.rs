@@ -1,15 +1,96 @@
 //! Search engine implementations.
 //!
-//! Defines different search modes (exact, case-insensitive, substring)
-//! and handles ripgrep command generation
+//! Defines different search modes (exact, case-insensitive, substring),
+//! handles ripgrep command generation and picks the search backend
+
+use super::backend::{GrepBackend, SearchBackend, SearchStream};
+use super::fuzzy::FuzzyMatcher;
+use super::parser::RgOutputParser;
+use crate::{cli::Cli, dependencies, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ripgrep type names that are not plain file extensions, passed as `--type=NAME`
+const RG_TYPE_NAMES: &[&str] = &[
+    "cpp",
+    "csharp",
+    "docker",
+    "elixir",
+    "erlang",
+    "fortran",
+    "haskell",
+    "julia",
+    "kotlin",
+    "make",
+    "markdown",
+    "ocaml",
+    "perl",
+    "python",
+    "ruby",
+    "rust",
+    "typescript",
+];
+
+/// Checks if the file type is a ripgrep type name rather than an extension
+pub(crate) fn is_rg_type_name(file_type: &str) -> bool {
+    RG_TYPE_NAMES.contains(&file_type)
+}
 
-use crate::{cli::Cli, Result};
+/// Ignore files ripgrep reads in every directory, not only inside git repositories
+const DOT_IGNORE_FILES: &[&str] = &[".ignore", ".rgignore"];
+
+/// `.ignore` and `.rgignore` files at the top of the search directories
+/// No directories means the current directory
+pub fn dot_ignore_files(directories: &[String]) -> Vec<PathBuf> {
+    let current = [".".to_string()];
+    let directories = if directories.is_empty() {
+        &current[..]
+    } else {
+        directories
+    };
+    directories
+        .iter()
+        .flat_map(|dir| {
+            DOT_IGNORE_FILES
+                .iter()
+                .map(move |name| Path::new(dir).join(name))
+        })
+        .filter(|path| path.is_file())
+        .collect()
+}
 
-/// Search Engine that configures ripgrep based on search mode
+/// Options of a search, shared by every backend
 #[derive(Debug, Clone)]
-pub struct SearchEngine {
+pub struct SearchConfig {
     pub mode: SearchEngineMode,
+    /// File extensions or ripgrep type names to search
     pub file_types: Vec<String>,
+    /// Number of context lines around each match
+    pub context_lines: usize,
+    /// Globs of files to search, forwarded as `--glob`
+    pub include_globs: Vec<String>,
+    /// Globs of files to skip, forwarded as `--glob !`
+    pub exclude_globs: Vec<String>,
+    /// Maximum number of matching lines per file
+    pub max_count: Option<usize>,
+    /// Maximum directory depth to descend
+    pub max_depth: Option<usize>,
+    /// Lines longer than this many bytes are cut short by ripgrep
+    pub max_columns: Option<usize>,
+    /// Whether hidden files and directories are searched
+    pub hidden: bool,
+    /// Whether symbolic links are followed
+    pub follow_symlinks: bool,
+    /// Whether ignore files such as `.gitignore` are disregarded
+    pub no_ignore: bool,
+    /// Extra ignore file, forwarded as `--ignore-file`
+    pub ignore_file: Option<PathBuf>,
+    /// Files larger than this many bytes are skipped, forwarded as `--max-filesize`
+    pub max_filesize: Option<u64>,
+    /// Time after which the search process is killed
+    pub timeout: Option<Duration>,
 }
 
 /// Search Engine Mode
@@ -21,38 +102,94 @@ pub enum SearchEngineMode {
     CaseInsensitive,
     /// Substring search (case-sensitive)
     Substring,
+    /// Case-insensitive unless the pattern contains an uppercase character
+    SmartCase,
+    /// Fuzzy search, ripgrep only finds candidate lines which are then scored
+    Fuzzy,
 }
 
-impl SearchEngine {
-    pub fn from_cli(cli: &Cli) -> Result<Self> {
-        Self::from_cli_with_config(cli)
+impl SearchEngineMode {
+    /// Label of the mode in effect for the pattern, used in the status bar
+    /// Smart case resolves to the case sensitivity ripgrep will use
+    pub fn label(&self, pattern: &str) -> &'static str {
+        match self {
+            SearchEngineMode::Exact => "exact",
+            SearchEngineMode::CaseInsensitive => "ignore case",
+            SearchEngineMode::Substring => "substring",
+            SearchEngineMode::SmartCase if pattern.chars().any(char::is_uppercase) => {
+                "smart case (sensitive)"
+            }
+            SearchEngineMode::SmartCase => "smart case (insensitive)",
+            SearchEngineMode::Fuzzy => "fuzzy",
+        }
     }
+}
 
-    pub fn from_cli_with_config(cli: &Cli) -> Result<Self> {
+impl SearchConfig {
+    pub fn from_cli(cli: &Cli) -> Self {
         let mode = if cli.exact {
             SearchEngineMode::Exact
         } else if cli.ignore_case {
             SearchEngineMode::CaseInsensitive
         } else if cli.substring {
             SearchEngineMode::Substring
+        } else if cli.smart_case {
+            SearchEngineMode::SmartCase
+        } else if cli.fuzzy {
+            SearchEngineMode::Fuzzy
         } else {
             SearchEngineMode::CaseInsensitive
         };
 
-        let file_types = vec![];
+        Self {
+            mode,
+            file_types: cli.file_types.clone(),
+            context_lines: cli.context,
+            include_globs: cli.include.clone(),
+            exclude_globs: cli.exclude.clone(),
+            max_count: cli.max_count,
+            max_depth: cli.max_depth,
+            max_columns: cli.line_length_limit(),
+            hidden: cli.hidden,
+            follow_symlinks: cli.follow_symlinks,
+            no_ignore: cli.no_ignore,
+            ignore_file: cli.ignore_file.clone(),
+            max_filesize: cli.max_filesize,
+            timeout: cli.search_timeout(),
+        }
+    }
 
-        Ok(Self { mode, file_types })
+    /// Whether results are fuzzy scored and sorted by score
+    pub fn is_fuzzy(&self) -> bool {
+        matches!(self.mode, SearchEngineMode::Fuzzy)
     }
 
+    /// Matcher used to score results, only in fuzzy mode
+    pub fn fuzzy_matcher(&self, pattern: &str) -> Option<FuzzyMatcher> {
+        self.is_fuzzy().then(|| FuzzyMatcher::new(pattern))
+    }
+}
+
+/// Searches with ripgrep
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RipgrepBackend;
+
+impl RipgrepBackend {
     /// Generates the ripgrep command based on the search mode
-    pub fn generate_rg_args(&self, pattern: &str, directory: Option<&str>) -> Vec<String> {
+    /// All directories are appended at the end, defaulting to the current directory
+    pub fn generate_args<S: AsRef<str>>(
+        &self,
+        pattern: &str,
+        directories: &[S],
+        config: &SearchConfig,
+    ) -> Vec<String> {
         crate::logging::debug_log(&format!("Generating ripgrep args for pattern: {}", pattern));
         let mut args = Vec::new();
 
-        let search_pattern = pattern.to_string();
+        let mut search_pattern = pattern.to_string();
 
         // Add search mode-specific flags
-        match &self.mode {
+        match &config.mode {
             SearchEngineMode::Exact => {
                 args.push("--word-regexp".to_string());
                 args.push("--case-sensitive".to_string());
@@ -63,33 +200,188 @@ impl SearchEngine {
             SearchEngineMode::Substring => {
                 args.push("--case-sensitive".to_string());
             }
+            SearchEngineMode::SmartCase => {
+                args.push("--smart-case".to_string());
+            }
+            SearchEngineMode::Fuzzy => {
+                args.push("--ignore-case".to_string());
+                search_pattern = FuzzyMatcher::candidate_regex(pattern);
+            }
         }
 
-        // Add common flags
-        args.push("--line-number".to_string());
-        args.push("--no-heading".to_string());
-        args.push("--with-filename".to_string());
+        // JSON messages keep paths with ':' or '-' unambiguous and give the offset of
+        // every match on the line
+        args.push("--json".to_string());
+
+        if config.context_lines > 0 {
+            args.push("--context".to_string());
+            args.push(config.context_lines.to_string());
+        }
 
         // Add file type specifications only if file types are specified
-        if !self.file_types.is_empty() {
-            for file_type in &self.file_types {
-                args.push(format!("--type-add=custom:*.{}", file_type));
-            }
+        // Extensions are collected in a custom type, type names are passed through
+        let (type_names, extensions): (Vec<&String>, Vec<&String>) = config
+            .file_types
+            .iter()
+            .partition(|file_type| is_rg_type_name(file_type));
+        for extension in &extensions {
+            args.push(format!(
+                "--type-add=custom:*.{}",
+                extension.trim_start_matches('.')
+            ));
+        }
+        if !extensions.is_empty() {
             args.push("--type=custom".to_string());
         }
+        for type_name in type_names {
+            args.push(format!("--type={}", type_name));
+        }
 
-        // Add search pattern
+        if let Some(max_count) = config.max_count {
+            args.push("--max-count".to_string());
+            args.push(max_count.to_string());
+        }
+        if let Some(max_depth) = config.max_depth {
+            args.push("--max-depth".to_string());
+            args.push(max_depth.to_string());
+        }
+        if let Some(max_columns) = config.max_columns {
+            args.push("--max-columns".to_string());
+            args.push(max_columns.to_string());
+            args.push("--max-columns-preview".to_string());
+        }
+        if config.hidden {
+            args.push("--hidden".to_string());
+        }
+        if config.follow_symlinks {
+            args.push("--follow".to_string());
+        }
+        if config.no_ignore {
+            args.push("--no-ignore".to_string());
+        }
+        if let Some(ignore_file) = &config.ignore_file {
+            args.push("--ignore-file".to_string());
+            args.push(ignore_file.to_string_lossy().into_owned());
+        }
+        if let Some(max_filesize) = config.max_filesize {
+            args.push("--max-filesize".to_string());
+            args.push(max_filesize.to_string());
+        }
+
+        // Add include and exclude globs
+        for glob in &config.include_globs {
+            args.push("--glob".to_string());
+            args.push(glob.clone());
+        }
+        for glob in &config.exclude_globs {
+            args.push("--glob".to_string());
+            args.push(format!("!{}", glob));
+        }
+
+        // Add search pattern, behind `-e` so a pattern starting with `-` isn't an option
+        args.push("-e".to_string());
         args.push(search_pattern);
 
-        // Add directory if specified
-        if let Some(directory) = directory {
-            args.push(directory.to_string());
-        } else {
+        // Add directories if specified
+        if directories.is_empty() {
             args.push(".".to_string());
+        } else {
+            args.extend(directories.iter().map(|dir| dir.as_ref().to_string()));
         }
 
         args
     }
+
+    /// Builds the ripgrep command and the parser of its output
+    fn search_command(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> (Command, RgOutputParser) {
+        let args = self.generate_args(pattern, directories, config);
+        crate::logging::debug_log(&format!("Spawning ripgrep with args: {:?}", args));
+
+        let mut command = Command::new("rg");
+        command.args(&args);
+        let parser = RgOutputParser::new(pattern, directories, config.context_lines)
+            .with_json()
+            .with_fuzzy_matcher(config.fuzzy_matcher(pattern));
+        (command, parser)
+    }
+}
+
+impl SearchBackend for RipgrepBackend {
+    fn name(&self) -> &'static str {
+        "ripgrep"
+    }
+
+    fn search(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Result<SearchStream> {
+        let (command, parser) = self.search_command(pattern, directories, config);
+        SearchStream::spawn(command, parser)
+    }
+
+    fn command(
+        &self,
+        pattern: &str,
+        directories: &[String],
+        config: &SearchConfig,
+    ) -> Option<(Command, RgOutputParser)> {
+        Some(self.search_command(pattern, directories, config))
+    }
+}
+
+/// Runs searches with the configured options on a backend
+#[derive(Debug, Clone)]
+pub struct SearchEngine {
+    pub config: SearchConfig,
+    backend: Arc<dyn SearchBackend>,
+}
+
+impl SearchEngine {
+    /// Search with ripgrep, or grep when ripgrep is not installed
+    pub fn from_cli(cli: &Cli) -> Result<Self> {
+        let backend: Arc<dyn SearchBackend> = if dependencies::ripgrep_installed() {
+            Arc::new(RipgrepBackend)
+        } else {
+            Arc::new(GrepBackend)
+        };
+        Ok(Self::with_backend(SearchConfig::from_cli(cli), backend))
+    }
+
+    pub fn with_backend(config: SearchConfig, backend: Arc<dyn SearchBackend>) -> Self {
+        Self { config, backend }
+    }
+
+    /// Name of the backend running the searches
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Whether results are fuzzy scored and sorted by score
+    pub fn is_fuzzy(&self) -> bool {
+        self.config.is_fuzzy()
+    }
+
+    /// Start searching the directories for the pattern
+    pub fn search(&self, pattern: &str, directories: &[String]) -> Result<SearchStream> {
+        self.backend.search(pattern, directories, &self.config)
+    }
+
+    /// Command running the search and the parser of its output, `None` when the
+    /// backend doesn't run a process
+    pub fn command(
+        &self,
+        pattern: &str,
+        directories: &[String],
+    ) -> Option<(Command, RgOutputParser)> {
+        self.backend.command(pattern, directories, &self.config)
+    }
 }
 
 #[cfg(test)]
@@ -97,7 +389,7 @@ mod tests {
     use std::vec;
 
     use super::*;
-    use crate::cli::Cli;
+    use crate::cli::{Cli, OutputFormat};
 
     // Helper function to create CLI
     fn create_cli(exact: bool, ignore_case: bool, substring: bool) -> Cli {
@@ -106,23 +398,69 @@ mod tests {
             exact,
             ignore_case,
             substring,
-            directory: None,
+            smart_case: false,
+            fuzzy: false,
+            directory: vec![],
+            context: 0,
+            file_types: vec![],
+            and_patterns: vec![],
+            or_patterns: vec![],
+            include: vec![],
+            exclude: vec![],
+            max_count: None,
+            max_results: None,
+            max_depth: None,
+            max_line_length: 0,
+            search_timeout_ms: 0,
+            hidden: false,
+            follow_symlinks: false,
+            no_ignore: false,
+            ignore_file: None,
+            max_filesize: None,
+            sort: None,
+            editor: None,
+            theme: None,
+            theme_file: None,
+            import_annotations: None,
+            hex: false,
+            render_markdown: false,
+            encoding: None,
+            no_dedup: false,
+            output: OutputFormat::Tui,
+            json: false,
+            print_config: false,
+            list_themes: false,
+            clear_history: false,
+            generate_completion: None,
             debug: false,
+            log_format: None,
         }
     }
 
-    // Helper function to create SearchEngine
-    fn create_engine(mode: SearchEngineMode, file_types: Vec<&str>) -> SearchEngine {
+    // Helper function to create SearchConfig
+    fn create_config(mode: SearchEngineMode, file_types: Vec<&str>) -> SearchConfig {
         // .collect will create Vec<String> from Vec<&str>
-        SearchEngine {
+        SearchConfig {
             mode,
             file_types: file_types.iter().map(|s| s.to_string()).collect(),
+            context_lines: 0,
+            include_globs: vec![],
+            exclude_globs: vec![],
+            max_count: None,
+            max_depth: None,
+            max_columns: None,
+            hidden: false,
+            follow_symlinks: false,
+            no_ignore: false,
+            ignore_file: None,
+            max_filesize: None,
+            timeout: None,
         }
     }
 
     // Helper function to assert common flags are present
     fn assert_common_flags(args: &[String]) {
-        let common_flags = ["--line-number", "--no-heading", "--with-filename"];
+        let common_flags = ["--json"];
         for flag in common_flags {
             // helpful error message if assertion fails
             assert!(
@@ -150,8 +488,8 @@ mod tests {
     fn test_searchengine_from_exact_mode() {
         let cli = create_cli(true, false, false);
 
-        let search_engine = SearchEngine::from_cli(&cli).unwrap();
-        matches!(search_engine.mode, SearchEngineMode::Exact);
+        let config = SearchConfig::from_cli(&cli);
+        matches!(config.mode, SearchEngineMode::Exact);
     }
 
     #[test]
@@ -165,17 +503,19 @@ mod tests {
 
         for (exact, ignore_case, substring, expected_mode) in test_cases {
             let cli = create_cli(exact, ignore_case, substring);
-            let search_engine = SearchEngine::from_cli(&cli).unwrap();
+            let config = SearchConfig::from_cli(&cli);
             match expected_mode {
                 SearchEngineMode::Exact => {
-                    assert!(matches!(search_engine.mode, SearchEngineMode::Exact))
+                    assert!(matches!(config.mode, SearchEngineMode::Exact))
+                }
+                SearchEngineMode::CaseInsensitive => {
+                    assert!(matches!(config.mode, SearchEngineMode::CaseInsensitive))
                 }
-                SearchEngineMode::CaseInsensitive => assert!(matches!(
-                    search_engine.mode,
-                    SearchEngineMode::CaseInsensitive
-                )),
                 SearchEngineMode::Substring => {
-                    assert!(matches!(search_engine.mode, SearchEngineMode::Substring))
+                    assert!(matches!(config.mode, SearchEngineMode::Substring))
+                }
+                SearchEngineMode::SmartCase | SearchEngineMode::Fuzzy => {
+                    unreachable!("not selected by these flags")
                 }
             }
         }
@@ -199,11 +539,16 @@ mod tests {
                 vec!["--case-sensitive"],
                 vec!["--word-regexp", "--ignore-case"],
             ),
+            (
+                SearchEngineMode::SmartCase,
+                vec!["--smart-case"],
+                vec!["--case-sensitive", "--ignore-case", "--word-regexp"],
+            ),
         ];
 
         for (mode, should_contain, should_not_contain) in test_cases {
-            let engine = create_engine(mode.clone(), vec!["rs"]);
-            let args = engine.generate_rg_args("pattern", Some("src/"));
+            let config = create_config(mode.clone(), vec!["rs"]);
+            let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
 
             // check mode-specific flags
             for flag in should_contain {
@@ -231,6 +576,125 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rg_args_include_exclude_globs() {
+        let mut cli = create_cli(false, false, false);
+        cli.include = vec!["*.rs".to_string(), "*.toml".to_string()];
+        cli.exclude = vec!["target/**".to_string()];
+        let config = SearchConfig::from_cli(&cli);
+
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        let globs: Vec<&str> = args
+            .windows(2)
+            .filter(|pair| pair[0] == "--glob")
+            .map(|pair| pair[1].as_str())
+            .collect();
+        assert_eq!(globs, vec!["*.rs", "*.toml", "!target/**"]);
+
+        // Globs come before the pattern so they are not taken as search paths
+        let pattern_index = args.iter().position(|arg| arg == "pattern").unwrap();
+        assert!(args.iter().rposition(|arg| arg == "--glob").unwrap() < pattern_index);
+
+        // No globs by default
+        let config = create_config(SearchEngineMode::Exact, vec![]);
+        assert!(!RipgrepBackend
+            .generate_args("pattern", &["src/"], &config)
+            .contains(&"--glob".to_string()));
+    }
+
+    #[test]
+    fn test_rg_args_limits_and_hidden() {
+        let mut cli = create_cli(false, false, false);
+        cli.max_count = Some(50);
+        cli.max_depth = Some(3);
+        cli.max_line_length = 500;
+        cli.hidden = true;
+        cli.follow_symlinks = true;
+        cli.no_ignore = true;
+        let config = SearchConfig::from_cli(&cli);
+
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        assert_common_flags(&args);
+        let joined = args.join(" ");
+        assert!(joined.contains("--max-count 50"));
+        assert!(joined.contains("--max-depth 3"));
+        assert!(joined.contains("--max-columns 500 --max-columns-preview"));
+        assert!(args.contains(&"--hidden".to_string()));
+        assert!(args.contains(&"--follow".to_string()));
+        assert!(args.contains(&"--no-ignore".to_string()));
+
+        cli.ignore_file = Some(PathBuf::from("ci/ignore"));
+        cli.max_filesize = Some(1_048_576);
+        let config = SearchConfig::from_cli(&cli);
+        let joined = RipgrepBackend
+            .generate_args("pattern", &["src/"], &config)
+            .join(" ");
+        assert!(joined.contains("--ignore-file ci/ignore"));
+        assert!(joined.contains("--max-filesize 1048576"));
+
+        // Nothing is passed by default
+        let config = create_config(SearchEngineMode::Exact, vec![]);
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        for flag in [
+            "--max-count",
+            "--max-depth",
+            "--max-columns",
+            "--hidden",
+            "--follow",
+            "--no-ignore",
+            "--ignore-file",
+            "--max-filesize",
+        ] {
+            assert!(!args.contains(&flag.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_dot_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory = dir.path().to_string_lossy().into_owned();
+        assert!(dot_ignore_files(std::slice::from_ref(&directory)).is_empty());
+
+        std::fs::write(dir.path().join(".rgignore"), "generated/\n").unwrap();
+        assert_eq!(
+            dot_ignore_files(&[directory]),
+            [dir.path().join(".rgignore")]
+        );
+    }
+
+    #[test]
+    fn test_smart_case_mode() {
+        let mut cli = create_cli(false, false, false);
+        cli.smart_case = true;
+        let config = SearchConfig::from_cli(&cli);
+        assert!(matches!(config.mode, SearchEngineMode::SmartCase));
+
+        // Effective case sensitivity follows the pattern
+        assert_eq!(config.mode.label("pattern"), "smart case (insensitive)");
+        assert_eq!(config.mode.label("Pattern"), "smart case (sensitive)");
+        assert_eq!(SearchEngineMode::Exact.label("Pattern"), "exact");
+    }
+
+    #[test]
+    fn test_rg_args_fuzzy_mode() {
+        let mut cli = create_cli(false, false, false);
+        cli.fuzzy = true;
+        let config = SearchConfig::from_cli(&cli);
+        assert!(config.is_fuzzy());
+        assert!(config.fuzzy_matcher("fnmn").is_some());
+
+        // Ripgrep gets a candidate regex instead of the raw pattern
+        let args = RipgrepBackend.generate_args("fnmn", &["src/"], &config);
+        assert_common_flags(&args);
+        assert!(args.contains(&"--ignore-case".to_string()));
+        assert!(args.contains(&"f.*n.*m.*n".to_string()));
+        assert!(!args.contains(&"fnmn".to_string()));
+
+        // Other modes have no fuzzy matcher
+        let config = create_config(SearchEngineMode::Substring, vec![]);
+        assert!(config.fuzzy_matcher("fnmn").is_none());
+    }
+
     #[test]
     fn test_search_engine_from_cli_all_combinations() {
         let test_cases = vec![
@@ -246,11 +710,11 @@ mod tests {
 
         for (exact, ignore_case, substring, expected_mode) in test_cases {
             let cli = create_cli(exact, ignore_case, substring);
-            let search_engine = SearchEngine::from_cli(&cli).unwrap();
+            let config = SearchConfig::from_cli(&cli);
             match expected_mode {
                 SearchEngineMode::Exact => {
                     assert!(
-                        matches!(search_engine.mode, SearchEngineMode::Exact),
+                        matches!(config.mode, SearchEngineMode::Exact),
                         "Failed for exact: {}, ignore_case: {}, substring: {}",
                         exact,
                         ignore_case,
@@ -259,7 +723,7 @@ mod tests {
                 }
                 SearchEngineMode::CaseInsensitive => {
                     assert!(
-                        matches!(search_engine.mode, SearchEngineMode::CaseInsensitive),
+                        matches!(config.mode, SearchEngineMode::CaseInsensitive),
                         "Failed for exact: {}, ignore_case: {}, substring: {}",
                         exact,
                         ignore_case,
@@ -268,13 +732,17 @@ mod tests {
                 }
                 SearchEngineMode::Substring => {
                     assert!(
-                        matches!(search_engine.mode, SearchEngineMode::Substring),
+                        matches!(config.mode, SearchEngineMode::Substring),
                         "Failed for exact: {}, ignore_case: {}, substring: {}",
                         exact,
                         ignore_case,
                         substring
                     );
                 }
+
+                SearchEngineMode::SmartCase | SearchEngineMode::Fuzzy => {
+                    unreachable!("not selected by these flags")
+                }
             }
         }
     }
@@ -290,45 +758,104 @@ mod tests {
         ];
 
         for mode in modes {
-            let engine = create_engine(mode, file_types.clone());
-            let args = engine.generate_rg_args("pattern", Some("src/"));
+            let config = create_config(mode, file_types.clone());
+            let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
             assert_file_type_args(&args, &file_types);
             assert!(args.contains(&"pattern".to_string()));
             assert!(args.contains(&"src/".to_string()));
         }
     }
 
+    #[test]
+    fn test_file_types_from_cli() {
+        let mut cli = create_cli(false, false, false);
+        cli.file_types = vec!["rs".to_string(), ".toml".to_string()];
+        let config = SearchConfig::from_cli(&cli);
+        assert_eq!(config.file_types, vec!["rs", ".toml"]);
+
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        assert_file_type_args(&args, &["rs", "toml"]);
+        assert!(!args.iter().any(|arg| arg == "--type=rs"));
+    }
+
+    #[test]
+    fn test_named_file_types() {
+        let config = create_config(SearchEngineMode::Exact, vec!["rust", "python"]);
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        assert!(args.contains(&"--type=rust".to_string()));
+        assert!(args.contains(&"--type=python".to_string()));
+        assert_file_type_args(&args, &[]);
+
+        // Extensions and type names can be mixed
+        let config = create_config(SearchEngineMode::Exact, vec!["toml", "rust"]);
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        assert_file_type_args(&args, &["toml"]);
+        assert!(args.contains(&"--type=rust".to_string()));
+    }
+
     #[test]
     fn test_search_engine_empty_file_types() {
         let cli = create_cli(false, true, false);
-        let engine = SearchEngine::from_cli(&cli).unwrap();
-        assert!(engine.file_types.is_empty());
+        let config = SearchConfig::from_cli(&cli);
+        assert!(config.file_types.is_empty());
 
-        let args = engine.generate_rg_args("pattern", Some("src/"));
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
         assert_file_type_args(&args, &[]);
     }
 
     // Test directory handling
     #[test]
     fn test_rg_args_directory_handling() {
-        let engine = create_engine(SearchEngineMode::CaseInsensitive, vec!["rs"]);
+        let config = create_config(SearchEngineMode::CaseInsensitive, vec!["rs"]);
 
         // Test with directory
-        let args = engine.generate_rg_args("pattern", Some("src/"));
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
         assert!(args.contains(&"src/".to_string()));
         assert!(!args.contains(&".".to_string()));
 
         // Test without directory
-        let args = engine.generate_rg_args("pattern", None);
+        let no_dirs: [&str; 0] = [];
+        let args = RipgrepBackend.generate_args("pattern", &no_dirs, &config);
         assert!(args.contains(&".".to_string()));
         assert!(!args.contains(&"src/".to_string()));
+
+        // Test multiple directories are all appended at the end
+        let dirs = vec!["src/".to_string(), "tests/".to_string()];
+        let args = RipgrepBackend.generate_args("pattern", &dirs, &config);
+        assert_eq!(&args[args.len() - 2..], &dirs[..]);
+        assert!(!args.contains(&".".to_string()));
+    }
+
+    #[test]
+    fn test_rg_args_pattern_starting_with_dash() {
+        let config = create_config(SearchEngineMode::CaseInsensitive, vec![]);
+        let args = RipgrepBackend.generate_args("--files", &["src"], &config);
+        assert_eq!(args[args.len() - 3..], ["-e", "--files", "src"]);
+    }
+
+    // Test context lines handling
+    #[test]
+    fn test_rg_args_context_lines() {
+        let mut config = create_config(SearchEngineMode::CaseInsensitive, vec![]);
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        assert!(!args.contains(&"--context".to_string()));
+
+        config.context_lines = 3;
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
+        let position = args.iter().position(|arg| arg == "--context").unwrap();
+        assert_eq!(args[position + 1], "3");
+
+        let mut cli = create_cli(false, true, false);
+        cli.context = 2;
+        let config = SearchConfig::from_cli(&cli);
+        assert_eq!(config.context_lines, 2);
     }
 
     // Test pattern handling
     #[test]
     fn test_rg_args_pattern_handling() {
-        let engine = create_engine(SearchEngineMode::CaseInsensitive, vec!["rs"]);
-        let args = engine.generate_rg_args("pattern", Some("src/"));
+        let config = create_config(SearchEngineMode::CaseInsensitive, vec!["rs"]);
+        let args = RipgrepBackend.generate_args("pattern", &["src/"], &config);
         assert!(args.contains(&"pattern".to_string()));
         assert_common_flags(&args);
     }
@@ -336,7 +863,7 @@ mod tests {
     // Test special characters in paths
     #[test]
     fn test_special_characters_in_paths() {
-        let engine = create_engine(SearchEngineMode::CaseInsensitive, vec!["rs"]);
+        let config = create_config(SearchEngineMode::CaseInsensitive, vec!["rs"]);
         let special_chars = vec![
             "path with spaces/file.rs",
             "path-with-hyphens/file.rs",
@@ -345,7 +872,7 @@ mod tests {
         ];
 
         for dir in special_chars {
-            let args = engine.generate_rg_args("pattern", Some(dir));
+            let args = RipgrepBackend.generate_args("pattern", &[dir], &config);
             assert!(args.contains(&dir.to_string()), "Failed for dir: {}", dir);
         }
     }
@@ -353,21 +880,21 @@ mod tests {
     // Test Debug and Clone traits
     #[test]
     fn test_search_engine_triats() {
-        let engine = create_engine(SearchEngineMode::CaseInsensitive, vec!["rs"]);
+        let config = create_config(SearchEngineMode::CaseInsensitive, vec!["rs"]);
 
         // Test Clone
-        let cloned = engine.clone();
+        let cloned = config.clone();
         assert!(matches!(cloned.mode, SearchEngineMode::CaseInsensitive));
         assert_eq!(cloned.file_types, vec!["rs"]);
 
         // Test Debug
-        let debug_str = format!("{:?}", engine);
-        assert!(debug_str.contains("SearchEngine"));
+        let debug_str = format!("{:?}", config);
+        assert!(debug_str.contains("SearchConfig"));
         assert!(debug_str.contains("CaseInsensitive"));
         assert!(debug_str.contains("rs"));
 
         // Test mode debug
-        for mode in vec![
+        for mode in [
             SearchEngineMode::Exact,
             SearchEngineMode::CaseInsensitive,
             SearchEngineMode::Substring,
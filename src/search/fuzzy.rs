@@ -0,0 +1,100 @@
+//! Fuzzy matching for search results.
+//!
+//! Ripgrep has no fuzzy mode, so it is only used to emit candidate lines and
+//! every line is re-scored here. Large result sets pay a secondary latency
+//! cost for this re-scoring.
+
+use super::SearchResult;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher as _;
+
+/// Scores lines against a fzf-style fuzzy pattern
+pub struct FuzzyMatcher {
+    pattern: String,
+    matcher: SkimMatcherV2,
+}
+
+impl std::fmt::Debug for FuzzyMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FuzzyMatcher")
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+impl FuzzyMatcher {
+    /// Create a case-insensitive matcher for the pattern
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            matcher: SkimMatcherV2::default().ignore_case(),
+        }
+    }
+
+    /// Regex for ripgrep that keeps every line containing the pattern's characters in order
+    /// Lines that fail it can never get a fuzzy score, so nothing is lost
+    pub fn candidate_regex(pattern: &str) -> String {
+        pattern
+            .chars()
+            .map(|ch| regex::escape(&ch.to_string()))
+            .collect::<Vec<String>>()
+            .join(".*")
+    }
+
+    /// Score a line, higher is better, `None` when it does not match
+    pub fn score(&self, line: &str) -> Option<i64> {
+        self.matcher.fuzzy_match(line, &self.pattern)
+    }
+
+    /// Store the score on the result, returning false when the line does not match
+    pub fn score_result(&self, result: &mut SearchResult) -> bool {
+        result.match_score = self.score(&result.line_content);
+        result.match_score.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        let matcher = FuzzyMatcher::new("fnmn");
+        assert!(matcher.score("fn main() {}").is_some());
+        assert!(matcher.score("FN MAIN").is_some());
+        assert!(matcher.score("nothing here").is_none());
+
+        // Tighter matches score higher
+        let tight = matcher.score("fnmn").unwrap();
+        let loose = matcher.score("fxxxnxxxmxxxn").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn test_candidate_regex() {
+        assert_eq!(FuzzyMatcher::candidate_regex("fm"), "f.*m");
+        assert_eq!(FuzzyMatcher::candidate_regex("a.b"), "a.*\\..*b");
+        assert_eq!(FuzzyMatcher::candidate_regex("x"), "x");
+
+        let regex = regex::Regex::new(&FuzzyMatcher::candidate_regex("f(m")).unwrap();
+        assert!(regex.is_match("fn (main"));
+        assert!(!regex.is_match("fn main"));
+    }
+
+    #[test]
+    fn test_score_result() {
+        let matcher = FuzzyMatcher::new("fnmn");
+        let mut result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(1)
+            .line_content("fn main() {")
+            .matched_text("fnmn")
+            .build();
+        assert!(matcher.score_result(&mut result));
+        assert!(result.match_score.is_some());
+
+        result.line_content = "use std::io;".to_string();
+        assert!(!matcher.score_result(&mut result));
+        assert_eq!(result.match_score, None);
+    }
+}
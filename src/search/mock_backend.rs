@@ -0,0 +1,103 @@
+//! Search backend with fixed results.
+//!
+//! Lets tests run searches through `SearchEngine` without ripgrep or grep installed.
+
+use super::backend::{SearchBackend, SearchStream};
+use super::engines::SearchConfig;
+use super::{SearchEngine, SearchResult};
+use crate::cli::Cli;
+use crate::Result;
+use clap::Parser;
+use std::sync::Arc;
+
+/// Backend yielding pre-loaded results
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    pub results: Vec<SearchResult>,
+}
+
+impl MockBackend {
+    pub fn from_results(results: Vec<SearchResult>) -> Self {
+        Self { results }
+    }
+
+    /// Backend that never finds anything
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Search engine with the default options running on this backend
+    pub fn into_engine(self) -> SearchEngine {
+        let config = SearchConfig::from_cli(&Cli::parse_from(["search-rs", ""]));
+        SearchEngine::with_backend(config, Arc::new(self))
+    }
+}
+
+impl SearchBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    /// Results whose line contains the pattern, in the order they were loaded
+    fn search(
+        &self,
+        pattern: &str,
+        _directories: &[String],
+        _config: &SearchConfig,
+    ) -> Result<SearchStream> {
+        let results: Vec<SearchResult> = self
+            .results
+            .iter()
+            .filter(|result| result.line_content.contains(pattern))
+            .map(|result| SearchResult {
+                matched_text: pattern.to_string(),
+                ..result.clone()
+            })
+            .collect();
+        Ok(SearchStream::new(results.into_iter().map(Ok)))
+    }
+}
+
+/// Result for the file and line with the given content
+pub fn mock_result(file_path: &str, line_number: usize, line_content: &str) -> SearchResult {
+    SearchResult::builder()
+        .file_path(file_path)
+        .line_number(line_number)
+        .line_content(line_content)
+        .build()
+}
+
+/// Run the search on the engine and collect every result
+pub fn collect_results(engine: &SearchEngine, pattern: &str) -> Vec<SearchResult> {
+    engine
+        .search(pattern, &[])
+        .unwrap()
+        .map(|result| result.unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_backend() {
+        let engine = MockBackend::from_results(vec![
+            mock_result("a.rs", 1, "fn main() {"),
+            mock_result("a.rs", 2, "let x = 1;"),
+        ])
+        .into_engine();
+        assert_eq!(engine.backend_name(), "mock");
+
+        let results = collect_results(&engine, "main");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_text, "main");
+        assert_eq!(collect_results(&engine, "").len(), 2);
+    }
+
+    #[test]
+    fn test_empty_mock_backend() {
+        let engine = MockBackend::empty().into_engine();
+        assert!(collect_results(&engine, "main").is_empty());
+    }
+}
@@ -2,31 +2,137 @@
 //!
 //! Manages the search piplenes: rg -> Rust program
 
+pub mod backend;
 pub mod engines;
+pub mod fuzzy;
+#[cfg(test)]
+pub mod mock_backend;
+pub mod multi;
+pub mod parser;
+pub mod progressive;
+pub mod rate_limit;
+pub mod rg_json;
 pub mod sorter;
+pub mod stream;
 
 pub use engines::SearchEngine;
 
-use crate::{cli::Cli, tui::highlighter::SyntaxHighlighter};
-use ratatui::text::Line;
+use crate::tui::highlighter::SyntaxHighlighter;
+use parser::{RgLine, RgLineKind};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
+use sorter::GitBlameInfo;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
 
 /// Represents a single search result
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SerializedSearchResult")]
 pub struct SearchResult {
     pub file_path: String,
     pub line_number: usize,
+    /// Column of the first match on the line, when reported by ripgrep
+    pub column: Option<usize>,
     pub line_content: String,
     pub matched_text: String,
+    /// Start and end byte offsets of each match in `line_content`, when reported by ripgrep
+    pub match_spans: Vec<(usize, usize)>,
     /// Original line content with coloring from rg
     pub line_colored_content: Option<String>,
     /// Base directory of search (used for relative path)
     pub base_dir: Option<String>,
+    /// Context lines printed before the match
+    pub context_before: Vec<String>,
+    /// Context lines printed after the match
+    pub context_after: Vec<String>,
+    /// Fuzzy match score, higher is better (fuzzy mode only)
+    pub match_score: Option<i64>,
+    /// Last commit that changed the line, looked up when the blame column is shown
+    #[serde(skip)]
+    pub blame_info: Option<GitBlameInfo>,
     /// Pre-computed display path (cached for performance)
     display_path: String,
 }
 
+/// Builds a `SearchResult`, fields that are not set stay empty
+#[derive(Debug, Clone, Default)]
+pub struct SearchResultBuilder {
+    file_path: String,
+    line_number: usize,
+    line_content: String,
+    matched_text: String,
+    colored_content: Option<String>,
+    base_dir: Option<String>,
+}
+
+impl SearchResultBuilder {
+    /// Path of the file as the search reported it
+    pub fn file_path(mut self, file_path: impl Into<String>) -> Self {
+        self.file_path = file_path.into();
+        self
+    }
+
+    /// 1-based line number of the match
+    pub fn line_number(mut self, line_number: usize) -> Self {
+        self.line_number = line_number;
+        self
+    }
+
+    /// Text of the matching line
+    pub fn line_content(mut self, line_content: impl Into<String>) -> Self {
+        self.line_content = line_content.into();
+        self
+    }
+
+    /// Part of the line that matched the pattern
+    pub fn matched_text(mut self, matched_text: impl Into<String>) -> Self {
+        self.matched_text = matched_text.into();
+        self
+    }
+
+    /// Line content with coloring from rg
+    pub fn colored_content(mut self, colored_content: Option<&str>) -> Self {
+        self.colored_content = colored_content.map(str::to_string);
+        self
+    }
+
+    /// Directory the display path is made relative to
+    pub fn base_dir(mut self, base_dir: Option<&str>) -> Self {
+        self.base_dir = base_dir.map(str::to_string);
+        self
+    }
+
+    /// Create the search result and compute its display path
+    pub fn build(self) -> SearchResult {
+        let display_path =
+            SearchResult::compute_display_path(&self.file_path, self.base_dir.as_deref());
+        SearchResult {
+            file_path: self.file_path,
+            line_number: self.line_number,
+            column: None,
+            line_content: self.line_content,
+            matched_text: self.matched_text,
+            match_spans: Vec::new(),
+            line_colored_content: self.colored_content,
+            base_dir: self.base_dir,
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            match_score: None,
+            blame_info: None,
+            display_path,
+        }
+    }
+}
+
 impl SearchResult {
-    /// Creates a new search result
+    /// Start building a search result
+    pub fn builder() -> SearchResultBuilder {
+        SearchResultBuilder::default()
+    }
+
+    /// Creates a new search result, kept for compatibility with `builder`
     pub fn new(
         file_path: String,
         line_number: usize,
@@ -35,25 +141,35 @@ impl SearchResult {
         line_colored_content: Option<String>,
         base_dir: Option<String>,
     ) -> Self {
-        let display_path = Self::compute_display_path(&file_path, base_dir.as_deref());
-        Self {
-            file_path,
-            line_number,
-            line_content,
-            matched_text,
-            line_colored_content,
-            base_dir,
-            display_path,
-        }
+        Self::builder()
+            .file_path(file_path)
+            .line_number(line_number)
+            .line_content(line_content)
+            .matched_text(matched_text)
+            .colored_content(line_colored_content.as_deref())
+            .base_dir(base_dir.as_deref())
+            .build()
+    }
+
+    /// Parse a single match line of ripgrep output
+    /// The base directory is taken from whichever search directory contains the file
+    pub fn from_rg_line(line: &str, pattern: &str, search_dirs: &[String]) -> Option<Self> {
+        let parsed = RgLine::parse(line).filter(|parsed| parsed.kind == RgLineKind::Match)?;
+
+        let mut result = Self::builder()
+            .file_path(parsed.file_path)
+            .line_number(parsed.line_number)
+            .line_content(parsed.content)
+            .matched_text(pattern)
+            .base_dir(parser::base_dir_for(parsed.file_path, search_dirs).as_deref())
+            .build();
+        result.column = parsed.column;
+        Some(result)
     }
 
     /// Compute display path once during construction (for performance)
     fn compute_display_path(file_path: &str, base_dir: Option<&str>) -> String {
-        let cleaned_path = if file_path.starts_with("./") {
-            &file_path[2..]
-        } else {
-            file_path
-        };
+        let cleaned_path = file_path.strip_prefix("./").unwrap_or(file_path);
 
         // If base_dir is set, make path relative to it
         if let Some(base_directory) = base_dir {
@@ -69,34 +185,164 @@ impl SearchResult {
         cleaned_path.to_string()
     }
 
+    /// Location of the match as `path:line`, or `path:line:column` when the column is known
+    fn location(&self) -> String {
+        match self.column {
+            Some(column) => format!("{}:{}:{}", self.display_path, self.line_number, column),
+            None => format!("{}:{}", self.display_path, self.line_number),
+        }
+    }
+
+    /// Relevance of the result, higher is better. Combines how early the match is on the
+    /// line, how recently the line was modified and how shallow the file is, each scored
+    /// between 0 and 1 and weighted
+    pub fn relevance_score(
+        &self,
+        mtime: SystemTime,
+        mtime_weight: f64,
+        position_weight: f64,
+        depth_weight: f64,
+    ) -> f64 {
+        let position = match self.column {
+            Some(column) => column.saturating_sub(1),
+            None if self.matched_text.is_empty() => 0,
+            None => self
+                .line_content
+                .find(&self.matched_text)
+                .unwrap_or(self.line_content.len()),
+        };
+        let age_days = SystemTime::now()
+            .duration_since(mtime)
+            .unwrap_or_default()
+            .as_secs_f64()
+            / 86_400.0;
+        let depth = Path::new(&self.display_path).components().count().max(1);
+
+        mtime_weight / (1.0 + age_days)
+            + position_weight / (1.0 + position as f64)
+            + depth_weight / depth as f64
+    }
+
+    /// Case-insensitive substring match of the filter against the display path and line
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        self.display_path.to_lowercase().contains(&filter)
+            || self.line_content.to_lowercase().contains(&filter)
+    }
+
     /// Format the result for display in the TUI
     /// If use_color is true, the line will be syntax-highlighted
     pub fn format_for_display(&self, use_color: bool) -> String {
         // Use the pre-computed display path for optimal performance
         // Use colored content if available and requested, otherwise fallback to line content
-        let content = if use_color && self.line_colored_content.is_some() {
-            self.line_colored_content.as_ref().unwrap().trim()
-        } else {
-            self.line_content.trim()
+        let content = match &self.line_colored_content {
+            Some(colored_content) if use_color => colored_content.trim(),
+            _ => self.line_content.trim(),
         };
 
-        format!("{}:{} {}", self.display_path, self.line_number, content)
+        let line = format!("{} {}", self.location(), content);
+        if self.context_before.is_empty() && self.context_after.is_empty() {
+            return line;
+        }
+
+        // Context lines use ripgrep's `-` separator to set them apart from the match
+        let first_before = self.line_number.saturating_sub(self.context_before.len());
+        let before =
+            self.context_before.iter().enumerate().map(|(idx, text)| {
+                format!("{}-{} {}", self.display_path, first_before + idx, text)
+            });
+        let after = self.context_after.iter().enumerate().map(|(idx, text)| {
+            format!(
+                "{}-{} {}",
+                self.display_path,
+                self.line_number + idx + 1,
+                text
+            )
+        });
+
+        before
+            .chain(std::iter::once(line))
+            .chain(after)
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 
     /// Format the result for TUI display with fast syntax highlighting
-    pub fn format_for_tui_display(&self, highlighter: &mut SyntaxHighlighter) -> Line<'static> {
+    /// With `highlight_match` the matched text is drawn on a yellow background
+    pub fn format_for_tui_display(
+        &self,
+        highlighter: &mut SyntaxHighlighter,
+        highlight_match: bool,
+    ) -> Line<'static> {
         // Use the pre-computed display path for optimal performance
         // Extract file extension for syntax highlighting
         let extension = SyntaxHighlighter::get_extension(&self.display_path);
 
         // Create formated line with syntax highlighting
-        let line_content = format!(
-            "{}:{} {}",
-            self.display_path,
-            self.line_number,
-            self.line_content.trim()
-        );
-        highlighter.highlight_line(&line_content, extension)
+        let content = self.line_content.trim();
+        let line_content = format!("{} {}", self.location(), content);
+        let line = highlighter.highlight_line(&line_content, extension);
+        if !highlight_match || (self.matched_text.is_empty() && self.match_spans.is_empty()) {
+            return line;
+        }
+
+        // Only look for the match after the location prefix
+        let content_start = line_content.len() - content.len();
+        let ranges: Vec<(usize, usize)> = if self.match_spans.is_empty() {
+            content
+                .match_indices(self.matched_text.as_str())
+                .map(|(idx, text)| (content_start + idx, content_start + idx + text.len()))
+                .collect()
+        } else {
+            // Spans are offsets into the untrimmed line, clip them to the trimmed content
+            let trimmed_start = self.line_content.len() - self.line_content.trim_start().len();
+            let mut spans = self.match_spans.clone();
+            spans.sort_unstable();
+            let mut ranges: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+            for (start, end) in spans {
+                let clip = |offset: usize| {
+                    content_start + offset.saturating_sub(trimmed_start).min(content.len())
+                };
+                let (start, end) = (clip(start), clip(end));
+                // Overlapping spans are merged, style_ranges needs disjoint ranges
+                match ranges.last_mut() {
+                    Some(last) if start < last.1 => last.1 = last.1.max(end),
+                    _ if start < end => ranges.push((start, end)),
+                    _ => {}
+                }
+            }
+            ranges
+        };
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        style_ranges(line, &ranges, match_style)
+    }
+
+    /// Format the context lines for TUI display in a dimmer style
+    /// Returns the lines before and after the match
+    pub fn format_context_for_tui_display(&self) -> (Vec<Line<'static>>, Vec<Line<'static>>) {
+        let dim = Style::default().fg(Color::DarkGray);
+        let first_before = self.line_number.saturating_sub(self.context_before.len());
+        let format_line = |line_number: usize, text: &str| {
+            Line::from(Span::styled(
+                format!("{}-{} {}", self.display_path, line_number, text.trim_end()),
+                dim,
+            ))
+        };
+
+        let before = self
+            .context_before
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| format_line(first_before + idx, text))
+            .collect();
+        let after = self
+            .context_after
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| format_line(self.line_number + idx + 1, text))
+            .collect();
+
+        (before, after)
     }
 
     /// Get pre-computed display path
@@ -105,13 +351,128 @@ impl SearchResult {
     }
 }
 
+/// Apply the style to the byte ranges of the line, splitting the spans at the range boundaries
+/// The ranges must be sorted and must not overlap
+fn style_ranges(mut line: Line<'static>, ranges: &[(usize, usize)], style: Style) -> Line<'static> {
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in line.spans.drain(..) {
+        let text = span.content.as_ref();
+        let end = offset + text.len();
+        let mut pos = offset;
+        for &(start, stop) in ranges.iter().filter(|(s, e)| *s < end && *e > offset) {
+            let start = start.max(offset);
+            let stop = stop.min(end);
+            if start > pos {
+                spans.push(Span::styled(
+                    text[pos - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                text[start - offset..stop - offset].to_string(),
+                style,
+            ));
+            pos = stop;
+        }
+        if pos == offset {
+            spans.push(span);
+        } else if pos < end {
+            spans.push(Span::styled(text[pos - offset..].to_string(), span.style));
+        }
+        offset = end;
+    }
+
+    line.spans = spans;
+    line
+}
+
+impl std::fmt::Display for SearchResult {
+    /// Same as `format_for_display(false)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format_for_display(false))
+    }
+}
+
+/// Serialized form of `SearchResult`
+/// The display path is always recomputed so a stale value is never restored
+#[derive(Deserialize)]
+struct SerializedSearchResult {
+    file_path: String,
+    line_number: usize,
+    #[serde(default)]
+    column: Option<usize>,
+    line_content: String,
+    matched_text: String,
+    #[serde(default)]
+    match_spans: Vec<(usize, usize)>,
+    #[serde(default)]
+    line_colored_content: Option<String>,
+    #[serde(default)]
+    base_dir: Option<String>,
+    #[serde(default)]
+    context_before: Vec<String>,
+    #[serde(default)]
+    context_after: Vec<String>,
+    #[serde(default)]
+    match_score: Option<i64>,
+}
+
+impl From<SerializedSearchResult> for SearchResult {
+    fn from(serialized: SerializedSearchResult) -> Self {
+        let mut result = SearchResult::builder()
+            .file_path(serialized.file_path)
+            .line_number(serialized.line_number)
+            .line_content(serialized.line_content)
+            .matched_text(serialized.matched_text)
+            .colored_content(serialized.line_colored_content.as_deref())
+            .base_dir(serialized.base_dir.as_deref())
+            .build();
+        result.column = serialized.column;
+        result.match_spans = serialized.match_spans;
+        result.context_before = serialized.context_before;
+        result.context_after = serialized.context_after;
+        result.match_score = serialized.match_score;
+        result
+    }
+}
+
 /// Status information for progressive loading
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProgressiveLoadStatus {
     pub total_loaded: usize,
     pub loading_complete: bool,
     pub total_files_found: usize,
     pub load_threshold: usize,
+    /// Number of matching lines received
+    pub total_matches: usize,
+    /// Number of matching lines received for each file
+    pub matches_per_file: HashMap<String, usize>,
+    /// Whether the search stopped at the result limit
+    pub results_truncated: bool,
+    /// Number of results dropped because the file and line were already found
+    pub deduplication_count: usize,
+}
+
+impl ProgressiveLoadStatus {
+    /// Count a match in the file
+    pub fn record_match(&mut self, file_path: &str) {
+        self.total_matches += 1;
+        *self
+            .matches_per_file
+            .entry(file_path.to_string())
+            .or_insert(0) += 1;
+        self.total_files_found = self.matches_per_file.len();
+    }
+
+    /// Number of matches received for the file
+    pub fn matches_for_file(&self, file_path: &str) -> usize {
+        self.matches_per_file.get(file_path).copied().unwrap_or(0)
+    }
 }
 
 #[cfg(test)]
@@ -121,14 +482,12 @@ mod tests {
     #[test]
     fn test_compute_display_path_computation_and_formatting() {
         // Test basic cases
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            42,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(42)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
         assert_eq!(result.get_display_path(), "src/main.rs");
         assert_eq!(
             result.format_for_display(false),
@@ -136,66 +495,61 @@ mod tests {
         );
 
         // Test dot prefix removal
-        let result = SearchResult::new(
-            "./src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("./src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
         assert_eq!(result.get_display_path(), "src/main.rs");
         let display = result.format_for_display(false);
         assert!(!display.starts_with("./"));
         assert!(display.starts_with("src/main.rs:10"));
 
         // Test relative path
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            Some("src".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .base_dir(Some("src"))
+            .build();
         assert_eq!(result.get_display_path(), "main.rs");
         let display = result.format_for_display(false);
         assert!(display.starts_with("main.rs:10"));
 
         // Test dot prefix removal with relative path
-        let result = SearchResult::new(
-            "./src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            Some("src".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("./src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .base_dir(Some("src"))
+            .build();
         assert_eq!(result.get_display_path(), "main.rs");
 
         // Test caching and performance - very long path
-        let result = SearchResult::new(
-            "/home/user/project/src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            Some("/home/user/project".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("/home/user/project/src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .base_dir(Some("/home/user/project"))
+            .build();
         let formatted1 = result.format_for_display(false);
         let formatted2 = result.format_for_display(false);
         assert_eq!(formatted1, formatted2);
         assert!(formatted1.starts_with("src/main.rs:10"));
 
         // Test coloring
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            Some("assert_eq!(formatted1, formatted2);".to_string()),
-            Some("src".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .colored_content(Some("assert_eq!(formatted1, formatted2);"))
+            .base_dir(Some("src"))
+            .build();
         let formatted = result.format_for_display(true);
         assert_eq!(formatted, "main.rs:10 assert_eq!(formatted1, formatted2);");
 
@@ -218,6 +572,234 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_builder_matches_new() {
+        let built = SearchResult::builder()
+            .file_path("/home/user/proj/src/main.rs")
+            .line_number(12)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .colored_content(Some("fn \x1b[31mmain\x1b[0m() {"))
+            .base_dir(Some("/home/user/proj"))
+            .build();
+        let new = SearchResult::new(
+            "/home/user/proj/src/main.rs".to_string(),
+            12,
+            "fn main() {".to_string(),
+            "main".to_string(),
+            Some("fn \x1b[31mmain\x1b[0m() {".to_string()),
+            Some("/home/user/proj".to_string()),
+        );
+        assert_eq!(built, new);
+        assert_eq!(built.display_path, "src/main.rs");
+
+        // Unset fields stay empty
+        let result = SearchResult::builder().file_path("./a.rs").build();
+        assert_eq!(result.line_number, 0);
+        assert_eq!(result.line_content, "");
+        assert_eq!(result.base_dir, None);
+        assert_eq!(result.display_path, "a.rs");
+    }
+
+    #[test]
+    fn test_from_rg_line_parsing() {
+        let dirs = vec![".".to_string()];
+
+        // Basic ripgrep line
+        let result =
+            SearchResult::from_rg_line("./src/main.rs:42:fn main() {", "main", &dirs).unwrap();
+        assert_eq!(result.file_path, "./src/main.rs");
+        assert_eq!(result.line_number, 42);
+        assert_eq!(result.line_content, "fn main() {");
+        assert_eq!(result.matched_text, "main");
+        assert_eq!(result.base_dir, None);
+        assert_eq!(result.get_display_path(), "src/main.rs");
+
+        // Content containing colons and numbers
+        let result =
+            SearchResult::from_rg_line("src/lib.rs:7:let x: u8 = 1:2;", "x", &dirs).unwrap();
+        assert_eq!(result.line_number, 7);
+        assert_eq!(result.line_content, "let x: u8 = 1:2;");
+
+        // Path containing a colon not followed by a line number
+        let result = SearchResult::from_rg_line("dir:name/file.rs:3:content", "c", &dirs).unwrap();
+        assert_eq!(result.file_path, "dir:name/file.rs");
+        assert_eq!(result.line_number, 3);
+
+        // Base directory is picked from the matching search directory
+        let dirs = vec![
+            "/home/user/project".to_string(),
+            "/home/user/shared".to_string(),
+        ];
+        let result =
+            SearchResult::from_rg_line("/home/user/shared/src/lib.rs:1:mod a;", "mod", &dirs)
+                .unwrap();
+        assert_eq!(result.base_dir, Some("/home/user/shared".to_string()));
+        assert_eq!(result.get_display_path(), "src/lib.rs");
+
+        // NUL separated output from `--null`
+        let result = SearchResult::from_rg_line("a:1:b.rs\u{0}5:content", "c", &dirs).unwrap();
+        assert_eq!(result.file_path, "a:1:b.rs");
+        assert_eq!(result.line_number, 5);
+
+        assert_eq!(result.column, None);
+
+        // Column from `--column`
+        let result = SearchResult::from_rg_line("a.rs\u{0}5:3:content", "c", &dirs).unwrap();
+        assert_eq!(result.line_number, 5);
+        assert_eq!(result.column, Some(3));
+        assert_eq!(result.line_content, "content");
+
+        // Context lines are not matches
+        assert!(SearchResult::from_rg_line("a.rs\u{0}5-content", "c", &dirs).is_none());
+
+        // Invalid lines
+        assert!(SearchResult::from_rg_line("", "x", &dirs).is_none());
+        assert!(SearchResult::from_rg_line("no separators", "x", &dirs).is_none());
+        assert!(SearchResult::from_rg_line(":1:missing path", "x", &dirs).is_none());
+        assert!(SearchResult::from_rg_line("file.rs:abc:content", "x", &dirs).is_none());
+    }
+
+    #[test]
+    fn test_format_with_column() {
+        let mut result = SearchResult::builder()
+            .file_path("./src/main.rs")
+            .line_number(42)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
+
+        // Without a column the output is unchanged
+        assert_eq!(result.column, None);
+        assert_eq!(
+            result.format_for_display(false),
+            "src/main.rs:42 fn main() {"
+        );
+
+        result.column = Some(4);
+        assert_eq!(
+            result.format_for_display(false),
+            "src/main.rs:42:4 fn main() {"
+        );
+        assert_eq!(result.get_display_path(), "src/main.rs");
+    }
+
+    #[test]
+    fn test_relevance_score() {
+        let result = |file_path: &str, line_content: &str| {
+            SearchResult::builder()
+                .file_path(file_path)
+                .line_number(1)
+                .line_content(line_content)
+                .matched_text("needle")
+                .build()
+        };
+        let now = SystemTime::now();
+        let score = |result: &SearchResult, mtime| result.relevance_score(mtime, 1.0, 1.0, 1.0);
+
+        // Every part scores 1 for a match at the start of a root file modified just now
+        let best = result("a.rs", "needle");
+        assert!((score(&best, now) - 3.0).abs() < 0.01);
+
+        let later = result("a.rs", "the needle");
+        let deeper = result("src/a.rs", "needle");
+        assert!(score(&later, now) < score(&best, now));
+        assert!(score(&deeper, now) < score(&best, now));
+        assert!(score(&best, SystemTime::UNIX_EPOCH) < score(&best, now));
+
+        // Weights scale each part
+        assert!((best.relevance_score(now, 0.0, 0.5, 0.0) - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let result = SearchResult::builder()
+            .file_path("./src/tui/App.rs")
+            .line_number(7)
+            .line_content("pub fn Quit(&mut self) {")
+            .matched_text("quit")
+            .build();
+
+        assert!(result.matches_filter("tui/app"));
+        assert!(result.matches_filter("QUIT"));
+        assert!(result.matches_filter(""));
+        assert!(!result.matches_filter("main.rs"));
+        // The stripped "./" prefix is not part of the display path
+        assert!(!result.matches_filter("./src"));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut result = SearchResult::builder()
+            .file_path("/home/user/project/src/main.rs")
+            .line_number(42)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .base_dir(Some("/home/user/project"))
+            .build();
+        result.column = Some(4);
+        result.context_after = vec!["    run();".to_string()];
+
+        let json = serde_json::to_string(&result).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["display_path"], "src/main.rs");
+        assert_eq!(value["column"], 4);
+
+        let restored: SearchResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, result);
+        assert_eq!(restored.get_display_path(), "src/main.rs");
+
+        // A stale display path is recomputed and optional fields default
+        let json = r#"{
+            "file_path": "./src/lib.rs",
+            "line_number": 3,
+            "line_content": "mod a;",
+            "matched_text": "mod",
+            "display_path": "wrong.rs"
+        }"#;
+        let restored: SearchResult = serde_json::from_str(json).unwrap();
+        assert_eq!(restored.get_display_path(), "src/lib.rs");
+        assert_eq!(restored.column, None);
+        assert!(restored.context_before.is_empty());
+    }
+
+    #[test]
+    fn test_format_with_context_lines() {
+        let mut result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
+        result.context_before = vec!["// one".to_string(), "// two".to_string()];
+        result.context_after = vec!["    run();".to_string()];
+
+        assert_eq!(
+            result.format_for_display(false),
+            "src/main.rs-8 // one\nsrc/main.rs-9 // two\nsrc/main.rs:10 fn main() {\nsrc/main.rs-11     run();"
+        );
+
+        let (before, after) = result.format_context_for_tui_display();
+        assert_eq!(before.len(), 2);
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].spans[0].content, "src/main.rs-11     run();");
+        assert_eq!(after[0].spans[0].style.fg, Some(Color::DarkGray));
+
+        // No context lines
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
+        let (before, after) = result.format_context_for_tui_display();
+        assert!(before.is_empty() && after.is_empty());
+        assert_eq!(
+            result.format_for_display(false),
+            "src/main.rs:10 fn main() {"
+        );
+    }
+
     #[test]
     fn test_display_path_consistency_across_constructor() {
         let path = "src/main.rs";
@@ -226,23 +808,20 @@ mod tests {
         let matched_text = "main";
 
         // All constructors should produce the same display path for the same input
-        let result1 = SearchResult::new(
-            path.to_string(),
-            line_number,
-            line_content.to_string(),
-            matched_text.to_string(),
-            None,
-            None,
-        );
+        let result1 = SearchResult::builder()
+            .file_path(path)
+            .line_number(line_number)
+            .line_content(line_content)
+            .matched_text(matched_text)
+            .build();
 
-        let result2 = SearchResult::new(
-            path.to_string(),
-            line_number,
-            line_content.to_string(),
-            matched_text.to_string(),
-            Some("colorized content".to_string()),
-            None,
-        );
+        let result2 = SearchResult::builder()
+            .file_path(path)
+            .line_number(line_number)
+            .line_content(line_content)
+            .matched_text(matched_text)
+            .colored_content(Some("colorized content"))
+            .build();
 
         assert_eq!(result1.get_display_path(), result2.get_display_path());
         assert_eq!(result1.get_display_path(), path);
@@ -251,14 +830,12 @@ mod tests {
     #[test]
     fn test_search_result_traits() {
         // Test basic field access and construction
-        let basic_result = SearchResult::new(
-            "src/main.rs".to_string(),
-            42,
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            None,
-            None,
-        );
+        let basic_result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(42)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .build();
         assert_eq!(basic_result.file_path, "src/main.rs");
         assert_eq!(basic_result.line_number, 42);
         assert_eq!(
@@ -272,31 +849,30 @@ mod tests {
         assert!(display.contains("src/main.rs:42"));
         assert!(display.contains("assert_eq!(formatted1, formatted2);"));
 
+        // Test Display
+        assert_eq!(format!("{}", basic_result), display);
+        println!("{}", basic_result);
+
         // Test PartialEq
-        let result1 = SearchResult::new(
-            "src/main.rs".to_string(),
-            42,
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            None,
-            None,
-        );
-        let result2 = SearchResult::new(
-            "src/main.rs".to_string(),
-            42,
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            None,
-            None,
-        );
-        let result3 = SearchResult::new(
-            "src/main.rs".to_string(),
-            36, // Different line number
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            None,
-            None,
-        );
+        let result1 = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(42)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .build();
+        let result2 = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(42)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .build();
+        let result3 = SearchResult::builder()
+            .file_path("src/main.rs")
+            // Different line number
+            .line_number(36)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .build();
         assert_eq!(result1, result2);
         assert_ne!(result1, result3);
 
@@ -330,163 +906,223 @@ mod tests {
         ];
 
         for (file_path, line_number, expected_substring) in test_cases {
-            let result = SearchResult::new(
-                file_path.to_string(),
-                line_number,
-                "some content".to_string(),
-                "some matched text".to_string(),
-                None,
-                None,
-            );
+            let result = SearchResult::builder()
+                .file_path(file_path)
+                .line_number(line_number)
+                .line_content("some content")
+                .matched_text("some matched text")
+                .build();
             let display = result.format_for_display(false);
             assert!(display.contains(expected_substring));
             assert!(display.contains("some content"));
         }
 
         //Empty content
-        let result = SearchResult::new(
-            "".to_string(),
-            0,
-            "".to_string(),
-            "".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("")
+            .line_number(0)
+            .line_content("")
+            .matched_text("")
+            .build();
         assert_eq!(result.file_path, "");
         assert_eq!(result.line_number, 0);
         assert_eq!(result.line_content, "");
 
         // Very long content
         let long_content = "a".repeat(1000);
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            long_content.to_string(),
-            "a".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content(&long_content)
+            .matched_text("a")
+            .build();
         assert_eq!(result.line_content, long_content);
-        
+
         // Special characters
         let special_content = "fn test(a: u8) -> u8, Box<u8> {\n let a = 1;\n    let b = 2;\n}";
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            special_content.to_string(),
-            "Box".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content(special_content)
+            .matched_text("Box")
+            .build();
         assert_eq!(result.line_content, special_content);
         assert!(result.format_for_display(false).contains("src/main.rs:10"));
-        
+
         // Unicode content - chinese + emoji
         let unicode_content = "// ❤️ 😍 你好 禾風紅土歡苗點不歌巴禾追休";
-        let result = SearchResult::new(
-            "src/歌巴.rs".to_string(),
-            10,
-            unicode_content.to_string(),
-            "禾風紅土歡苗點不歌巴禾追休".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/歌巴.rs")
+            .line_number(10)
+            .line_content(unicode_content)
+            .matched_text("禾風紅土歡苗點不歌巴禾追休")
+            .build();
         assert_eq!(result.file_path, "src/歌巴.rs");
         assert_eq!(result.matched_text, "禾風紅土歡苗點不歌巴禾追休");
         assert!(result.format_for_display(false).contains("src/歌巴.rs:10"));
     }
-    
+
     #[test]
     fn test_comprehensive_path_and_display_scenarios() {
         // Test with base directory
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            Some("src".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .base_dir(Some("src"))
+            .build();
         let display = result.format_for_display(false);
         assert!(display.starts_with("main.rs:10"));
         assert!(!display.contains("src/main.rs"));
-        
+
         // Test without base directory
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "main".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("main")
+            .build();
         let display = result.format_for_display(false);
         assert!(display.starts_with("src/main.rs:10"));
-        
+
         // Test no match (fallback to full path)
-        let result = SearchResult::new(
-            "other/path/src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "x".to_string(),
-            None,
-            Some("myproject".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("other/path/src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("x")
+            .base_dir(Some("myproject"))
+            .build();
         let display = result.format_for_display(false);
         assert!(display.starts_with("other/path/src/main.rs:10"));
-        
+
         // Test dot prefix with base directory
-        let result = SearchResult::new(
-            "./src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "test".to_string(),
-            None,
-            Some("myproj".to_string()),
-        );
+        let result = SearchResult::builder()
+            .file_path("./src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("test")
+            .base_dir(Some("myproj"))
+            .build();
         let display = result.format_for_display(false);
         assert!(display.starts_with("src/main.rs:10"));
         assert!(!display.contains("./src/main.rs"));
         assert!(!display.contains("./myproj/src/main.rs"));
-        
+
         // Test dot prefix without base directory
-        let result = SearchResult::new(
-            "./src/main.rs".to_string(),
-            10,
-            "fn main() {".to_string(),
-            "test".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("./src/main.rs")
+            .line_number(10)
+            .line_content("fn main() {")
+            .matched_text("test")
+            .build();
         let display = result.format_for_display(false);
         assert!(!display.starts_with("./"));
         assert!(display.starts_with("src/main.rs:10"));
-        
+
         // Test content trimming
-        let result = SearchResult::new(
-            "src/main.rs".to_string(),
-            10,
-            "    assert_eq!(formatted1, formatted2);".to_string(),
-            "assert_eq!".to_string(),
-            None,
-            None,
-        );
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(10)
+            .line_content("    assert_eq!(formatted1, formatted2);")
+            .matched_text("assert_eq!")
+            .build();
         let display = result.format_for_display(false);
         assert!(display.contains("src/main.rs:10 assert_eq!(formatted1, formatted2);"));
         assert!(!display.contains("    assert_eq!(formatted1, formatted2);"));
-        
+
         // Test complex content with dot prefix
-        let result = SearchResult::new(
-            "./very/long/path/src/main.rs".to_string(),
-            999,
-            "    let a = 1;\n    let b = 2;\n}".to_string(),
-            "let".to_string(),
-            None,
-            None
-        );
+        let result = SearchResult::builder()
+            .file_path("./very/long/path/src/main.rs")
+            .line_number(999)
+            .line_content("    let a = 1;\n    let b = 2;\n}")
+            .matched_text("let")
+            .build();
         let display = result.format_for_display(false);
         assert!(display.starts_with("very/long/path/src/main.rs:999"));
         assert!(display.contains(" let a = 1;\n    let b = 2;\n}"));
         assert!(!display.contains("./"));
     }
+
+    #[test]
+    fn test_progressive_load_status_match_counts() {
+        let mut status = ProgressiveLoadStatus::default();
+        status.record_match("src/main.rs");
+        status.record_match("src/lib.rs");
+        status.record_match("src/main.rs");
+
+        assert_eq!(status.total_matches, 3);
+        assert_eq!(status.total_files_found, 2);
+        assert_eq!(status.matches_for_file("src/main.rs"), 2);
+        assert_eq!(status.matches_for_file("src/lib.rs"), 1);
+        assert_eq!(status.matches_for_file("README.md"), 0);
+    }
+
+    #[test]
+    fn test_format_for_tui_display_highlights_match() {
+        let mut highlighter = SyntaxHighlighter::new();
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        let result = SearchResult::builder()
+            .file_path("NOTES")
+            .line_number(3)
+            .line_content("hello world again")
+            .matched_text("world")
+            .build();
+
+        let line = result.format_for_tui_display(&mut highlighter, false);
+        assert_eq!(line.spans.len(), 1);
+
+        let line = result.format_for_tui_display(&mut highlighter, true);
+        let texts: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, ["NOTES:3 hello ", "world", " again"]);
+        assert_eq!(line.spans[0].style, Style::default());
+        assert_eq!(line.spans[1].style, match_style);
+        assert_eq!(line.spans[2].style, Style::default());
+    }
+
+    #[test]
+    fn test_format_for_tui_display_splits_syntax_spans() {
+        let mut highlighter = SyntaxHighlighter::new();
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        // The match is also part of the path, only the line content is highlighted
+        let result = SearchResult::builder()
+            .file_path("src/main.rs")
+            .line_number(1)
+            .line_content("fn main() { domain() }")
+            .matched_text("main")
+            .build();
+
+        let line = result.format_for_tui_display(&mut highlighter, true);
+        let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "src/main.rs:1 fn main() { domain() }");
+
+        let matched: Vec<&str> = line
+            .spans
+            .iter()
+            .filter(|s| s.style == match_style)
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(matched, ["main", "main"]);
+    }
+
+    #[test]
+    fn test_format_for_tui_display_uses_match_spans() {
+        let mut highlighter = SyntaxHighlighter::new();
+        let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+        // A regex pattern never appears in the line, the spans locate the matches
+        let mut result = SearchResult::builder()
+            .file_path("NOTES")
+            .line_number(3)
+            .line_content("  word and ward")
+            .matched_text("w.rd")
+            .build();
+        result.match_spans = vec![(11, 15), (2, 6)];
+
+        let line = result.format_for_tui_display(&mut highlighter, true);
+        let texts: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, ["NOTES:3 ", "word", " and ", "ward"]);
+        assert_eq!(line.spans[1].style, match_style);
+        assert_eq!(line.spans[3].style, match_style);
+    }
 }
@@ -0,0 +1,255 @@
+//! Multi-pattern search.
+//!
+//! Runs a search once per pattern and combines the results: with `--and` only
+//! lines matched by every pattern are kept, with `--or` lines matched by any pattern.
+
+use super::backend::SearchStream;
+use super::{SearchEngine, SearchResult};
+use crate::cli::Cli;
+use crate::Result;
+use std::collections::HashSet;
+
+/// How the results of the patterns are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiPatternMode {
+    /// Lines matched by every pattern
+    And,
+    /// Lines matched by any pattern
+    Or,
+}
+
+/// Identity of a result line, used for the set operations
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResultKey(pub String, pub usize);
+
+impl From<&SearchResult> for ResultKey {
+    fn from(result: &SearchResult) -> Self {
+        ResultKey(result.file_path.clone(), result.line_number)
+    }
+}
+
+/// Search engine that combines the primary pattern with further patterns
+#[derive(Debug, Clone)]
+pub struct MultiPatternEngine {
+    pub engine: SearchEngine,
+    pub mode: MultiPatternMode,
+    /// Patterns combined with the primary pattern
+    pub patterns: Vec<String>,
+}
+
+impl MultiPatternEngine {
+    pub fn new(engine: SearchEngine, mode: MultiPatternMode, patterns: Vec<String>) -> Self {
+        Self {
+            engine,
+            mode,
+            patterns,
+        }
+    }
+
+    /// Create the engine from the `--and` or `--or` flags, `None` when neither is given
+    pub fn from_cli(cli: &Cli, engine: &SearchEngine) -> Option<Self> {
+        cli.multi_pattern()
+            .map(|(mode, patterns)| Self::new(engine.clone(), mode, patterns))
+    }
+
+    /// The primary pattern followed by the combined patterns
+    fn all_patterns<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a str> {
+        std::iter::once(pattern).chain(self.patterns.iter().map(String::as_str))
+    }
+
+    /// Start a search for the primary pattern and each combined pattern
+    pub fn start_searches(
+        &self,
+        pattern: &str,
+        directories: &[String],
+    ) -> Result<Vec<SearchStream>> {
+        let mut streams = Vec::new();
+        for pattern in self.all_patterns(pattern) {
+            match self.engine.search(pattern, directories) {
+                Ok(stream) => streams.push(stream),
+                Err(e) => {
+                    for mut stream in streams {
+                        stream.stop();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(streams)
+    }
+
+    /// Read the results of each pattern, in pattern order, and combine them
+    pub fn read_results<I>(&self, outputs: Vec<I>) -> Vec<SearchResult>
+    where
+        I: Iterator<Item = Result<SearchResult>>,
+    {
+        let result_sets = outputs
+            .into_iter()
+            .map(|output| output.map_while(Result::ok).collect())
+            .collect();
+        self.combine(result_sets)
+    }
+
+    /// Run every search to completion and combine the results
+    pub fn search(&self, pattern: &str, directories: &[String]) -> Result<Vec<SearchResult>> {
+        let mut streams = self.start_searches(pattern, directories)?;
+        for stream in &mut streams {
+            stream.watch(self.engine.config.timeout);
+        }
+        let results = self.read_results(streams.iter_mut().collect());
+        for stream in &mut streams {
+            stream.wait();
+        }
+        Ok(results)
+    }
+
+    /// Combine the result sets of the patterns
+    /// Lines found by several patterns keep the result of the first one
+    pub fn combine(&self, result_sets: Vec<Vec<SearchResult>>) -> Vec<SearchResult> {
+        let mut sets = result_sets.into_iter();
+        let first = sets.next().unwrap_or_default();
+
+        match self.mode {
+            MultiPatternMode::And => {
+                let others: Vec<HashSet<ResultKey>> = sets
+                    .map(|set| set.iter().map(ResultKey::from).collect())
+                    .collect();
+                first
+                    .into_iter()
+                    .filter(|result| {
+                        let key = ResultKey::from(result);
+                        others.iter().all(|keys| keys.contains(&key))
+                    })
+                    .collect()
+            }
+            MultiPatternMode::Or => {
+                let mut seen = HashSet::new();
+                first
+                    .into_iter()
+                    .chain(sets.flatten())
+                    .filter(|result| seen.insert(ResultKey::from(result)))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::backend::ParsedOutput;
+    use crate::search::mock_backend::MockBackend;
+    use crate::search::parser::RgOutputParser;
+    use clap::Parser;
+
+    fn create_engine(mode: MultiPatternMode, patterns: &[&str]) -> MultiPatternEngine {
+        let cli = Cli::parse_from(["search-rs", "foo"]);
+        let engine = SearchEngine::from_cli(&cli).unwrap();
+        MultiPatternEngine::new(
+            engine,
+            mode,
+            patterns.iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    fn result(file_path: &str, line_number: usize, matched_text: &str) -> SearchResult {
+        SearchResult::builder()
+            .file_path(file_path)
+            .line_number(line_number)
+            .line_content(format!("{} line", matched_text))
+            .matched_text(matched_text)
+            .build()
+    }
+
+    fn keys(results: &[SearchResult]) -> Vec<ResultKey> {
+        results.iter().map(ResultKey::from).collect()
+    }
+
+    #[test]
+    fn test_combine_and() {
+        let engine = create_engine(MultiPatternMode::And, &["bar"]);
+        let combined = engine.combine(vec![
+            vec![result("a.rs", 1, "foo"), result("a.rs", 2, "foo")],
+            vec![result("a.rs", 2, "bar"), result("b.rs", 2, "bar")],
+        ]);
+
+        assert_eq!(keys(&combined), vec![ResultKey("a.rs".to_string(), 2)]);
+        // The primary pattern's result is kept
+        assert_eq!(combined[0].matched_text, "foo");
+    }
+
+    #[test]
+    fn test_combine_or() {
+        let engine = create_engine(MultiPatternMode::Or, &["bar"]);
+        let combined = engine.combine(vec![
+            vec![result("a.rs", 1, "foo"), result("a.rs", 2, "foo")],
+            vec![result("a.rs", 2, "bar"), result("b.rs", 2, "bar")],
+        ]);
+
+        assert_eq!(
+            keys(&combined),
+            vec![
+                ResultKey("a.rs".to_string(), 1),
+                ResultKey("a.rs".to_string(), 2),
+                ResultKey("b.rs".to_string(), 2),
+            ]
+        );
+        assert_eq!(combined[1].matched_text, "foo");
+    }
+
+    #[test]
+    fn test_read_results() {
+        let engine = create_engine(MultiPatternMode::And, &["bar"]);
+        let foo = "a.rs\u{0}1:1:foo\na.rs\u{0}2:1:foo bar\n";
+        let bar = "a.rs\u{0}2:5:foo bar\nb.rs\u{0}3:1:bar\n";
+
+        let directories = [".".to_string()];
+        let output = |pattern: &str, text: &'static str| {
+            ParsedOutput::new(
+                RgOutputParser::new(pattern, &directories, 0),
+                text.as_bytes(),
+            )
+        };
+
+        let results = engine.read_results(vec![output("foo", foo), output("bar", bar)]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line_number, 2);
+        assert_eq!(results[0].matched_text, "foo");
+    }
+
+    #[test]
+    fn test_search_with_mock_backend() {
+        let backend = MockBackend::from_results(vec![
+            result("a.rs", 1, "foo"),
+            result("a.rs", 2, "foo bar"),
+            result("b.rs", 3, "bar"),
+        ]);
+        let engine = backend.into_engine();
+        let multi = MultiPatternEngine::new(engine, MultiPatternMode::Or, vec!["bar".to_string()]);
+
+        let results = multi.search("foo", &[]).unwrap();
+        assert_eq!(
+            keys(&results),
+            vec![
+                ResultKey("a.rs".to_string(), 1),
+                ResultKey("a.rs".to_string(), 2),
+                ResultKey("b.rs".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_cli() {
+        let cli = Cli::parse_from(["search-rs", "foo"]);
+        let engine = SearchEngine::from_cli(&cli).unwrap();
+        assert!(MultiPatternEngine::from_cli(&cli, &engine).is_none());
+
+        let cli = Cli::parse_from(["search-rs", "foo", "--and", "bar", "--and", "baz"]);
+        let multi = MultiPatternEngine::from_cli(&cli, &engine).unwrap();
+        assert_eq!(multi.mode, MultiPatternMode::And);
+        assert_eq!(
+            multi.all_patterns("foo").collect::<Vec<_>>(),
+            ["foo", "bar", "baz"]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Ripgrep output parsing.
+//!
+//! Parses ripgrep's plain-text output into search results. With `--null` the
+//! path is followed by a NUL byte, with `--column` matches look like
+//! `path\0line:column:content` and context lines look like `path\0line-content`
+//!
+//! Output of `--json` is read as the messages in `rg_json`, lines that are not JSON
+//! fall back to the plain-text format.
+
+use super::fuzzy::FuzzyMatcher;
+use super::rg_json::RgMessage;
+use super::SearchResult;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Kind of line emitted by ripgrep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgLineKind {
+    /// Line containing a match
+    Match,
+    /// Context line printed around a match
+    Context,
+}
+
+/// A single line of ripgrep output split into its fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgLine<'a> {
+    pub kind: RgLineKind,
+    pub file_path: &'a str,
+    pub line_number: usize,
+    /// Column of the first match, only reported for match lines
+    pub column: Option<usize>,
+    pub content: &'a str,
+    /// Start and end byte offsets of each match in `content`, only with `--json`
+    pub match_spans: Vec<(usize, usize)>,
+}
+
+impl<'a> RgLine<'a> {
+    /// Split a line of ripgrep output into its fields
+    /// Falls back to the first `:<digits>:` separator when there is no NUL after the path
+    pub fn parse(line: &'a str) -> Option<Self> {
+        Self::parse_with_columns(line, true)
+    }
+
+    /// Split a line of output, `columns` is false for output printed without `--column`
+    pub fn parse_with_columns(line: &'a str, columns: bool) -> Option<Self> {
+        let split_column = |text: &'a str| {
+            if columns {
+                split_column(text)
+            } else {
+                (None, text)
+            }
+        };
+        if let Some((file_path, rest)) = line.split_once('\0') {
+            let digits = count_digits(rest);
+            if file_path.is_empty() || digits == 0 {
+                return None;
+            }
+            let kind = match rest[digits..].chars().next()? {
+                ':' => RgLineKind::Match,
+                '-' => RgLineKind::Context,
+                _ => return None,
+            };
+            let line_number = rest[..digits].parse().ok()?;
+            let content = &rest[digits + 1..];
+            let (column, content) = match kind {
+                RgLineKind::Match => split_column(content),
+                RgLineKind::Context => (None, content),
+            };
+            return Some(Self {
+                kind,
+                file_path,
+                line_number,
+                column,
+                content,
+                match_spans: Vec::new(),
+            });
+        }
+
+        // Without NUL separators only match lines can be told apart reliably
+        // Paths may contain colons themselves so look for the first `:<digits>:`
+        let mut search_from = 0;
+        while let Some(offset) = line[search_from..].find(':') {
+            let separator = search_from + offset;
+            let rest = &line[separator + 1..];
+            let digits = count_digits(rest);
+
+            if separator > 0 && digits > 0 && rest[digits..].starts_with(':') {
+                let (column, content) = split_column(&rest[digits + 1..]);
+                return Some(Self {
+                    kind: RgLineKind::Match,
+                    file_path: &line[..separator],
+                    line_number: rest[..digits].parse().ok()?,
+                    column,
+                    content,
+                    match_spans: Vec::new(),
+                });
+            }
+            search_from = separator + 1;
+        }
+        None
+    }
+}
+
+/// Count leading ASCII digits
+fn count_digits(text: &str) -> usize {
+    text.bytes()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count()
+}
+
+/// Split the `column:` prefix that `--column` adds in front of match content
+fn split_column(text: &str) -> (Option<usize>, &str) {
+    let digits = count_digits(text);
+    if digits > 0 && text[digits..].starts_with(':') {
+        if let Ok(column) = text[..digits].parse() {
+            return (Some(column), &text[digits + 1..]);
+        }
+    }
+    (None, text)
+}
+
+/// Decode a line of search output read with its line ending
+/// Lines that are not UTF-8, such as Latin-1 text, get replacement characters
+pub fn decode_line(line: &[u8]) -> Cow<'_, str> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line)
+}
+
+/// Find the search directory that contains the file, used as base for display paths
+/// Directories are compared by path components, the deepest one wins when they nest
+pub fn base_dir_for(file_path: &str, search_dirs: &[String]) -> Option<String> {
+    search_dirs
+        .iter()
+        .filter(|dir| dir.as_str() != "." && Path::new(file_path).starts_with(dir.as_str()))
+        .max_by_key(|dir| Path::new(dir.as_str()).components().count())
+        .cloned()
+}
+
+/// Stateful parser that attaches context lines to the matches they surround
+/// A match is held back until its trailing context is complete
+#[derive(Debug)]
+pub struct RgOutputParser {
+    pattern: String,
+    search_dirs: Vec<String>,
+    context_lines: usize,
+    /// Match waiting for its trailing context lines
+    pending: Option<SearchResult>,
+    /// Context lines seen since the last match (file path, content)
+    recent_context: VecDeque<(String, String)>,
+    /// Scores matches in fuzzy mode, dropping lines that do not match
+    fuzzy_matcher: Option<FuzzyMatcher>,
+    /// Whether match lines carry a `column:` prefix
+    columns: bool,
+    /// Whether the output is ripgrep's `--json` messages
+    json: bool,
+}
+
+impl RgOutputParser {
+    /// Create a parser for output produced with `--context context_lines`
+    pub fn new(pattern: &str, search_dirs: &[String], context_lines: usize) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            search_dirs: search_dirs.to_vec(),
+            context_lines,
+            pending: None,
+            recent_context: VecDeque::new(),
+            fuzzy_matcher: None,
+            columns: true,
+            json: false,
+        }
+    }
+
+    /// Parse the JSON messages printed with `--json`
+    pub fn with_json(mut self) -> Self {
+        self.json = true;
+        self
+    }
+
+    /// Parse output printed without match columns
+    pub fn without_columns(mut self) -> Self {
+        self.columns = false;
+        self
+    }
+
+    /// Score every match with the fuzzy matcher, keeping only lines that match
+    pub fn with_fuzzy_matcher(mut self, fuzzy_matcher: Option<FuzzyMatcher>) -> Self {
+        self.fuzzy_matcher = fuzzy_matcher;
+        self
+    }
+
+    /// Feed one line of ripgrep output, returning any results that are complete
+    pub fn push_line(&mut self, line: &str) -> Vec<SearchResult> {
+        if self.json {
+            match RgMessage::parse(line) {
+                // Context doesn't carry over from one file to the next
+                Some(RgMessage::End(_)) => return self.end_group(),
+                Some(message) => {
+                    return match message.rg_line() {
+                        Some(parsed) => self.push_parsed(parsed),
+                        None => Vec::new(),
+                    }
+                }
+                // Not a JSON message, read it as plain text
+                None => {}
+            }
+        }
+
+        // Group separator between non-adjacent context blocks
+        if line == "--" {
+            return self.end_group();
+        }
+
+        match RgLine::parse_with_columns(line, self.columns) {
+            Some(parsed) => self.push_parsed(parsed),
+            None => Vec::new(),
+        }
+    }
+
+    /// Complete the held back match at the end of a block of context lines
+    fn end_group(&mut self) -> Vec<SearchResult> {
+        self.recent_context.clear();
+        self.pending.take().into_iter().collect()
+    }
+
+    /// Add a parsed match or context line
+    fn push_parsed(&mut self, parsed: RgLine) -> Vec<SearchResult> {
+        let mut completed = Vec::new();
+        match parsed.kind {
+            RgLineKind::Context => {
+                if let Some(pending) = self.pending.as_mut() {
+                    if pending.file_path == parsed.file_path
+                        && pending.context_after.len() < self.context_lines
+                    {
+                        pending.context_after.push(parsed.content.to_string());
+                    }
+                }
+
+                // May also be leading context for the next match
+                self.recent_context
+                    .push_back((parsed.file_path.to_string(), parsed.content.to_string()));
+                if self.recent_context.len() > self.context_lines {
+                    self.recent_context.pop_front();
+                }
+            }
+            RgLineKind::Match => {
+                completed.extend(self.pending.take());
+
+                let context_before = self
+                    .recent_context
+                    .drain(..)
+                    .filter(|(path, _)| path == parsed.file_path)
+                    .map(|(_, content)| content)
+                    .collect();
+
+                let mut result = SearchResult::builder()
+                    .file_path(parsed.file_path)
+                    .line_number(parsed.line_number)
+                    .line_content(parsed.content)
+                    .matched_text(self.pattern.clone())
+                    .base_dir(base_dir_for(parsed.file_path, &self.search_dirs).as_deref())
+                    .build();
+                result.column = parsed.column;
+                result.match_spans = parsed.match_spans;
+                result.context_before = context_before;
+
+                if let Some(matcher) = &self.fuzzy_matcher {
+                    if !matcher.score_result(&mut result) {
+                        return completed;
+                    }
+                }
+
+                if self.context_lines == 0 {
+                    completed.push(result);
+                } else {
+                    self.pending = Some(result);
+                }
+            }
+        }
+
+        completed
+    }
+
+    /// Flush the last held back match once ripgrep has exited
+    pub fn finish(&mut self) -> Option<SearchResult> {
+        self.recent_context.clear();
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(lines: &[&str], context_lines: usize) -> Vec<SearchResult> {
+        let mut parser = RgOutputParser::new("main", &[".".to_string()], context_lines);
+        let mut results: Vec<SearchResult> = lines
+            .iter()
+            .flat_map(|line| parser.push_line(line))
+            .collect();
+        results.extend(parser.finish());
+        results
+    }
+
+    #[test]
+    fn test_rg_line_parse() {
+        // NUL separated match and context lines
+        let line = RgLine::parse("src/main.rs\u{0}42:fn main() {").unwrap();
+        assert_eq!(line.kind, RgLineKind::Match);
+        assert_eq!(line.file_path, "src/main.rs");
+        assert_eq!(line.line_number, 42);
+        assert_eq!(line.column, None);
+        assert_eq!(line.content, "fn main() {");
+
+        // Match line with a column from `--column`
+        let line = RgLine::parse("src/main.rs\u{0}42:4:fn main() {").unwrap();
+        assert_eq!(line.line_number, 42);
+        assert_eq!(line.column, Some(4));
+        assert_eq!(line.content, "fn main() {");
+
+        let line = RgLine::parse("src/a-1-b.rs\u{0}41-// comment: 1:2").unwrap();
+        assert_eq!(line.kind, RgLineKind::Context);
+        assert_eq!(line.file_path, "src/a-1-b.rs");
+        assert_eq!(line.line_number, 41);
+        assert_eq!(line.column, None);
+        assert_eq!(line.content, "// comment: 1:2");
+
+        // Colon separated fallback
+        let line = RgLine::parse("src/main.rs:42:fn main() {").unwrap();
+        assert_eq!(line.kind, RgLineKind::Match);
+        assert_eq!(line.line_number, 42);
+        let line = RgLine::parse("src/main.rs:42:7:fn main() {").unwrap();
+        assert_eq!(line.column, Some(7));
+        assert_eq!(line.content, "fn main() {");
+
+        // Invalid lines
+        assert!(RgLine::parse("--").is_none());
+        assert!(RgLine::parse("src/main.rs\u{0}abc:content").is_none());
+        assert!(RgLine::parse("\u{0}1:content").is_none());
+        assert!(RgLine::parse("src/main.rs\u{0}1?content").is_none());
+    }
+
+    #[test]
+    fn test_parser_without_context() {
+        let results = parse_all(&["a.rs\u{0}1:4:fn main", "b.rs\u{0}2:1:main()"], 0);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].column, Some(4));
+        assert_eq!(results[0].line_content, "fn main");
+        assert!(results[0].context_before.is_empty());
+        assert!(results[0].context_after.is_empty());
+        assert_eq!(results[1].file_path, "b.rs");
+    }
+
+    #[test]
+    fn test_parser_attaches_context() {
+        let results = parse_all(
+            &[
+                "a.rs\u{0}1-// before",
+                "a.rs\u{0}2:fn main() {",
+                "a.rs\u{0}3-    body();",
+                "a.rs\u{0}4-}",
+                "--",
+                "b.rs\u{0}9-use x;",
+                "b.rs\u{0}10:main();",
+            ],
+            2,
+        );
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].context_before, vec!["// before"]);
+        assert_eq!(results[0].context_after, vec!["    body();", "}"]);
+        assert_eq!(results[1].file_path, "b.rs");
+        assert_eq!(results[1].context_before, vec!["use x;"]);
+        assert!(results[1].context_after.is_empty());
+    }
+
+    #[test]
+    fn test_parser_shares_context_between_close_matches() {
+        // Line 2 is after-context of line 1 and before-context of line 3
+        let results = parse_all(
+            &["a.rs\u{0}1:main", "a.rs\u{0}2-shared", "a.rs\u{0}3:main"],
+            1,
+        );
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].context_after, vec!["shared"]);
+        assert_eq!(results[1].context_before, vec!["shared"]);
+    }
+
+    #[test]
+    fn test_parser_with_json() {
+        let lines = [
+            r#"{"type":"begin","data":{"path":{"text":"./a.rs"}}}"#,
+            r#"{"type":"match","data":{"path":{"text":"./a.rs"},"lines":{"text":"a main b main\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"main"},"start":2,"end":6},{"match":{"text":"main"},"start":9,"end":13}]}}"#,
+            r#"{"type":"context","data":{"path":{"text":"./a.rs"},"lines":{"text":"x\r\n"},"line_number":2,"absolute_offset":14,"submatches":[]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"./a.rs"},"binary_offset":null,"stats":{}}}"#,
+            r#"{"type":"context","data":{"path":{"text":"./b.rs"},"lines":{"text":"before\n"},"line_number":6,"absolute_offset":22,"submatches":[]}}"#,
+            // Paths and lines that are not valid UTF-8 are reported as base64 bytes
+            r#"{"type":"match","data":{"path":{"bytes":"Li9iLnJz"},"lines":{"text":"main again\n"},"line_number":7,"absolute_offset":24,"submatches":[{"match":{"text":"main"},"start":0,"end":4}]}}"#,
+            r#"{"data":{"elapsed_total":{"secs":0}},"type":"summary"}"#,
+        ];
+        let mut parser = RgOutputParser::new("main", &[".".to_string()], 1).with_json();
+        let mut results: Vec<SearchResult> = lines
+            .iter()
+            .flat_map(|line| parser.push_line(line))
+            .collect();
+        results.extend(parser.finish());
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].line_content, "a main b main");
+        assert_eq!(results[0].column, Some(3));
+        assert_eq!(results[0].match_spans, vec![(2, 6), (9, 13)]);
+        assert_eq!(results[0].context_after, vec!["x"]);
+        assert_eq!(results[1].file_path, "./b.rs");
+        assert_eq!(results[1].match_spans, vec![(0, 4)]);
+        assert_eq!(results[1].context_before, vec!["before"]);
+
+        // Plain text output is still read
+        let mut parser = RgOutputParser::new("main", &[".".to_string()], 0).with_json();
+        let results = parser.push_line("a.rs\u{0}1:4:fn main");
+        assert_eq!(results[0].column, Some(4));
+        assert!(results[0].match_spans.is_empty());
+    }
+
+    #[test]
+    fn test_parser_with_fuzzy_matcher() {
+        let mut parser = RgOutputParser::new("fnmn", &[".".to_string()], 0)
+            .with_fuzzy_matcher(Some(FuzzyMatcher::new("fnmn")));
+
+        let results = parser.push_line("a.rs\u{0}1:1:fn main() {");
+        assert_eq!(results.len(), 1);
+        assert!(results[0].match_score.is_some());
+
+        // Candidate lines without a fuzzy match are dropped
+        assert!(parser.push_line("a.rs\u{0}2:1:f m n").is_empty());
+    }
+
+    #[test]
+    fn test_base_dir_for() {
+        let dirs = vec!["/home/user/project".to_string(), ".".to_string()];
+        assert_eq!(
+            base_dir_for("/home/user/project/src/lib.rs", &dirs),
+            Some("/home/user/project".to_string())
+        );
+        assert_eq!(base_dir_for("./src/lib.rs", &dirs), None);
+
+        // A directory is only a base for the files inside it, not for siblings sharing its name
+        let dirs = vec![
+            "src".to_string(),
+            "src-gen".to_string(),
+            "src/api".to_string(),
+        ];
+        assert_eq!(
+            base_dir_for("src-gen/types.rs", &dirs),
+            Some("src-gen".to_string())
+        );
+        assert_eq!(base_dir_for("src/main.rs", &dirs), Some("src".to_string()));
+        assert_eq!(
+            base_dir_for("src/api/routes.rs", &dirs),
+            Some("src/api".to_string())
+        );
+        assert_eq!(base_dir_for("srcs/lib.rs", &dirs), None);
+    }
+}
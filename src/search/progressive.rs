@@ -0,0 +1,89 @@
+//! Progressive loading of search results.
+//!
+//! The search output is read in the background (see `stream`), and the TUI takes
+//! the results in batches so a frame never waits for the search or takes in more
+//! results than it can draw quickly.
+
+use super::SearchResult;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::Receiver;
+
+/// Results read in the background, handed out a batch at a time
+#[derive(Debug)]
+pub struct ProgressiveLoader {
+    receiver: Receiver<SearchResult>,
+    /// Set once the reader finished and every result was handed out
+    exhausted: bool,
+}
+
+impl ProgressiveLoader {
+    pub fn new(receiver: Receiver<SearchResult>) -> Self {
+        Self {
+            receiver,
+            exhausted: false,
+        }
+    }
+
+    /// Take up to `batch_size` of the results read so far without waiting for more
+    pub fn next_batch(&mut self, batch_size: usize) -> Vec<SearchResult> {
+        let mut batch = Vec::new();
+        while batch.len() < batch_size {
+            match self.receiver.try_recv() {
+                Ok(result) => batch.push(result),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.exhausted = true;
+                    break;
+                }
+            }
+        }
+        batch
+    }
+
+    /// Wait for the next result, then take up to `batch_size` of the results read so far
+    /// Returns no results once the loader is exhausted. Safe to cancel in `select!`
+    pub async fn recv_batch(&mut self, batch_size: usize) -> Vec<SearchResult> {
+        match self.receiver.recv().await {
+            Some(result) => {
+                let mut batch = vec![result];
+                batch.extend(self.next_batch(batch_size.saturating_sub(1)));
+                batch
+            }
+            None => {
+                self.exhausted = true;
+                Vec::new()
+            }
+        }
+    }
+
+    /// Checks if the search finished and every result was handed out
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::mock_backend::mock_result;
+    use crate::search::stream::spawn_blocking_search;
+
+    #[tokio::test]
+    async fn test_progressive_loader_batches() {
+        let results: Vec<SearchResult> = (1..=250)
+            .map(|line| mock_result("a.rs", line, "fn main() {"))
+            .collect();
+        let (_task, mut loader) = spawn_blocking_search(Vec::new(), move || results);
+
+        let mut loaded = Vec::new();
+        while !loader.is_exhausted() {
+            let batch = loader.recv_batch(100).await;
+            assert!(batch.len() <= 100);
+            loaded.extend(batch);
+        }
+
+        assert_eq!(loaded.len(), 250);
+        assert_eq!(loaded[249].line_number, 250);
+        assert!(loader.next_batch(100).is_empty());
+    }
+}
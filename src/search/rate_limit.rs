@@ -0,0 +1,123 @@
+//! Rate limit for spawning searches.
+//!
+//! Live search starts a ripgrep process whenever typing pauses. A search requested
+//! less than the minimum interval after the last one is queued instead, replacing any
+//! search already queued, and runs once the interval has passed.
+
+use crate::{Result, SearchError};
+use std::time::{Duration, Instant};
+
+/// Minimum time between search spawns, with at most one search waiting for it
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    min_interval: Duration,
+    /// When the last search was spawned
+    last_spawn: Option<Instant>,
+    /// Whether a search is waiting for the interval to pass
+    queued: bool,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter, 0 lets every search through
+    pub fn new(min_interval_ms: u64) -> Self {
+        Self {
+            min_interval: Duration::from_millis(min_interval_ms),
+            last_spawn: None,
+            queued: false,
+        }
+    }
+
+    /// Check if a search may be spawned now
+    /// Otherwise the search is queued and `SearchError::RateLimit` has the time left to wait
+    pub fn request(&mut self) -> Result<()> {
+        self.request_at(Instant::now())
+    }
+
+    fn request_at(&mut self, now: Instant) -> Result<()> {
+        match self.allowed_at().filter(|&allowed| allowed > now) {
+            Some(allowed) => {
+                self.queued = true;
+                Err(SearchError::RateLimit(allowed - now))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Record that a search was spawned, it replaces the queued one
+    pub fn record_spawn(&mut self) {
+        self.record_spawn_at(Instant::now());
+    }
+
+    fn record_spawn_at(&mut self, now: Instant) {
+        self.last_spawn = Some(now);
+        self.queued = false;
+    }
+
+    /// Drop the queued search
+    pub fn cancel(&mut self) {
+        self.queued = false;
+    }
+
+    /// When the queued search may be spawned, `None` when no search is queued
+    pub fn next_allowed_at(&self) -> Option<Instant> {
+        if !self.queued {
+            return None;
+        }
+        self.allowed_at()
+    }
+
+    /// End of the interval after the last spawn
+    fn allowed_at(&self) -> Option<Instant> {
+        self.last_spawn.map(|last| last + self.min_interval)
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(100);
+        assert!(limiter.request_at(start).is_ok());
+        limiter.record_spawn_at(start);
+        assert_eq!(limiter.next_allowed_at(), None);
+
+        // Searches within the interval are queued until it ends
+        let Err(SearchError::RateLimit(wait)) =
+            limiter.request_at(start + Duration::from_millis(30))
+        else {
+            panic!("search was not rate limited");
+        };
+        assert_eq!(wait, Duration::from_millis(70));
+        assert!(limiter
+            .request_at(start + Duration::from_millis(60))
+            .is_err());
+        assert_eq!(
+            limiter.next_allowed_at(),
+            Some(start + Duration::from_millis(100))
+        );
+
+        // Spawning the queued search starts the next interval
+        let later = start + Duration::from_millis(100);
+        assert!(limiter.request_at(later).is_ok());
+        limiter.record_spawn_at(later);
+        assert_eq!(limiter.next_allowed_at(), None);
+
+        assert!(limiter.request_at(later).is_err());
+        limiter.cancel();
+        assert_eq!(limiter.next_allowed_at(), None);
+
+        // Without an interval every search goes through
+        let mut limiter = RateLimiter::default();
+        limiter.record_spawn_at(start);
+        assert!(limiter.request_at(start).is_ok());
+    }
+}
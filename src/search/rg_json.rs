@@ -0,0 +1,232 @@
+//! Ripgrep's JSON output.
+//!
+//! With `--json` ripgrep prints one message per line: `begin` and `end` around the
+//! lines of each file, `match` and `context` for the lines themselves and a final
+//! `summary`. Unlike the plain-text output it gives the byte offsets of every match.
+
+use super::parser::{RgLine, RgLineKind};
+use base64::prelude::*;
+use serde::Deserialize;
+
+/// Message of ripgrep's `--json` output
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+pub enum RgMessage {
+    /// Start of the lines of a file
+    Begin(RgBegin),
+    /// Line containing a match
+    Match(RgLineData),
+    /// Context line printed around a match
+    Context(RgLineData),
+    /// End of the lines of a file
+    End(RgEnd),
+    /// Totals of the whole search, the last message
+    Summary(RgSummary),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RgBegin {
+    pub path: RgData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RgEnd {
+    pub path: RgData,
+    #[serde(default)]
+    pub stats: RgStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RgSummary {
+    #[serde(default)]
+    pub elapsed_total: RgDuration,
+    #[serde(default)]
+    pub stats: RgStats,
+}
+
+/// Text ripgrep reports as `text`, or base64 encoded as `bytes` when it isn't valid UTF-8
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RgData {
+    pub text: Option<String>,
+    pub bytes: Option<String>,
+}
+
+/// Match or context line
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RgLineData {
+    pub path: RgData,
+    pub lines: RgData,
+    pub line_number: Option<usize>,
+    #[serde(default)]
+    pub absolute_offset: u64,
+    #[serde(default)]
+    pub submatches: Vec<RgSubmatch>,
+}
+
+/// Match on a line, with the byte offsets of its start and end
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RgSubmatch {
+    #[serde(rename = "match")]
+    pub matched: RgData,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RgStats {
+    pub searches: u64,
+    pub searches_with_match: u64,
+    pub bytes_searched: u64,
+    pub bytes_printed: u64,
+    pub matched_lines: u64,
+    pub matches: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RgDuration {
+    pub secs: u64,
+    pub nanos: u32,
+    pub human: String,
+}
+
+impl RgData {
+    /// Decode `bytes` into `text` when there is no `text`, invalid UTF-8 becomes U+FFFD
+    /// Returns the decoded bytes
+    fn decode_bytes(&mut self) -> Option<Vec<u8>> {
+        if self.text.is_some() {
+            return None;
+        }
+        let bytes = BASE64_STANDARD.decode(self.bytes.as_deref()?).ok()?;
+        self.text = Some(String::from_utf8_lossy(&bytes).into_owned());
+        Some(bytes)
+    }
+}
+
+impl RgLineData {
+    /// Decode a path or line reported as `bytes`, moving the match offsets along
+    fn decode_bytes(&mut self) {
+        self.path.decode_bytes();
+        if let Some(bytes) = self.lines.decode_bytes() {
+            // The offsets count raw bytes, a replacement character takes up three
+            let decoded_offset =
+                |offset: usize| String::from_utf8_lossy(&bytes[..offset.min(bytes.len())]).len();
+            for submatch in &mut self.submatches {
+                submatch.start = decoded_offset(submatch.start);
+                submatch.end = decoded_offset(submatch.end);
+            }
+        }
+    }
+}
+
+impl RgMessage {
+    /// Parse a line of `--json` output, `None` when it isn't a ripgrep message
+    /// Paths and lines that are not UTF-8 are decoded lossily
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut message: Self = serde_json::from_str(line).ok()?;
+        if let RgMessage::Match(data) | RgMessage::Context(data) = &mut message {
+            data.decode_bytes();
+        }
+        Some(message)
+    }
+
+    /// Fields of a match or context line, `None` for other messages
+    pub fn rg_line(&self) -> Option<RgLine<'_>> {
+        let (kind, data) = match self {
+            RgMessage::Match(data) => (RgLineKind::Match, data),
+            RgMessage::Context(data) => (RgLineKind::Context, data),
+            _ => return None,
+        };
+        let file_path = data.path.text.as_deref()?;
+        let lines = data.lines.text.as_deref()?;
+        let content = lines.strip_suffix('\n').unwrap_or(lines);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        let match_spans: Vec<(usize, usize)> = data
+            .submatches
+            .iter()
+            .filter(|submatch| {
+                submatch.start < submatch.end
+                    && submatch.end <= content.len()
+                    && content.is_char_boundary(submatch.start)
+                    && content.is_char_boundary(submatch.end)
+            })
+            .map(|submatch| (submatch.start, submatch.end))
+            .collect();
+        Some(RgLine {
+            kind,
+            file_path,
+            line_number: data.line_number?,
+            // Columns are 1-based like the ones `--column` prints
+            column: match kind {
+                RgLineKind::Match => match_spans.first().map(|(start, _)| start + 1),
+                RgLineKind::Context => None,
+            },
+            content,
+            match_spans,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_messages() {
+        let line = r#"{"type":"begin","data":{"path":{"text":"./a.rs"}}}"#;
+        let Some(RgMessage::Begin(begin)) = RgMessage::parse(line) else {
+            panic!("not a begin message");
+        };
+        assert_eq!(begin.path.text.as_deref(), Some("./a.rs"));
+
+        let line = r#"{"type":"match","data":{"path":{"text":"./a.rs"},"lines":{"text":"a main b main\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"main"},"start":2,"end":6},{"match":{"text":"main"},"start":9,"end":13}]}}"#;
+        let message = RgMessage::parse(line).unwrap();
+        let parsed = message.rg_line().unwrap();
+        assert_eq!(parsed.kind, RgLineKind::Match);
+        assert_eq!(parsed.file_path, "./a.rs");
+        assert_eq!(parsed.line_number, 1);
+        assert_eq!(parsed.column, Some(3));
+        assert_eq!(parsed.content, "a main b main");
+        assert_eq!(parsed.match_spans, vec![(2, 6), (9, 13)]);
+
+        let line = r#"{"type":"context","data":{"path":{"text":"./a.rs"},"lines":{"text":"x\r\n"},"line_number":2,"absolute_offset":14,"submatches":[]}}"#;
+        let message = RgMessage::parse(line).unwrap();
+        let parsed = message.rg_line().unwrap();
+        assert_eq!(parsed.kind, RgLineKind::Context);
+        assert_eq!(parsed.column, None);
+        assert_eq!(parsed.content, "x");
+
+        let line = r#"{"type":"end","data":{"path":{"text":"./a.rs"},"binary_offset":null,"stats":{"elapsed":{"secs":0,"nanos":4438817,"human":"0.004439s"},"searches":1,"searches_with_match":1,"bytes_searched":35,"bytes_printed":714,"matched_lines":2,"matches":3}}}"#;
+        let Some(RgMessage::End(end)) = RgMessage::parse(line) else {
+            panic!("not an end message");
+        };
+        assert_eq!(end.stats.matches, 3);
+
+        // The summary puts its data first
+        let line = r#"{"data":{"elapsed_total":{"human":"0.005112s","nanos":5111512,"secs":0},"stats":{"bytes_printed":714,"bytes_searched":35,"matched_lines":2,"matches":3,"searches":1,"searches_with_match":1}},"type":"summary"}"#;
+        let Some(RgMessage::Summary(summary)) = RgMessage::parse(line) else {
+            panic!("not a summary message");
+        };
+        assert_eq!(summary.elapsed_total.human, "0.005112s");
+        assert_eq!(summary.stats.matched_lines, 2);
+        assert!(RgMessage::Summary(summary).rg_line().is_none());
+
+        // Paths and lines that are not UTF-8 arrive base64 encoded and are decoded lossily
+        let line = r#"{"type":"match","data":{"path":{"bytes":"/w=="},"lines":{"text":"main\n"},"line_number":7,"absolute_offset":24,"submatches":[]}}"#;
+        let message = RgMessage::parse(line).unwrap();
+        assert_eq!(message.rg_line().unwrap().file_path, "\u{FFFD}");
+
+        // "café main" in Latin-1, the match offsets move past the replacement character
+        let line = r#"{"type":"match","data":{"path":{"text":"./menu.txt"},"lines":{"bytes":"Y2Fm6SBtYWluCg=="},"line_number":3,"absolute_offset":10,"submatches":[{"match":{"text":"main"},"start":5,"end":9}]}}"#;
+        let message = RgMessage::parse(line).unwrap();
+        let parsed = message.rg_line().unwrap();
+        assert_eq!(parsed.content, "caf\u{FFFD} main");
+        assert_eq!(parsed.match_spans, vec![(7, 11)]);
+        assert_eq!(&parsed.content[7..11], "main");
+        assert_eq!(parsed.column, Some(8));
+
+        assert!(RgMessage::parse("a.rs\u{0}1:main").is_none());
+    }
+}
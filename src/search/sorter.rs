@@ -4,20 +4,97 @@
 //! Most recently modified lines are prioritized in search results.
 
 use super::SearchResult;
+use crate::constants::{RELEVANCE_DEPTH_WEIGHT, RELEVANCE_MTIME_WEIGHT, RELEVANCE_POSITION_WEIGHT};
+use crate::logging::warn_log;
+use crate::{Result, SearchError};
+use clap::ValueEnum;
 use git2::Repository;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::time::SystemTime;
 
+/// Order in which sorted results are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// Most recently modified lines first (git blame, file mtime as fallback)
+    #[default]
+    ModificationTime,
+    /// By file path, then line number
+    Alphabetical,
+    /// Files with the most matches first
+    MatchCount,
+    /// By line number, then file path
+    LineNumber,
+    /// Matches early in recently modified, shallow files first
+    Relevance,
+}
+
+impl SortOrder {
+    /// Short label for the status bar
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::ModificationTime => "modified",
+            SortOrder::Alphabetical => "path",
+            SortOrder::MatchCount => "match count",
+            SortOrder::LineNumber => "line",
+            SortOrder::Relevance => "relevance",
+        }
+    }
+}
+
+/// Order of results the sort order ranks the same, e.g. lines of a file modified at
+/// the same time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecondarySortOrder {
+    /// By ascending line number
+    #[default]
+    ByLineNumber,
+}
+
+/// Last commit that changed a line, from git blame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitBlameInfo {
+    pub author: String,
+    /// Abbreviated commit hash
+    pub short_hash: String,
+    /// Commit time
+    pub timestamp: SystemTime,
+}
+
 /// Sorts search results based on file modification time using git line history and file metadata
 pub struct FileSorter {
     /// Whether sorting is enabled
     enabled: bool,
+    /// Sort order used when sorting is enabled
+    sort_order: SortOrder,
+    /// Whether the sort order is reversed, e.g. oldest first
+    reverse: bool,
+    /// Whether fuzzy match score is the primary sort key
+    sort_by_score: bool,
+    /// Order of results the sort order ranks the same, `None` keeps them in arrival order
+    secondary_sort: Option<SecondarySortOrder>,
     /// global sorted results maintained across all modules
     global_results: Vec<SearchResult>,
     /// metadata cache to avoid re-reading file metadata
     metadata_cache: HashMap<String, SystemTime>,
+    /// Size of each file looked up so far, `None` for files whose metadata can't be read
+    size_cache: HashMap<String, Option<u64>>,
+    /// git blame of each line looked up so far, `None` for lines outside the repository
+    blame_cache: HashMap<String, Option<GitBlameInfo>>,
+    /// Number of matches per file, for the match count order
+    match_counts: HashMap<String, usize>,
+    /// Whether results already added for the same file and line are skipped
+    deduplicate: bool,
+    /// Whether modification times are looked up for every result, not only when sorting by them
+    record_modification_times: bool,
+    /// Whether the git blame of every added result is stored on the result
+    record_blame: bool,
+    /// File and line of every added result, when deduplicating
+    seen: HashSet<(String, usize)>,
     /// Git repository for line history (if available)
     git_repo: Option<Repository>,
 }
@@ -27,6 +104,10 @@ impl std::fmt::Debug for FileSorter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FileSorter")
             .field("enabled", &self.enabled)
+            .field("sort_order", &self.sort_order)
+            .field("reverse", &self.reverse)
+            .field("sort_by_score", &self.sort_by_score)
+            .field("secondary_sort", &self.secondary_sort)
             .field("global_results", &self.global_results.len())
             .field("metadata_cache", &self.metadata_cache.len())
             .field(
@@ -56,8 +137,19 @@ impl Clone for FileSorter {
 
         Self {
             enabled: self.enabled,
+            sort_order: self.sort_order,
+            reverse: self.reverse,
+            sort_by_score: self.sort_by_score,
+            secondary_sort: self.secondary_sort,
             global_results: self.global_results.clone(),
             metadata_cache: self.metadata_cache.clone(),
+            size_cache: self.size_cache.clone(),
+            blame_cache: self.blame_cache.clone(),
+            match_counts: self.match_counts.clone(),
+            deduplicate: self.deduplicate,
+            record_modification_times: self.record_modification_times,
+            record_blame: self.record_blame,
+            seen: self.seen.clone(),
             git_repo,
         }
     }
@@ -76,9 +168,20 @@ impl FileSorter {
 
         Self {
             enabled: false,
+            sort_order: SortOrder::default(),
+            reverse: false,
+            sort_by_score: false,
+            secondary_sort: Some(SecondarySortOrder::default()),
             global_results: Vec::new(),
             metadata_cache: HashMap::new(),
-            git_repo: git_repo,
+            size_cache: HashMap::new(),
+            blame_cache: HashMap::new(),
+            match_counts: HashMap::new(),
+            deduplicate: true,
+            record_modification_times: false,
+            record_blame: false,
+            seen: HashSet::new(),
+            git_repo,
         }
     }
 
@@ -92,10 +195,99 @@ impl FileSorter {
         self.enabled
     }
 
+    /// Skip results for a file and line that was already added
+    pub fn set_deduplicate(&mut self, deduplicate: bool) {
+        self.deduplicate = deduplicate;
+    }
+
+    /// Checks if duplicate results are skipped
+    pub fn is_deduplicating(&self) -> bool {
+        self.deduplicate
+    }
+
+    /// Look up the modification time of every added result, e.g. to show it
+    pub fn set_record_modification_times(&mut self, record: bool) {
+        self.record_modification_times = record;
+    }
+
+    /// Look up the git blame of every added result and store it on the result
+    pub fn set_record_blame(&mut self, record: bool) {
+        self.record_blame = record;
+    }
+
+    /// Modification time of a result looked up when it was added
+    pub fn cached_modification_time(&self, result: &SearchResult) -> Option<SystemTime> {
+        let cache_key = format!("{}:{}", result.file_path, result.line_number);
+        self.metadata_cache.get(&cache_key).copied()
+    }
+
+    /// Checks if a result for the same file and line was already added
+    pub fn contains(&self, result: &SearchResult) -> bool {
+        self.seen
+            .contains(&(result.file_path.clone(), result.line_number))
+    }
+
+    /// Set the sort order used when sorting is enabled
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        self.sort_order = sort_order;
+    }
+
+    /// Get the current sort order
+    pub fn sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    /// Reverse the sort order, re-sorting the results collected so far
+    pub fn set_reverse(&mut self, reverse: bool) {
+        if self.reverse == reverse {
+            return;
+        }
+        self.reverse = reverse;
+        if self.enabled {
+            let mut global_results = std::mem::take(&mut self.global_results);
+            self.sort_results(&mut global_results);
+            self.global_results = global_results;
+        }
+    }
+
+    /// Checks if the sort order is reversed
+    pub fn is_reversed(&self) -> bool {
+        self.reverse
+    }
+
+    /// Set the order of results the sort order ranks the same
+    pub fn set_secondary_sort(&mut self, secondary_sort: Option<SecondarySortOrder>) {
+        self.secondary_sort = secondary_sort;
+    }
+
+    /// Get the order of results the sort order ranks the same
+    pub fn secondary_sort(&self) -> Option<SecondarySortOrder> {
+        self.secondary_sort
+    }
+
+    /// Checks if git line history is available for the modification time order
+    pub fn has_git_repo(&self) -> bool {
+        self.git_repo.is_some()
+    }
+
+    /// Sort by fuzzy match score first (used in fuzzy mode)
+    pub fn set_sort_by_score(&mut self, sort_by_score: bool) {
+        self.sort_by_score = sort_by_score;
+    }
+
+    /// Checks if results are sorted by fuzzy match score
+    pub fn is_sorting_by_score(&self) -> bool {
+        self.sort_by_score
+    }
+
     /// Clear all sorted results and metadata cache
     pub fn clear(&mut self) {
         self.global_results.clear();
         self.metadata_cache.clear();
+        self.size_cache.clear();
+        self.blame_cache.clear();
+        self.match_counts.clear();
+        self.seen.clear();
     }
 
     /// Get the current count of sorted results
@@ -113,6 +305,34 @@ impl FileSorter {
         &self.global_results
     }
 
+    /// Size of a file in bytes (with caching), `None` when its metadata can't be read
+    pub fn file_size(&mut self, file_path: &str) -> Option<u64> {
+        if let Some(size) = self.size_cache.get(file_path) {
+            return *size;
+        }
+
+        let size = match Self::read_file_size(file_path) {
+            Ok(size) => Some(size),
+            Err(e) => {
+                warn_log(&format!("Failed to read the file size: {:?}", e));
+                None
+            }
+        };
+        self.size_cache.insert(file_path.to_string(), size);
+        size
+    }
+
+    /// Size of a file looked up by an earlier `file_size` call
+    pub fn cached_file_size(&self, file_path: &str) -> Option<u64> {
+        self.size_cache.get(file_path).copied().flatten()
+    }
+
+    fn read_file_size(file_path: &str) -> Result<u64> {
+        fs::metadata(file_path)
+            .map(|metadata| metadata.len())
+            .map_err(|e| SearchError::file_access_error(file_path, &e.to_string()))
+    }
+
     /// Get the file modification time of a line using git history (with caching)
     fn get_modification_time(&mut self, result: &SearchResult) -> SystemTime {
         let cache_key = format!("{}:{}", result.file_path, result.line_number);
@@ -121,7 +341,8 @@ impl FileSorter {
         }
 
         let mtime = self
-            .get_git_line_modification_time(&result.file_path, result.line_number)
+            .get_blame_info(result)
+            .map(|blame| blame.timestamp)
             .unwrap_or_else(|| {
                 // Fallback to file metadata if git line history is unavailable
                 // and_then is daisy-chained only if first operation is successful the second one is executed
@@ -136,12 +357,20 @@ impl FileSorter {
         mtime
     }
 
-    /// Get git line modification time using blame
-    fn get_git_line_modification_time(
-        &self,
-        file_path: &str,
-        line_number: usize,
-    ) -> Option<SystemTime> {
+    /// Get the git blame of a line (with caching)
+    fn get_blame_info(&mut self, result: &SearchResult) -> Option<GitBlameInfo> {
+        let cache_key = format!("{}:{}", result.file_path, result.line_number);
+        if let Some(blame) = self.blame_cache.get(&cache_key) {
+            return blame.clone();
+        }
+
+        let blame = self.get_git_blame_info(&result.file_path, result.line_number);
+        self.blame_cache.insert(cache_key, blame.clone());
+        blame
+    }
+
+    /// Get the author, commit and time of the last change to a line using blame
+    fn get_git_blame_info(&self, file_path: &str, line_number: usize) -> Option<GitBlameInfo> {
         let repo = self.git_repo.as_ref()?;
 
         // Convert absolute path to relative path within git repo
@@ -165,32 +394,76 @@ impl FileSorter {
         // Get the commit that modified the line
         let commit_oid = hunk.final_commit_id();
         let commit = repo.find_commit(commit_oid).ok()?;
+        let author = String::from_utf8_lossy(commit.author().name_bytes()).into_owned();
+        let short_hash = commit_oid.to_string().chars().take(7).collect();
 
         // Convert git time to SystemTime
         let git_time = commit.time();
         let timestamp = git_time.seconds();
 
         // Convert to SystemTime
-        if timestamp >= 0 {
-            Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp as u64))
+        let timestamp = if timestamp >= 0 {
+            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp as u64)
         } else {
             // Handles negative timestamps (before epoch)
             let duration = std::time::Duration::from_secs(-timestamp as u64);
-            SystemTime::UNIX_EPOCH.checked_sub(duration)
-        }
+            SystemTime::UNIX_EPOCH.checked_sub(duration)?
+        };
+
+        Some(GitBlameInfo {
+            author,
+            short_hash,
+            timestamp,
+        })
     }
 
     /// Add new results to the global sorted collection
     /// Retunrs only the newly added results in their correct sorted positions
     pub fn add_results(&mut self, mut new_results: Vec<SearchResult>) -> Vec<SearchResult> {
-        if !self.enabled || new_results.is_empty() {
+        if self.deduplicate {
+            new_results.retain(|result| {
+                self.seen
+                    .insert((result.file_path.clone(), result.line_number))
+            });
+        }
+
+        if self.record_blame {
+            for result in &mut new_results {
+                result.blame_info = self.get_blame_info(result);
+            }
+        }
+
+        // Pre-populate metadata cache for the new results
+        if self.record_modification_times
+            || (self.enabled
+                && matches!(
+                    self.sort_order,
+                    SortOrder::ModificationTime | SortOrder::Relevance
+                ))
+        {
+            for result in &new_results {
+                self.get_modification_time(result);
+            }
+        }
+
+        if !(self.enabled || self.sort_by_score) || new_results.is_empty() {
             self.global_results.extend(new_results.clone());
             return new_results;
         }
 
-        // Pre-populate metadata cache for the new results
-        for result in &new_results {
-            self.get_modification_time(result);
+        // New matches change the rank of files already placed, so re-sort everything
+        if self.enabled && self.sort_order == SortOrder::MatchCount {
+            for result in &new_results {
+                *self
+                    .match_counts
+                    .entry(result.file_path.clone())
+                    .or_insert(0) += 1;
+            }
+            let mut global_results = std::mem::take(&mut self.global_results);
+            global_results.extend(new_results.iter().cloned());
+            self.sort_results(&mut global_results);
+            self.global_results = global_results;
+            return new_results;
         }
 
         // Sort the new batch internally first
@@ -220,7 +493,8 @@ impl FileSorter {
             let global_result = &self.global_results[i];
             let batch_result = &sorted_batch[j];
 
-            if self.compare_results(global_result, batch_result) == std::cmp::Ordering::Equal {
+            // Keep existing results first on ties so the merge is stable
+            if self.compare_results(global_result, batch_result) != Ordering::Greater {
                 merged.push(self.global_results[i].clone());
                 i += 1;
             } else {
@@ -242,34 +516,66 @@ impl FileSorter {
         self.global_results = merged;
     }
 
-    /// Merge a sorted batch of results with the global results
-    fn merge_sorted_results_mut(&mut self, sorted_batch: Vec<SearchResult>) {
-        let mut merged = Vec::with_capacity(self.global_results.len() + sorted_batch.len());
-        let mut i = 0;
-        let mut j = 0;
+    /// Compares two search results based on sorting criteria
+    fn compare_results(&self, a: &SearchResult, b: &SearchResult) -> Ordering {
+        // Best fuzzy score first, results without a score last
+        if self.sort_by_score {
+            let by_score = b.match_score.cmp(&a.match_score);
+            if by_score != Ordering::Equal || !self.enabled {
+                return by_score;
+            }
+        }
 
-        // Merge the two sorted batches
-        while i < self.global_results.len() && j < sorted_batch.len() {
-            let global_result = &self.global_results[i];
-            let batch_result = &sorted_batch[j];
+        let ordering = self.compare_by_sort_order(a, b);
+        let ordering = if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then_with(|| match self.secondary_sort {
+            Some(SecondarySortOrder::ByLineNumber) => a.line_number.cmp(&b.line_number),
+            None => Ordering::Equal,
+        })
+    }
 
-            if self.compare_results(global_result, batch_result) == std::cmp::Ordering::Equal {
-                merged.push(self.global_results[i].clone());
-                i += 1;
-            } else {
-                merged.push(sorted_batch[j].clone());
-                j += 1;
+    /// Compares two search results by the current sort order
+    fn compare_by_sort_order(&self, a: &SearchResult, b: &SearchResult) -> Ordering {
+        let by_path = || {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_number.cmp(&b.line_number))
+        };
+        match self.sort_order {
+            SortOrder::ModificationTime => self.compare_modification_time(a, b),
+            SortOrder::Alphabetical => by_path(),
+            SortOrder::MatchCount => {
+                let count = |result: &SearchResult| self.match_counts.get(&result.file_path);
+                count(b).cmp(&count(a)).then_with(by_path)
             }
+            SortOrder::LineNumber => a
+                .line_number
+                .cmp(&b.line_number)
+                .then(a.file_path.cmp(&b.file_path)),
+            SortOrder::Relevance => self.compare_relevance(a, b).then_with(by_path),
         }
+    }
 
-        // Add the remaining results from either array
-        while i < self.global_results.len() {
-            merged.push(self.global_results[i].clone());
-        }
+    /// Compares two search results by relevance score (highest first)
+    fn compare_relevance(&self, a: &SearchResult, b: &SearchResult) -> Ordering {
+        let score = |result: &SearchResult| {
+            let cache_key = format!("{}:{}", result.file_path, result.line_number);
+            result.relevance_score(
+                self.metadata_cache[&cache_key],
+                RELEVANCE_MTIME_WEIGHT,
+                RELEVANCE_POSITION_WEIGHT,
+                RELEVANCE_DEPTH_WEIGHT,
+            )
+        };
+        score(b).total_cmp(&score(a))
     }
 
-    /// Compares two search results based on sorting criteria
-    fn compare_results(&self, a: &SearchResult, b: &SearchResult) -> std::cmp::Ordering {
+    /// Compares two search results by line modification time
+    fn compare_modification_time(&self, a: &SearchResult, b: &SearchResult) -> Ordering {
         let cache_key_a = format!("{}:{}", a.file_path, a.line_number);
         let cache_key_b = format!("{}:{}", b.file_path, b.line_number);
 
@@ -290,16 +596,15 @@ impl FileSorter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::mock_backend::{collect_results, mock_result, MockBackend};
 
     fn create_test_result(file_path: &str, line_number: usize) -> SearchResult {
-        SearchResult::new(
-            file_path.to_string(),
-            line_number,
-            "test content".to_string(),
-            "test".to_string(),
-            None,
-            None,
-        )
+        SearchResult::builder()
+            .file_path(file_path)
+            .line_number(line_number)
+            .line_content("test content")
+            .matched_text("test")
+            .build()
     }
 
     #[test]
@@ -314,6 +619,266 @@ mod tests {
         assert!(sorter.is_enabled());
     }
 
+    #[test]
+    fn test_add_results_skips_duplicates() {
+        let mut sorter = FileSorter::new();
+        let added = sorter.add_results(vec![
+            create_test_result("a.rs", 1),
+            create_test_result("a.rs", 1),
+            create_test_result("a.rs", 2),
+        ]);
+        assert_eq!(added.len(), 2);
+        assert!(sorter.contains(&create_test_result("a.rs", 2)));
+
+        let added = sorter.add_results(vec![create_test_result("a.rs", 2)]);
+        assert!(added.is_empty());
+        assert_eq!(sorter.len(), 2);
+
+        // Without deduplication every result is kept
+        sorter.clear();
+        sorter.set_deduplicate(false);
+        sorter.add_results(vec![create_test_result("a.rs", 1); 2]);
+        assert_eq!(sorter.len(), 2);
+    }
+
+    #[test]
+    fn test_add_results_from_mock_backend() {
+        let engine = MockBackend::from_results(vec![
+            mock_result("b.rs", 4, "needle"),
+            mock_result("a.rs", 9, "needle"),
+            mock_result("a.rs", 2, "needle"),
+            mock_result("c.rs", 1, "haystack"),
+        ])
+        .into_engine();
+
+        let mut sorter = FileSorter::new();
+        sorter.set_enabled(true);
+        sorter.set_sort_order(SortOrder::Alphabetical);
+        sorter.add_results(collect_results(&engine, "needle"));
+        assert_eq!(
+            sorted_locations(&sorter),
+            [
+                location("a.rs", 2),
+                location("a.rs", 9),
+                location("b.rs", 4)
+            ]
+        );
+    }
+
+    fn sorted_locations(sorter: &FileSorter) -> Vec<(String, usize)> {
+        sorter
+            .get_all_results()
+            .iter()
+            .map(|result| (result.file_path.clone(), result.line_number))
+            .collect()
+    }
+
+    fn location(file_path: &str, line_number: usize) -> (String, usize) {
+        (file_path.to_string(), line_number)
+    }
+
+    #[test]
+    fn test_sort_orders() {
+        let batches = || {
+            vec![
+                vec![create_test_result("b.rs", 3), create_test_result("a.rs", 9)],
+                vec![
+                    create_test_result("c.rs", 1),
+                    create_test_result("b.rs", 1),
+                    create_test_result("a.rs", 2),
+                    create_test_result("c.rs", 5),
+                    create_test_result("c.rs", 7),
+                ],
+            ]
+        };
+        let sorted = |order: SortOrder| {
+            let mut sorter = FileSorter::new();
+            sorter.set_enabled(true);
+            sorter.set_sort_order(order);
+            assert_eq!(sorter.sort_order(), order);
+            for batch in batches() {
+                sorter.add_results(batch);
+            }
+            sorted_locations(&sorter)
+        };
+
+        assert_eq!(
+            sorted(SortOrder::Alphabetical),
+            vec![
+                location("a.rs", 2),
+                location("a.rs", 9),
+                location("b.rs", 1),
+                location("b.rs", 3),
+                location("c.rs", 1),
+                location("c.rs", 5),
+                location("c.rs", 7),
+            ]
+        );
+        assert_eq!(
+            sorted(SortOrder::LineNumber),
+            vec![
+                location("b.rs", 1),
+                location("c.rs", 1),
+                location("a.rs", 2),
+                location("b.rs", 3),
+                location("c.rs", 5),
+                location("c.rs", 7),
+                location("a.rs", 9),
+            ]
+        );
+        // c.rs overtakes the files of the first batch once its matches arrive
+        assert_eq!(
+            sorted(SortOrder::MatchCount),
+            vec![
+                location("c.rs", 1),
+                location("c.rs", 5),
+                location("c.rs", 7),
+                location("a.rs", 2),
+                location("a.rs", 9),
+                location("b.rs", 1),
+                location("b.rs", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relevance_sort() {
+        let with_column = |file_path: &str, line_number: usize, column: usize| {
+            let mut result = create_test_result(file_path, line_number);
+            result.column = Some(column);
+            result
+        };
+
+        let mut sorter = FileSorter::new();
+        sorter.set_enabled(true);
+        sorter.set_sort_order(SortOrder::Relevance);
+        sorter.add_results(vec![
+            with_column("deep/nested/a.rs", 1, 1),
+            with_column("b.rs", 2, 30),
+            with_column("c.rs", 3, 1),
+        ]);
+        sorter.add_results(vec![with_column("nested/d.rs", 4, 1)]);
+
+        // Missing files share the same modification time, so the match position and
+        // the file depth decide
+        assert_eq!(
+            sorted_locations(&sorter),
+            vec![
+                location("c.rs", 3),
+                location("nested/d.rs", 4),
+                location("deep/nested/a.rs", 1),
+                location("b.rs", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverse_sort() {
+        let mut sorter = FileSorter::new();
+        sorter.set_enabled(true);
+        sorter.set_sort_order(SortOrder::LineNumber);
+        sorter.add_results(vec![
+            create_test_result("a.rs", 2),
+            create_test_result("a.rs", 1),
+        ]);
+
+        // Toggling re-sorts the existing results, later batches follow the new direction
+        sorter.set_reverse(true);
+        assert!(sorter.is_reversed());
+        sorter.add_results(vec![create_test_result("a.rs", 3)]);
+        assert_eq!(
+            sorted_locations(&sorter),
+            vec![
+                location("a.rs", 3),
+                location("a.rs", 2),
+                location("a.rs", 1),
+            ]
+        );
+
+        sorter.set_reverse(false);
+        assert_eq!(
+            sorted_locations(&sorter),
+            vec![
+                location("a.rs", 1),
+                location("a.rs", 2),
+                location("a.rs", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_secondary_sort_by_line_number() {
+        // Files that don't exist all have the same modification time
+        let batches = || {
+            vec![
+                vec![create_test_result("a.rs", 7), create_test_result("a.rs", 2)],
+                vec![create_test_result("a.rs", 5), create_test_result("a.rs", 1)],
+            ]
+        };
+        let sorted = |secondary_sort: Option<SecondarySortOrder>| {
+            let mut sorter = FileSorter::new();
+            sorter.set_enabled(true);
+            sorter.set_sort_order(SortOrder::ModificationTime);
+            sorter.set_secondary_sort(secondary_sort);
+            for batch in batches() {
+                sorter.add_results(batch);
+            }
+            sorted_locations(&sorter)
+        };
+
+        assert_eq!(
+            FileSorter::new().secondary_sort(),
+            Some(SecondarySortOrder::ByLineNumber)
+        );
+        assert_eq!(
+            sorted(Some(SecondarySortOrder::ByLineNumber)),
+            vec![
+                location("a.rs", 1),
+                location("a.rs", 2),
+                location("a.rs", 5),
+                location("a.rs", 7),
+            ]
+        );
+        // Without a secondary order ties keep their arrival order
+        assert_eq!(
+            sorted(None),
+            vec![
+                location("a.rs", 7),
+                location("a.rs", 2),
+                location("a.rs", 5),
+                location("a.rs", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_fuzzy_score() {
+        let scored = |line_number: usize, score: Option<i64>| {
+            let mut result = create_test_result("src/main.rs", line_number);
+            result.match_score = score;
+            result
+        };
+
+        let mut sorter = FileSorter::new();
+        sorter.set_sort_by_score(true);
+        assert!(sorter.is_sorting_by_score());
+
+        sorter.add_results(vec![
+            scored(1, Some(10)),
+            scored(2, None),
+            scored(3, Some(50)),
+        ]);
+        sorter.add_results(vec![scored(4, Some(30)), scored(5, Some(10))]);
+
+        // Highest score first, ties keep arrival order, unscored last
+        let order: Vec<usize> = sorter
+            .get_all_results()
+            .iter()
+            .map(|result| result.line_number)
+            .collect();
+        assert_eq!(order, vec![3, 4, 1, 5, 2]);
+    }
+
     // Integration test that would work with real files
     #[test]
     fn test_git_line_modification_time() {
@@ -324,7 +889,9 @@ mod tests {
         let line_number = 10;
 
         // This test will only pass if were in a git repo
-        if let Some(time) = sorter.get_git_line_modification_time(current_file, line_number) {
+        let result = create_test_result(current_file, line_number);
+        if let Some(blame) = sorter.get_blame_info(&result) {
+            let time = blame.timestamp;
             // If we got a time from git, it should be acceptable
             // not Unix epoch or in the future
             let now = std::time::SystemTime::now();
@@ -332,6 +899,7 @@ mod tests {
 
             assert!(time > unix_epoch, "Time should be after Unix epoch");
             assert!(time < now, "Time should be before now");
+            assert_eq!(blame.short_hash.len(), 7);
 
             println!(
                 "Git line history working: got timestamp for {}:{}",
@@ -358,4 +926,38 @@ mod tests {
 
         println!("Modification time retrival working");
     }
+
+    #[test]
+    fn test_record_modification_times() {
+        let mut sorter = FileSorter::new();
+        let result = create_test_result("src/search/sorter.rs", 1);
+
+        // Unsorted results are not looked up unless recording is enabled
+        sorter.add_results(vec![result.clone()]);
+        assert_eq!(sorter.cached_modification_time(&result), None);
+
+        sorter.clear();
+        sorter.set_record_modification_times(true);
+        sorter.add_results(vec![result.clone()]);
+        assert!(sorter.cached_modification_time(&result).is_some());
+    }
+
+    #[test]
+    fn test_record_blame() {
+        let mut sorter = FileSorter::new();
+        let result = create_test_result("src/search/sorter.rs", 1);
+
+        let added = sorter.add_results(vec![result.clone()]);
+        assert_eq!(added[0].blame_info, None);
+
+        sorter.clear();
+        sorter.set_record_blame(true);
+        let added = sorter.add_results(vec![result.clone()]);
+        // Only looked up when the file is tracked in a git repository
+        if sorter.has_git_repo() {
+            if let Some(blame) = &added[0].blame_info {
+                assert_eq!(sorter.get_blame_info(&result).as_ref(), Some(blame));
+            }
+        }
+    }
 }
@@ -0,0 +1,212 @@
+//! Async streaming of search output.
+//!
+//! The output of the search process is read line by line on a tokio task and the
+//! parsed results are sent over a channel, which the TUI event loop selects on
+//! together with terminal input. Searches that only have a blocking reader, such as
+//! `--and`/`--or` searches or backends without a process, run on a blocking task.
+
+use super::parser::decode_line;
+use super::progressive::ProgressiveLoader;
+use super::{SearchEngine, SearchResult};
+use crate::logging::warn_log;
+use crate::{Result, SearchError};
+use std::process::{Child, Stdio};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Results buffered in the channel before the reader waits for the TUI to catch up
+const RESULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Search running in the background
+#[derive(Debug)]
+pub struct SearchTask {
+    task: JoinHandle<()>,
+    /// Processes read by a blocking task, an async task owns its process
+    processes: Vec<Child>,
+}
+
+impl SearchTask {
+    /// Stop reading and kill the search processes
+    pub fn stop(&mut self) {
+        // The process of an async search is killed when the aborted task drops it
+        self.task.abort();
+        for process in &mut self.processes {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+
+    /// Reap the processes of a search that finished
+    pub fn finish(&mut self) {
+        for process in &mut self.processes {
+            let _ = process.wait();
+        }
+    }
+}
+
+/// Start the search on a tokio task that reads the output of its process
+/// Backends that don't run a process are read on a blocking task
+pub fn spawn_search(
+    engine: &SearchEngine,
+    pattern: &str,
+    directories: &[String],
+) -> Result<(SearchTask, ProgressiveLoader)> {
+    let Some((command, mut parser)) = engine.command(pattern, directories) else {
+        let mut stream = engine.search(pattern, directories)?;
+        let processes = stream.process.take().into_iter().collect();
+        return Ok(spawn_blocking_search(processes, move || {
+            stream.map_while(Result::ok)
+        }));
+    };
+
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut process = Command::from(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            SearchError::SearchProcessError(format!("Failed to start {}: {}", program, e))
+        })?;
+    let stdout = process.stdout.take().ok_or_else(|| {
+        SearchError::SearchProcessError(format!("Failed to capture {} output", program))
+    })?;
+
+    let (sender, receiver) = mpsc::channel(RESULT_CHANNEL_CAPACITY);
+    let task = tokio::spawn(async move {
+        let mut reader = BufReader::new(stdout);
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    warn_log(&format!("Failed to read the {} output: {}", program, e));
+                    break;
+                }
+            }
+            for result in parser.push_line(&decode_line(&buffer)) {
+                // Receiver is gone when the search was replaced, stop reading
+                if sender.send(result).await.is_err() {
+                    return;
+                }
+            }
+        }
+        if let Some(result) = parser.finish() {
+            if sender.send(result).await.is_err() {
+                return;
+            }
+        }
+        // A process still writing to a pipe nobody reads would never exit
+        drop(reader);
+        let _ = process.wait().await;
+    });
+
+    Ok((
+        SearchTask {
+            task,
+            processes: Vec::new(),
+        },
+        ProgressiveLoader::new(receiver),
+    ))
+}
+
+/// Send the results of a blocking reader, such as a synchronous backend, from a
+/// blocking task. The processes are killed when the search is stopped
+pub fn spawn_blocking_search<F, I>(
+    processes: Vec<Child>,
+    read: F,
+) -> (SearchTask, ProgressiveLoader)
+where
+    F: FnOnce() -> I + Send + 'static,
+    I: IntoIterator<Item = SearchResult>,
+{
+    let (sender, receiver) = mpsc::channel(RESULT_CHANNEL_CAPACITY);
+    let task = tokio::task::spawn_blocking(move || {
+        for result in read() {
+            if sender.blocking_send(result).is_err() {
+                return;
+            }
+        }
+    });
+
+    (
+        SearchTask { task, processes },
+        ProgressiveLoader::new(receiver),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::backend::GrepBackend;
+    use crate::search::mock_backend::{mock_result, MockBackend};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_spawn_search_without_process() {
+        let engine = MockBackend::from_results(vec![
+            mock_result("a.rs", 1, "fn main() {"),
+            mock_result("a.rs", 2, "let x = 1;"),
+            mock_result("b.rs", 4, "main();"),
+        ])
+        .into_engine();
+
+        let (mut task, mut loader) = spawn_search(&engine, "main", &[]).unwrap();
+        let mut results = Vec::new();
+        while !loader.is_exhausted() {
+            results.extend(loader.recv_batch(100).await);
+        }
+        task.finish();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].file_path, "b.rs");
+    }
+
+    #[tokio::test]
+    async fn test_spawn_search_reads_process_output() {
+        // grep skips hidden directories such as the default `.tmp` prefix
+        let dir = tempfile::Builder::new().prefix("stream").tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {\n}\nfn other() {}\n").unwrap();
+        let config = MockBackend::empty().into_engine().config;
+        let engine = SearchEngine::with_backend(config, Arc::new(GrepBackend));
+        let directory = dir.path().to_string_lossy().into_owned();
+
+        let (_task, mut loader) = spawn_search(&engine, "fn", &[directory]).unwrap();
+        let mut results = Vec::new();
+        while !loader.is_exhausted() {
+            results.extend(loader.recv_batch(1).await);
+        }
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].line_number, 3);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_search_reads_lines_that_are_not_utf8() {
+        let dir = tempfile::Builder::new().prefix("stream").tempdir().unwrap();
+        // "café main" in Latin-1 between two UTF-8 lines
+        std::fs::write(
+            dir.path().join("menu.txt"),
+            b"main course\ncaf\xe9 main\nmain dish\n",
+        )
+        .unwrap();
+        let config = MockBackend::empty().into_engine().config;
+        let engine = SearchEngine::with_backend(config, Arc::new(GrepBackend));
+        let directory = dir.path().to_string_lossy().into_owned();
+
+        let (_task, mut loader) = spawn_search(&engine, "main", &[directory]).unwrap();
+        let mut results = Vec::new();
+        while !loader.is_exhausted() {
+            results.extend(loader.recv_batch(100).await);
+        }
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].line_content, "caf\u{FFFD} main");
+        assert_eq!(results[2].line_number, 3);
+    }
+}
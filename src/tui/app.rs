@@ -1,12 +1,82 @@
 //! TUI application state and event handling
 
-use crate::preview::PreviewHandler;
-use crate::search::sorter::FileSorter;
+use crate::config::{
+    DEFAULT_CACHE_SIZE_LIMIT, DEFAULT_LINE_COLUMN_WIDTH, DEFAULT_LOAD_BATCH_SIZE,
+    DEFAULT_MIN_SEARCH_INTERVAL_MS, DEFAULT_MTIME_FORMAT, DEFAULT_PATH_COLUMN_PERCENT,
+    DEFAULT_PROGRESSIVE_LOAD_THRESHOLD, DEFAULT_SEARCH_DEBOUNCE_MS,
+};
+use crate::constants::{CLIPBOARD_MESSAGE_SECS, MTIME_CHECK_INTERVAL_MS, SEARCH_HISTORY_LIMIT};
+use crate::history;
+use crate::output::{self, ExportFormat};
+use crate::preview::{CsvTable, PreviewHandler};
+use crate::search::engines::SearchEngineMode;
+use crate::search::rate_limit::RateLimiter;
+use crate::search::sorter::{FileSorter, SortOrder};
 use crate::search::{ProgressiveLoadStatus, SearchResult};
+use crate::tui::diff_view::diff_context;
+use crate::tui::directory_tree::{normalize_path, DirectoryTree};
+use crate::tui::events;
 use crate::tui::highlighter::SyntaxHighlighter;
-use ratatui::text::Line;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use crate::tui::icons::FileTypeIcon;
+use crate::tui::keybindings::KeyBindings;
+use crate::tui::ResultsAreaInfo;
+use lru::LruCache;
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Where the preview pane sits relative to the results list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewPosition {
+    /// Results on the left, preview on the right
+    #[default]
+    Right,
+    /// Results on top, preview below, for portrait screens
+    Bottom,
+}
+
+impl PreviewPosition {
+    /// Get the position as it is written in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            PreviewPosition::Right => "right",
+            PreviewPosition::Bottom => "bottom",
+        }
+    }
+}
+
+/// How much of the terminal the preview pane takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewMode {
+    /// Preview next to the results list
+    #[default]
+    Normal,
+    /// Preview fills the terminal instead of sharing it with the results list
+    Fullscreen,
+    /// No preview, the results list takes the whole height
+    Hidden,
+}
+
+impl PreviewMode {
+    /// Get the mode as it is written in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            PreviewMode::Normal => "normal",
+            PreviewMode::Fullscreen => "fullscreen",
+            PreviewMode::Hidden => "hidden",
+        }
+    }
+}
 
 /// Input focus state for search interface
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,10 +85,42 @@ pub enum InputFocus {
     Primary,
     /// Results list is focused
     Results,
+    /// Results filter box is focused
+    Filter,
+    /// Path filter box is focused
+    PathFilter,
+    /// Directory tree is focused
+    Tree,
+}
+
+/// What the export prompt writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportTarget {
+    /// The search results, JSON or plain text by the file extension
+    #[default]
+    Results,
+    /// The annotations as tab separated values
+    Annotations,
+}
+
+/// A row of the results list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultRow {
+    /// Header of a file group in the grouped view
+    FileHeader {
+        /// Index into `search_results` of the first result in the file
+        first_result: usize,
+        /// Number of visible results in the file
+        match_count: usize,
+        /// Whether the file's results are hidden
+        collapsed: bool,
+    },
+    /// Index into `search_results`
+    Result(usize),
 }
 
 /// Search progress state
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchProgress {
     /// Number of files with matches found so far
     pub files_with_matches: usize,
@@ -26,6 +128,14 @@ pub struct SearchProgress {
     pub is_searching: bool,
     /// Whether the search is complete
     pub is_complete: bool,
+    /// Whether the search was stopped before ripgrep finished
+    pub is_cancelled: bool,
+    /// When the search was started
+    #[serde(skip)]
+    pub started_at: Option<Instant>,
+    /// When the search completed or was cancelled, the elapsed time stops there
+    #[serde(skip)]
+    pub completed_at: Option<Instant>,
 }
 
 impl SearchProgress {
@@ -35,6 +145,9 @@ impl SearchProgress {
             files_with_matches: 0,
             is_searching: false,
             is_complete: false,
+            is_cancelled: false,
+            started_at: None,
+            completed_at: None,
         }
     }
 
@@ -43,6 +156,26 @@ impl SearchProgress {
         self.files_with_matches = 0;
         self.is_searching = true;
         self.is_complete = false;
+        self.is_cancelled = false;
+        self.started_at = Some(Instant::now());
+        self.completed_at = None;
+    }
+
+    /// Time the search has been running, or took once it completed
+    /// Zero before a search was started
+    pub fn elapsed(&self) -> Duration {
+        match (self.started_at, self.completed_at) {
+            (Some(started), Some(completed)) => completed.duration_since(started),
+            (Some(started), None) => started.elapsed(),
+            (None, _) => Duration::ZERO,
+        }
+    }
+
+    /// Stop the elapsed time of a running search
+    fn stop_timer(&mut self) {
+        if self.started_at.is_some() && self.completed_at.is_none() {
+            self.completed_at = Some(Instant::now());
+        }
     }
 
     /// Update the search progress with current file count
@@ -54,6 +187,14 @@ impl SearchProgress {
     pub fn complete_search(&mut self) {
         self.is_searching = false;
         self.is_complete = true;
+        self.stop_timer();
+    }
+
+    /// Mark the search as cancelled, keeping the file count found so far
+    pub fn cancel_search(&mut self) {
+        self.is_searching = false;
+        self.is_cancelled = true;
+        self.stop_timer();
     }
 
     /// Reset the search progress
@@ -61,6 +202,9 @@ impl SearchProgress {
         self.files_with_matches = 0;
         self.is_searching = false;
         self.is_complete = false;
+        self.is_cancelled = false;
+        self.started_at = None;
+        self.completed_at = None;
     }
 }
 
@@ -70,6 +214,25 @@ impl Default for SearchProgress {
     }
 }
 
+/// Preview cache key: file path, line number, terminal width and height
+type PreviewCacheKey = (String, usize, usize, usize);
+
+/// Diff cache key: file path, line number and modification time of the file
+type DiffCacheKey = (String, usize, Option<SystemTime>);
+
+/// Highlighted result line with the modification time of its file when it was cached
+struct CachedLine {
+    line: Line<'static>,
+    modified: Option<SystemTime>,
+}
+
+/// Modification time of a file, `None` if it can't be read
+fn file_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
 /// Main TUI application state
 pub struct App {
     /// Current search results
@@ -78,36 +241,222 @@ pub struct App {
     /// Currently selected search result index
     pub selected_index: usize,
 
+    /// Indices into `search_results` of bookmarked results
+    pub bookmarks: HashSet<usize>,
+
+    /// Indices into `search_results` of the results marked to open or export together
+    pub selected_results: HashSet<usize>,
+
+    /// Notes attached to results, keyed on file path and line number
+    pub annotations: HashMap<(String, usize), String>,
+
     /// Current search pattern
     pub current_pattern: String,
 
+    /// Byte offset of the cursor in the search pattern
+    pub cursor_position: usize,
+
+    /// Byte offset where the selection in the search pattern started, it ends at the cursor
+    pub selection_start: Option<usize>,
+
+    /// Whether results are grouped under a header per file
+    pub grouped_view: bool,
+
+    /// Files whose results are hidden in the grouped view
+    pub collapsed_files: HashSet<String>,
+
+    /// Secondary pattern narrowing the visible results
+    pub filter_pattern: Option<String>,
+
+    /// Glob on the display path narrowing the visible results, kept across searches
+    pub path_filter: Option<String>,
+
+    /// Directory tree of the search root, `None` when the tree pane is hidden
+    pub directory_tree: Option<DirectoryTree>,
+
+    /// Only results under this path are shown, picked in the directory tree
+    pub directory_scope: Option<PathBuf>,
+
+    /// Previously committed search patterns, oldest first
+    pub search_history: Vec<String>,
+
+    /// Position in the search history while browsing it
+    pub history_index: Option<usize>,
+
+    /// Pattern being typed before browsing the history started
+    history_draft: String,
+
+    /// File the search history is saved to, `None` keeps it in memory only
+    history_file: Option<PathBuf>,
+
+    /// Directories being searched
+    pub search_directories: Vec<String>,
+
+    /// Search mode of the engine
+    pub search_mode: SearchEngineMode,
+
+    /// Whether some results were cut off by the per-file or total match limit
+    pub results_truncated: bool,
+
+    /// Maximum number of results in total, the search stops once it is reached
+    pub max_results: Option<usize>,
+
+    /// Results on lines longer than this many bytes are dropped
+    pub max_line_length: Option<usize>,
+
+    /// Results from files larger than this many bytes are dropped
+    pub max_filesize: Option<u64>,
+
+    /// When the pattern was last edited, the search re-runs once the debounce delay has passed
+    pub pending_search: Option<Instant>,
+
+    /// Delay in milliseconds between the last keystroke and the live search
+    pub search_debounce_ms: u64,
+
+    /// Minimum time between live searches, a search typed sooner waits in its queue
+    pub search_rate_limiter: RateLimiter,
+
+    /// Maximum number of streamed results added per frame
+    pub load_batch_size: usize,
+
+    /// Results kept loaded past the selection, more are read from the search once the
+    /// selection comes closer to the end
+    pub progressive_load_threshold: usize,
+
+    /// Pattern of the last search, typing that ends on the same pattern doesn't search again
+    pub last_searched_pattern: String,
+
+    /// Pattern last submitted with Enter, Escape goes back to it while the pattern is edited
+    pub previous_committed_pattern: String,
+
     /// Whether the app should quit
     pub should_quit: bool,
 
+    /// Whether the keybinding help overlay is shown
+    pub show_help: bool,
+
+    /// Rows of the help overlay as (action, keys), built from the configured keybindings
+    pub help_rows: Vec<(String, String)>,
+
+    /// Configured keybindings, for the keys named in pane titles
+    pub keybindings: KeyBindings,
+
+    /// Whether the preview is shown next to the results list, fullscreen or not at all
+    pub preview_mode: PreviewMode,
+
+    /// Whether result lines wider than the results list wrap onto more rows
+    pub word_wrap_results: bool,
+
+    /// Whether the modification time of each result is shown in the results list
+    show_mtime: bool,
+
+    /// strftime format of the modification time column
+    pub mtime_format: String,
+
+    /// Whether the commit and author of each result line are shown in the results list
+    show_git_blame: bool,
+
+    /// Whether results are numbered in the results list
+    pub show_result_numbers: bool,
+
+    /// Whether file type icons are shown in the results list
+    pub show_icons: bool,
+
+    /// Whether results are shown as aligned path, line number and content columns
+    pub column_layout: bool,
+
+    /// Share of the results list width taken by the path column, in percent
+    pub path_column_percent: usize,
+
+    /// Width of the line number column
+    pub line_column_width: usize,
+
+    /// Where the preview pane sits relative to the results list
+    pub preview_position: PreviewPosition,
+
+    /// File name being typed in the export prompt, `None` when the prompt is closed
+    pub export_prompt: Option<String>,
+
+    /// What the export prompt writes
+    pub export_target: ExportTarget,
+
+    /// Note being typed for the selected result, `None` when the prompt is closed
+    pub annotation_prompt: Option<String>,
+
+    /// Message shown in the status bar, e.g. the result of an export
+    pub status_message: Option<String>,
+
+    /// When text was last copied, "Copied!" is shown for a short while after
+    pub clipboard_message: Option<Instant>,
+
+    /// System clipboard, kept open so the copied text stays available
+    clipboard: Option<arboard::Clipboard>,
+
     /// Current input focus state
     pub input_focus: InputFocus,
 
+    /// Editor command used to open results
+    pub editor: String,
+
     /// preview handler for the file content
     pub preview_handler: PreviewHandler,
 
+    /// Number of lines the preview is scrolled down from the target line
+    pub preview_scroll_offset: usize,
+
+    /// Columns and rows of the terminal, updated when it is resized
+    pub terminal_dimensions: (u16, u16),
+
+    /// Whether the preview shows the git diff of the hunk around the selected result
+    pub show_diff_view: bool,
+
+    /// Whether the terminal supports colors (syntax highlighted preview)
+    pub use_color: bool,
+
     /// Search progress tracking
     pub search_progress: SearchProgress,
 
     /// Progressive load status
     pub progressive_load_status: Option<ProgressiveLoadStatus>,
 
-    /// Flag to trigger progressive loading check
+    /// Whether the selection is close enough to the end of the loaded results that
+    /// results from the running search are drawn as soon as they arrive
     pub needs_progressive_load_check: bool,
 
-    /// Cache for syntax-highlighted results to avoid re-processing
-    /// Key: (file_path, line_number, line_content) hash, Value: syntax-highlighted line
+    /// Least recently used cache for syntax-highlighted results to avoid re-processing
+    /// Key: (file_path, line_number, line_content, matched_text) hash, Value: syntax-highlighted line
     // Refcell smart pointer moves borrowing checks to runtime
     // allows mutability of contents while ensuring safety
-    highlighted_cache: RefCell<HashMap<u64, Line<'static>>>, // static lifetime makes the memory persist
+    highlighted_cache: RefCell<LruCache<u64, CachedLine>>,
+
+    /// Modification times of the files of highlighted lines and when they were looked up
+    file_mtimes: RefCell<HashMap<String, (Instant, Option<SystemTime>)>>,
 
     /// Cache size limit to prevent unlimited memory usage
     cache_size_limit: usize,
 
+    /// Highlighted line cache hits and misses, for debugging
+    cache_hits: Cell<u64>,
+    cache_misses: Cell<u64>,
+
+    /// Cache for preview content to avoid re-reading the file on every redraw
+    preview_cache: RefCell<HashMap<PreviewCacheKey, String>>,
+
+    /// Cache for syntax highlighted preview content
+    highlighted_preview_cache: RefCell<HashMap<PreviewCacheKey, Text<'static>>>,
+
+    /// Cache for table previews, `None` when the file doesn't parse as CSV
+    table_preview_cache: RefCell<HashMap<PreviewCacheKey, Option<CsvTable>>>,
+
+    /// File the preview caches belong to, they are evicted when it changes
+    preview_cache_file: RefCell<Option<String>>,
+
+    /// Number of lines of the file the preview caches belong to
+    preview_line_count: RefCell<Option<usize>>,
+
+    /// Diff of the last result shown in the diff view, by file, line and modification time
+    diff_cache: RefCell<Option<(DiffCacheKey, Option<String>)>>,
+
     /// File sorter for maintaining global sort order
     sorter: FileSorter,
 }
@@ -115,26 +464,194 @@ pub struct App {
 impl App {
     /// Crete new application instance
     pub fn new() -> Self {
+        Self::new_with_cache_size(DEFAULT_CACHE_SIZE_LIMIT)
+    }
+
+    /// Create a new application instance keeping up to `limit` highlighted lines in the cache
+    pub fn new_with_cache_size(limit: usize) -> Self {
+        let cache_size_limit = limit.max(1);
         Self {
             search_results: Vec::new(),
             selected_index: 0,
+            bookmarks: HashSet::new(),
+            annotations: HashMap::new(),
+            selected_results: HashSet::new(),
             current_pattern: String::new(),
+            cursor_position: 0,
+            selection_start: None,
+            grouped_view: false,
+            collapsed_files: HashSet::new(),
+            filter_pattern: None,
+            path_filter: None,
+            directory_tree: None,
+            directory_scope: None,
+            search_history: Vec::new(),
+            history_index: None,
+            history_draft: String::new(),
+            history_file: None,
+            search_directories: vec![".".to_string()],
+            search_mode: SearchEngineMode::CaseInsensitive,
+            results_truncated: false,
+            max_results: None,
+            pending_search: None,
+            search_debounce_ms: DEFAULT_SEARCH_DEBOUNCE_MS,
+            search_rate_limiter: RateLimiter::new(DEFAULT_MIN_SEARCH_INTERVAL_MS),
+            load_batch_size: DEFAULT_LOAD_BATCH_SIZE,
+            progressive_load_threshold: DEFAULT_PROGRESSIVE_LOAD_THRESHOLD,
+            last_searched_pattern: String::new(),
+            previous_committed_pattern: String::new(),
+            max_line_length: None,
+            max_filesize: None,
             should_quit: false,
+            show_help: false,
+            help_rows: events::help_rows(&KeyBindings::default()),
+            keybindings: KeyBindings::default(),
+            preview_mode: PreviewMode::default(),
+            word_wrap_results: false,
+            show_mtime: false,
+            mtime_format: DEFAULT_MTIME_FORMAT.to_string(),
+            show_git_blame: false,
+            show_result_numbers: true,
+            show_icons: false,
+            column_layout: false,
+            path_column_percent: DEFAULT_PATH_COLUMN_PERCENT,
+            line_column_width: DEFAULT_LINE_COLUMN_WIDTH,
+            preview_position: PreviewPosition::default(),
+            export_prompt: None,
+            export_target: ExportTarget::default(),
+            annotation_prompt: None,
+            status_message: None,
+            clipboard_message: None,
+            clipboard: None,
             input_focus: InputFocus::Primary,
+            editor: crate::editor::DEFAULT_EDITOR.to_string(),
             preview_handler: PreviewHandler::new(),
+            preview_scroll_offset: 0,
+            terminal_dimensions: (0, 0),
+            show_diff_view: false,
+            use_color: true,
             search_progress: SearchProgress::new(),
             progressive_load_status: None,
             needs_progressive_load_check: false,
-            highlighted_cache: RefCell::new(HashMap::new()),
-            cache_size_limit: 1000,
+            highlighted_cache: RefCell::new(LruCache::new(Self::cache_capacity(cache_size_limit))),
+            file_mtimes: RefCell::new(HashMap::new()),
+            cache_size_limit,
+            cache_hits: Cell::new(0),
+            cache_misses: Cell::new(0),
+            preview_cache: RefCell::new(HashMap::new()),
+            highlighted_preview_cache: RefCell::new(HashMap::new()),
+            table_preview_cache: RefCell::new(HashMap::new()),
+            preview_cache_file: RefCell::new(None),
+            preview_line_count: RefCell::new(None),
+            diff_cache: RefCell::new(None),
             sorter: FileSorter::new(),
         }
     }
 
+    /// Set the maximum number of highlighted lines kept in the cache
+    pub fn set_cache_size_limit(&mut self, cache_size_limit: usize) {
+        self.cache_size_limit = cache_size_limit.max(1);
+        self.highlighted_cache
+            .borrow_mut()
+            .resize(Self::cache_capacity(self.cache_size_limit));
+    }
+
+    /// Capacity of the highlighted line cache, at least one line
+    fn cache_capacity(limit: usize) -> NonZeroUsize {
+        NonZeroUsize::new(limit).unwrap_or(NonZeroUsize::MIN)
+    }
+
+    /// Enable sorting in the given order
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        self.sorter.set_enabled(true);
+        self.sorter.set_sort_order(sort_order);
+    }
+
+    /// Checks if the modification time order can use git line history
+    pub fn has_git_repo(&self) -> bool {
+        self.sorter.has_git_repo()
+    }
+
+    /// Reverse the sort order at runtime
+    pub fn toggle_reverse_sort(&mut self) {
+        let reverse = !self.sorter.is_reversed();
+        self.sorter.set_reverse(reverse);
+        self.sync_results_from_sorter();
+    }
+
+    /// Get the label of the active sort order and direction for the status bar
+    pub fn get_sort_label(&self) -> String {
+        if self.sorter.is_sorting_by_score() {
+            "score".to_string()
+        } else if self.sorter.is_enabled() {
+            let direction = if self.sorter.is_reversed() {
+                "↑"
+            } else {
+                "↓"
+            };
+            format!("{} {}", self.sorter.sort_order().label(), direction)
+        } else {
+            "none".to_string()
+        }
+    }
+
+    /// Drop results for a file and line that was already found
+    pub fn set_deduplicate(&mut self, deduplicate: bool) {
+        self.sorter.set_deduplicate(deduplicate);
+    }
+
+    /// Show the modification time of each result, the sorter looks them up as results arrive
+    pub fn set_show_mtime(&mut self, show_mtime: bool) {
+        self.show_mtime = show_mtime;
+        self.sorter.set_record_modification_times(show_mtime);
+    }
+
+    /// Formatted modification time of a result, `None` when the column is hidden
+    /// or the format is invalid
+    pub fn get_mtime_label(&self, result: &SearchResult) -> Option<String> {
+        if !self.show_mtime {
+            return None;
+        }
+        let mtime = self.sorter.cached_modification_time(result)?;
+        let mut label = String::new();
+        write!(
+            label,
+            "{}",
+            chrono::DateTime::<chrono::Local>::from(mtime).format(&self.mtime_format)
+        )
+        .ok()?;
+        Some(label)
+    }
+
+    /// Show the git blame of each result, the sorter looks it up as results arrive
+    pub fn set_show_git_blame(&mut self, show_git_blame: bool) {
+        self.show_git_blame = show_git_blame;
+        self.sorter.set_record_blame(show_git_blame);
+    }
+
+    /// Short commit hash and author of a result, `None` when the column is hidden
+    /// or the line has no git history
+    pub fn get_blame_label(&self, result: &SearchResult) -> Option<String> {
+        if !self.show_git_blame {
+            return None;
+        }
+        let blame = result.blame_info.as_ref()?;
+        Some(format!("{} {}", blame.short_hash, blame.author))
+    }
+
+    /// Sort results by fuzzy match score (fuzzy mode)
+    pub fn set_sort_by_score(&mut self, sort_by_score: bool) {
+        self.sorter.set_sort_by_score(sort_by_score);
+    }
+
     /// Update search results (replace all results)
     pub fn update_search_results(&mut self, results: Vec<SearchResult>) {
+        let bookmarked = self.result_keys(&self.bookmarks);
+        let selected = self.result_keys(&self.selected_results);
         self.search_results = results.clone();
         self.selected_index = 0;
+        self.bookmarks = self.indices_of(&bookmarked);
+        self.selected_results = self.indices_of(&selected);
 
         // update sorter
         self.sorter.clear();
@@ -145,43 +662,355 @@ impl App {
 
     /// Add a new search results (for streamng results) - maintains sort order
     pub fn add_search_result(&mut self, result: SearchResult) {
-        // Let the sorter handle the insertion and maintain the master list
-        let _ = self.sorter.add_results(vec![result]);
-
-        // Sync our display with the sorter's sorted list
-        self.sync_results_from_sorter();
+        self.add_sarch_results(vec![result]);
     }
 
     /// Add multiple search results (for streamng results) - maintains sort order
-    pub fn add_sarch_results(&mut self, results: Vec<SearchResult>) {
+    pub fn add_sarch_results(&mut self, mut results: Vec<SearchResult>) {
+        // Ripgrep shortens long lines with --max-columns-preview, so they end up longer than the limit
+        if let Some(max_line_length) = self.max_line_length {
+            results.retain(|result| result.line_content.len() <= max_line_length);
+        }
+        // Ripgrep already skips large files, other backends don't. Sizes are looked up
+        // either way for the file headers, files that can't be read are kept
+        let max_filesize = self.max_filesize;
+        let sorter = &mut self.sorter;
+        results.retain(|result| {
+            let size = sorter.file_size(&result.file_path);
+            max_filesize.is_none_or(|max| size.is_none_or(|size| size <= max))
+        });
+        let duplicates = self.dedup_results(&mut results);
+        let status = self
+            .progressive_load_status
+            .get_or_insert_with(Default::default);
+        status.deduplication_count += duplicates;
+
+        // Drop results past the total limit
+        if let Some(max_results) = self.max_results {
+            let remaining = max_results.saturating_sub(status.total_matches);
+            if results.len() >= remaining {
+                results.truncate(remaining);
+                status.results_truncated = true;
+                self.results_truncated = true;
+            }
+        }
         if results.is_empty() {
             return;
         }
 
+        for result in &results {
+            status.record_match(&result.file_path);
+        }
+        self.search_progress
+            .update_file_count(status.total_files_found);
+
         // Let the sorter handle the insertion and maintain the master list
         let _ = self.sorter.add_results(results);
 
         // Sync our display with the sorter's sorted list
         self.sync_results_from_sorter();
+        if let Some(status) = self.progressive_load_status.as_mut() {
+            status.total_loaded = self.search_results.len();
+        }
+        self.check_progressive_load();
+    }
+
+    /// Draw results from the running search as they arrive when the selection is within
+    /// `progressive_load_threshold` results of the end of the loaded results
+    pub fn check_progressive_load(&mut self) {
+        let loaded = self.visible_rows().len();
+        self.needs_progressive_load_check =
+            self.selected_index + self.progressive_load_threshold >= loaded;
+    }
+
+    /// Keep only the first result for each file and line, including the results already added
+    /// Returns the number of results dropped
+    pub fn dedup_results(&self, results: &mut Vec<SearchResult>) -> usize {
+        if !self.sorter.is_deduplicating() {
+            return 0;
+        }
+        let received = results.len();
+        let mut batch = HashSet::new();
+        results.retain(|result| {
+            !self.sorter.contains(result)
+                && batch.insert((result.file_path.clone(), result.line_number))
+        });
+        received - results.len()
+    }
+
+    /// Checks if the search stopped at the `--max-results` limit
+    pub fn is_result_limit_reached(&self) -> bool {
+        self.progressive_load_status
+            .as_ref()
+            .is_some_and(|status| status.results_truncated)
+    }
+
+    /// Number of matches received for the file in the current search
+    pub fn matches_for_file(&self, path: &str) -> usize {
+        self.progressive_load_status
+            .as_ref()
+            .map_or(0, |status| status.matches_for_file(path))
     }
 
     /// Sync the results from the sorter to the display
     fn sync_results_from_sorter(&mut self) {
-        self.search_results = self.sorter.get_all_results().to_vec()
+        let bookmarked = self.result_keys(&self.bookmarks);
+        let selected = self.result_keys(&self.selected_results);
+        self.search_results = self.sorter.get_all_results().to_vec();
+        self.bookmarks = self.indices_of(&bookmarked);
+        self.selected_results = self.indices_of(&selected);
+    }
+
+    /// Stable identity of a result, used to keep bookmarks and selections when results are re-sorted
+    fn result_key(result: &SearchResult) -> (&str, usize) {
+        (&result.file_path, result.line_number)
+    }
+
+    /// Identities of the results at the indices
+    fn result_keys(&self, indices: &HashSet<usize>) -> HashSet<(String, usize)> {
+        indices
+            .iter()
+            .filter_map(|&index| self.search_results.get(index))
+            .map(|result| (result.file_path.clone(), result.line_number))
+            .collect()
+    }
+
+    /// Current indices of the results with the identities
+    fn indices_of(&self, keys: &HashSet<(String, usize)>) -> HashSet<usize> {
+        if keys.is_empty() {
+            return HashSet::new();
+        }
+        self.search_results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| {
+                let (file_path, line_number) = Self::result_key(result);
+                keys.contains(&(file_path.to_string(), line_number))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Toggle the bookmark on the selected result
+    pub fn toggle_bookmark(&mut self) {
+        if let Some(&ResultRow::Result(index)) = self.visible_rows().get(self.selected_index) {
+            if !self.bookmarks.remove(&index) {
+                self.bookmarks.insert(index);
+            }
+        }
+    }
+
+    /// Check whether a result is bookmarked
+    pub fn is_bookmarked(&self, result: &SearchResult) -> bool {
+        self.bookmarks.iter().any(|&index| {
+            self.search_results
+                .get(index)
+                .is_some_and(|bookmarked| Self::result_key(bookmarked) == Self::result_key(result))
+        })
+    }
+
+    /// Positions of the bookmarked results in the visible list
+    fn bookmark_positions(&self) -> Vec<usize> {
+        self.visible_rows()
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                matches!(row, ResultRow::Result(index) if self.bookmarks.contains(index))
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Select the next bookmarked result, wrapping around to the first
+    pub fn jump_to_next_bookmark(&mut self) {
+        let positions = self.bookmark_positions();
+        if let Some(&position) = positions
+            .iter()
+            .find(|&&position| position > self.selected_index)
+            .or_else(|| positions.first())
+        {
+            self.selected_index = position;
+        }
+    }
+
+    /// Select the previous bookmarked result, wrapping around to the last
+    pub fn jump_to_prev_bookmark(&mut self) {
+        let positions = self.bookmark_positions();
+        if let Some(&position) = positions
+            .iter()
+            .rev()
+            .find(|&&position| position < self.selected_index)
+            .or_else(|| positions.last())
+        {
+            self.selected_index = position;
+        }
+    }
+
+    /// Select the first result in a file whose display path starts with `path`,
+    /// compared by whole path components. Returns false when no result matches
+    pub fn jump_to_file(&mut self, path: &str) -> bool {
+        self.jump_to_result(|result| Path::new(result.get_display_path()).starts_with(path))
+    }
+
+    /// Select the first visible result that matches, or the header of its file
+    /// when the file is collapsed
+    fn jump_to_result(&mut self, matches: impl Fn(&SearchResult) -> bool) -> bool {
+        let rows = self.visible_rows();
+        let position = rows
+            .iter()
+            .position(|row| {
+                matches!(row, ResultRow::Result(index) if matches(&self.search_results[*index]))
+            })
+            .or_else(|| {
+                rows.iter().position(|row| {
+                    matches!(row, ResultRow::FileHeader { first_result, .. }
+                        if matches(&self.search_results[*first_result]))
+                })
+            });
+        match position {
+            Some(position) => {
+                self.selected_index = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// File of each visible row, a file header belongs to its file
+    fn row_files(&self) -> Vec<&str> {
+        self.visible_rows()
+            .iter()
+            .map(|row| {
+                let index = match *row {
+                    ResultRow::FileHeader { first_result, .. } => first_result,
+                    ResultRow::Result(index) => index,
+                };
+                self.search_results[index].file_path.as_str()
+            })
+            .collect()
+    }
+
+    /// Select the first row of the next file in the results list
+    pub fn jump_to_next_file(&mut self) {
+        let files = self.row_files();
+        let Some(current) = files.get(self.selected_index) else {
+            return;
+        };
+        if let Some(offset) = files[self.selected_index..]
+            .iter()
+            .position(|file| file != current)
+        {
+            self.selected_index += offset;
+        }
+    }
+
+    /// Select the first row of the previous file in the results list
+    pub fn jump_to_prev_file(&mut self) {
+        let files = self.row_files();
+        let Some(current) = files.get(self.selected_index) else {
+            return;
+        };
+        let file_start = files[..self.selected_index]
+            .iter()
+            .rposition(|file| file != current)
+            .map_or(0, |position| position + 1);
+        if file_start == 0 {
+            return;
+        }
+        let previous = files[file_start - 1];
+        self.selected_index = files[..file_start]
+            .iter()
+            .rposition(|file| *file != previous)
+            .map_or(0, |position| position + 1);
+    }
+
+    /// Mark or unmark the selected result for opening together
+    pub fn toggle_select(&mut self) {
+        if let Some(&ResultRow::Result(index)) = self.visible_rows().get(self.selected_index) {
+            if !self.selected_results.remove(&index) {
+                self.selected_results.insert(index);
+            }
+        }
+    }
+
+    /// Mark every result that passes the filter
+    pub fn select_all(&mut self) {
+        self.selected_results.extend(self.filtered_indices());
+    }
+
+    /// Unmark all results
+    pub fn clear_selection(&mut self) {
+        self.selected_results.clear();
+    }
+
+    /// Check whether a result is marked
+    pub fn is_selected(&self, result: &SearchResult) -> bool {
+        self.selected_results.iter().any(|&index| {
+            self.search_results
+                .get(index)
+                .is_some_and(|selected| Self::result_key(selected) == Self::result_key(result))
+        })
+    }
+
+    /// The marked results in list order
+    pub fn marked_results(&self) -> Vec<&SearchResult> {
+        let mut indices: Vec<usize> = self.selected_results.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .filter_map(|index| self.search_results.get(index))
+            .collect()
     }
 
     /// Clear all search results (when starting a new search)
     pub fn clear_search_results(&mut self) {
         self.search_results.clear();
         self.selected_index = 0;
+        self.bookmarks.clear();
+        self.selected_results.clear();
+        self.collapsed_files.clear();
         self.sorter.clear();
-        self.clear_highlighting_cache();
+        self.clear_preview_cache();
     }
 
     /// Start a new search
     pub fn start_new_search(&mut self) {
         self.clear_search_results();
+        self.filter_pattern = None;
+        self.status_message = None;
+        self.results_truncated = false;
+        self.progressive_load_status = Some(ProgressiveLoadStatus {
+            load_threshold: self.progressive_load_threshold,
+            ..ProgressiveLoadStatus::default()
+        });
+        self.needs_progressive_load_check = true;
         self.search_progress.start_search();
+        self.last_searched_pattern = self.current_pattern.clone();
+    }
+
+    /// Time the current search has been running, or the last search took
+    /// `None` before the first search
+    pub fn search_elapsed(&self) -> Option<Duration> {
+        self.search_progress
+            .started_at
+            .map(|_| self.search_progress.elapsed())
+    }
+
+    /// Schedule a live search after the pattern changed
+    pub fn schedule_search(&mut self) {
+        self.pending_search = Some(Instant::now());
+    }
+
+    /// Checks if typing stopped long enough ago to run the scheduled search
+    pub fn is_search_due(&self) -> bool {
+        self.pending_search.is_some_and(|edited| {
+            edited.elapsed() >= Duration::from_millis(self.search_debounce_ms)
+        })
+    }
+
+    /// Checks if the pattern was edited since the last search
+    pub fn is_pattern_changed(&self) -> bool {
+        self.current_pattern != self.last_searched_pattern
     }
 
     /// Update file counts with matches
@@ -189,14 +1018,41 @@ impl App {
         self.search_progress.update_file_count(file_with_matches);
     }
 
+    /// Record that some files had more matches than the limit allows
+    pub fn mark_results_truncated(&mut self) {
+        self.results_truncated = true;
+    }
+
     /// Complete the current search
     pub fn complete_search(&mut self) {
         self.search_progress.complete_search();
+        if let Some(status) = self.progressive_load_status.as_mut() {
+            status.loading_complete = true;
+        }
+    }
+
+    /// Cancel the current search, keeping the results received so far
+    pub fn cancel_search(&mut self) {
+        self.pending_search = None;
+        self.search_progress.cancel_search();
+        self.status_message = Some("Search cancelled (partial results shown)".to_string());
+    }
+
+    /// Stop the current search at its timeout, keeping the results received so far
+    pub fn time_out_search(&mut self, timeout: Duration) {
+        self.cancel_search();
+        self.status_message = Some(format!(
+            "Search timed out after {} ms (partial results shown)",
+            timeout.as_millis()
+        ));
     }
 
     /// Get currently selected search result
     pub fn selected_result(&self) -> Option<&SearchResult> {
-        self.search_results.get(self.selected_index)
+        match self.visible_rows().get(self.selected_index) {
+            Some(&ResultRow::Result(index)) => self.search_results.get(index),
+            _ => None,
+        }
     }
 
     /// Get the search results
@@ -204,126 +1060,1080 @@ impl App {
         &self.search_results
     }
 
-    /// Toggle input focus
-    pub fn toggle_focus(&mut self) {
-        match self.input_focus {
-            InputFocus::Primary => self.input_focus = InputFocus::Results,
-            InputFocus::Results => self.input_focus = InputFocus::Primary,
-        }
+    /// Get the search results matching the filter pattern
+    pub fn active_results_filtered(&self) -> Vec<&SearchResult> {
+        self.filtered_indices()
+            .into_iter()
+            .map(|index| &self.search_results[index])
+            .collect()
     }
 
-    /// Set quit flag
-    pub fn quit(&mut self) {
-        self.should_quit = true;
+    /// Whether the filter pattern, path filter or directory scope hides results
+    pub fn is_filtered(&self) -> bool {
+        self.filter_pattern
+            .as_deref()
+            .is_some_and(|f| !f.is_empty())
+            || self.path_filter_glob().is_some()
+            || self.directory_scope.is_some()
     }
 
-    /// Update search pattern
-    pub fn update_pattern(&mut self, pattern: String) {
-        self.current_pattern = pattern;
+    /// The path filter as a glob, a filter without wildcards matches anywhere in the path
+    fn path_filter_glob(&self) -> Option<glob::Pattern> {
+        let filter = self.path_filter.as_deref().filter(|f| !f.is_empty())?;
+        let glob = if filter.contains(['*', '?', '[']) {
+            filter.to_string()
+        } else {
+            format!("*{}*", filter)
+        };
+        // Half-typed globs such as `src/[` match literally
+        Some(
+            glob::Pattern::new(&glob)
+                .unwrap_or_else(|_| glob::Pattern::new(&glob::Pattern::escape(&glob)).unwrap()),
+        )
     }
 
-    /// Get preview content for the currently selected result with optional terminal dimensions
-    pub fn get_preview_content(&self, terminal_dimensions: Option<(usize, usize)>) -> String {
-        if let Some(result) = self.selected_result() {
-            match self.preview_handler.preview_file(
-                &result.file_path,
-                Some(result.line_number),
-                terminal_dimensions,
-            ) {
-                Ok(preview) => preview,
-                Err(e) => format!("Error Loading Preview: {:?}", e),
+    /// Indices into `search_results` of the results matching the filter pattern
+    /// and path filter, inside the directory picked in the tree
+    fn filtered_indices(&self) -> Vec<usize> {
+        let filter = self.filter_pattern.as_deref().filter(|f| !f.is_empty());
+        let path_filter = self.path_filter_glob();
+        let scope = self.directory_scope.as_deref();
+        if filter.is_none() && path_filter.is_none() && scope.is_none() {
+            return (0..self.search_results.len()).collect();
+        }
+
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        self.search_results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| filter.is_none_or(|filter| result.matches_filter(filter)))
+            .filter(|(_, result)| {
+                path_filter
+                    .as_ref()
+                    .is_none_or(|glob| glob.matches_with(result.get_display_path(), options))
+            })
+            .filter(|(_, result)| {
+                scope.is_none_or(|scope| {
+                    normalize_path(Path::new(&result.file_path)).starts_with(scope)
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Show or hide the directory tree of the first search directory
+    /// Hiding the tree shows the results of every directory again
+    pub fn toggle_directory_tree(&mut self) {
+        if self.directory_tree.take().is_some() {
+            self.directory_scope = None;
+            if self.input_focus == InputFocus::Tree {
+                self.input_focus = InputFocus::Results;
             }
         } else {
-            "No file selected".to_string()
+            let root = self
+                .search_directories
+                .first()
+                .map_or(".", String::as_str)
+                .to_string();
+            self.directory_tree = Some(DirectoryTree::new(root));
         }
+        self.selected_index = 0;
     }
 
-    /// Handle mouse click within the results list
-    /// Returns true if the click resulted in selection change
-    pub fn handle_results_click(
-        &mut self,
-        click_row: u16,
-        results_area_top: u16,
-        results_area_height: u16,
-    ) -> bool {
-        // Calculate which result was clicked based on the click position
-        // results_area_top is the top of the results list (after header)
-        // Each result takes up exactly one row in the list
-
-        if click_row < results_area_top || click_row >= results_area_top + results_area_height {
-            return false; // Click was outside of the results list
+    /// Expand or collapse the selected tree directory and narrow the results to the selected path
+    /// Picking the root shows every result again
+    pub fn activate_tree_selection(&mut self) {
+        let Some(tree) = self.directory_tree.as_mut() else {
+            return;
+        };
+        let Some(row) = tree.selected_row() else {
+            return;
+        };
+        tree.toggle(tree.selected);
+
+        // A file shows its first match instead of narrowing the results
+        if !row.is_dir {
+            let path = normalize_path(&row.path);
+            self.jump_to_result(|result| normalize_path(Path::new(&result.file_path)) == path);
+            return;
         }
 
-        let click_index = (click_row - results_area_top) as usize;
-        if click_index >= self.search_results.len() {
-            self.selected_index = click_index;
-            true
+        self.directory_scope = if row.depth == 0 {
+            None
         } else {
-            false
-        }
+            Some(normalize_path(&row.path))
+        };
+        self.selected_index = 0;
     }
 
-    /// Set selection to a specific index
-    pub fn select_iindex(&mut self, index: usize) {
-        if index < self.search_results.len() {
-            self.selected_index = index;
+    /// Select the tree row at the clicked position and activate it
+    pub fn handle_tree_click(&mut self, click_row: u16, tree_top: u16, tree_height: u16) {
+        let Some(tree) = self.directory_tree.as_mut() else {
+            return;
+        };
+        if click_row < tree_top || click_row >= tree_top + tree_height {
+            return;
+        }
+        let index = tree.scroll_offset(tree_height as usize) + (click_row - tree_top) as usize;
+        if index < tree.rows().len() {
+            tree.select(index);
+            self.input_focus = InputFocus::Tree;
+            self.activate_tree_selection();
         }
     }
 
-    /// Get a reference to the search pattern
-    pub fn active_pattern(&self) -> &str {
-        &self.current_pattern
+    /// Normalized paths of the files with results, to highlight them in the tree
+    pub fn matched_paths(&self) -> HashSet<PathBuf> {
+        self.search_results
+            .iter()
+            .map(|result| normalize_path(Path::new(&result.file_path)))
+            .collect()
     }
 
-    /// Get a reference to the search pattern
-    pub fn active_pattern_mut(&mut self) -> &mut String {
-        &mut self.current_pattern
-    }
+    /// Rows of the results list: the filtered results, under file headers in the grouped view
+    /// Selection indices count the header rows
+    pub fn visible_rows(&self) -> Vec<ResultRow> {
+        let indices = self.filtered_indices();
+        if !self.grouped_view {
+            return indices.into_iter().map(ResultRow::Result).collect();
+        }
 
-    /// Get progressive loading status for display
-    pub fn get_progressive_load_status(&self) -> Option<&ProgressiveLoadStatus> {
+        // Group by file in order of first appearance, keeping the result order within a file
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of_file: HashMap<&str, usize> = HashMap::new();
+        for index in indices {
+            let file_path = self.search_results[index].file_path.as_str();
+            let group = *group_of_file.entry(file_path).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(index);
+        }
+
+        let mut rows = Vec::new();
+        for group in groups {
+            let first_result = group[0];
+            let collapsed = self
+                .collapsed_files
+                .contains(&self.search_results[first_result].file_path);
+            rows.push(ResultRow::FileHeader {
+                first_result,
+                match_count: group.len(),
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(group.into_iter().map(ResultRow::Result));
+            }
+        }
+        rows
+    }
+
+    /// Toggle grouping results by file
+    pub fn toggle_grouped_view(&mut self) {
+        self.grouped_view = !self.grouped_view;
+        self.selected_index = 0;
+    }
+
+    /// Collapse or expand the file group if a header is selected
+    /// Returns false when the selection is a result
+    pub fn toggle_selected_group(&mut self) -> bool {
+        let Some(&ResultRow::FileHeader { first_result, .. }) =
+            self.visible_rows().get(self.selected_index)
+        else {
+            return false;
+        };
+
+        let file_path = self.search_results[first_result].file_path.clone();
+        if !self.collapsed_files.remove(&file_path) {
+            self.collapsed_files.insert(file_path);
+        }
+        true
+    }
+
+    /// Get a mutable reference to the filter pattern, creating it if needed
+    pub fn filter_pattern_mut(&mut self) -> &mut String {
+        // The visible list changes, so start from the top again
+        self.selected_index = 0;
+        self.filter_pattern.get_or_insert_with(String::new)
+    }
+
+    /// Get a mutable reference to the path filter, creating it if needed
+    pub fn path_filter_mut(&mut self) -> &mut String {
+        self.selected_index = 0;
+        self.path_filter.get_or_insert_with(String::new)
+    }
+
+    /// Toggle input focus
+    pub fn toggle_focus(&mut self) {
+        self.input_focus = match self.input_focus {
+            InputFocus::Primary => InputFocus::Results,
+            InputFocus::Results if self.directory_tree.is_some() => InputFocus::Tree,
+            InputFocus::Results
+            | InputFocus::Filter
+            | InputFocus::PathFilter
+            | InputFocus::Tree => InputFocus::Primary,
+        }
+    }
+
+    /// Toggle focus between the filter box and the search box
+    pub fn toggle_filter_focus(&mut self) {
+        self.input_focus = match self.input_focus {
+            InputFocus::Filter => InputFocus::Primary,
+            _ => InputFocus::Filter,
+        };
+    }
+
+    /// Toggle focus between the path filter box and the search box
+    pub fn toggle_path_filter_focus(&mut self) {
+        self.input_focus = match self.input_focus {
+            InputFocus::PathFilter => InputFocus::Primary,
+            _ => InputFocus::PathFilter,
+        };
+    }
+
+    /// Show or hide the keybinding help overlay
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+    }
+
+    /// Cycle from the split view to the fullscreen preview to no preview at all
+    pub fn cycle_preview_mode(&mut self) {
+        self.preview_mode = match self.preview_mode {
+            PreviewMode::Normal => PreviewMode::Fullscreen,
+            PreviewMode::Fullscreen => PreviewMode::Hidden,
+            PreviewMode::Hidden => PreviewMode::Normal,
+        };
+    }
+
+    /// Switch the preview between the file and the git diff around the selected result
+    pub fn toggle_diff_view(&mut self) {
+        self.show_diff_view = !self.show_diff_view;
+    }
+
+    /// Unified diff of the unstaged git hunk containing the result line
+    /// `None` when the line is unchanged or the file is not in a git repository
+    pub fn get_diff_context(&self, result: &SearchResult) -> Option<String> {
+        let key = (
+            result.file_path.clone(),
+            result.line_number,
+            file_modified(&result.file_path),
+        );
+        if let Some((cached_key, diff)) = self.diff_cache.borrow().as_ref() {
+            if *cached_key == key {
+                return diff.clone();
+            }
+        }
+
+        let diff = diff_context(Path::new(&result.file_path), result.line_number);
+        *self.diff_cache.borrow_mut() = Some((key, diff.clone()));
+        diff
+    }
+
+    /// Wrap long result lines or cut them at the edge of the results list
+    pub fn toggle_word_wrap(&mut self) {
+        self.word_wrap_results = !self.word_wrap_results;
+    }
+
+    /// Show or hide the number of each result
+    pub fn toggle_result_numbers(&mut self) {
+        self.show_result_numbers = !self.show_result_numbers;
+    }
+
+    /// Move the preview pane between the right of the results list and below it
+    pub fn toggle_preview_position(&mut self) {
+        self.preview_position = match self.preview_position {
+            PreviewPosition::Right => PreviewPosition::Bottom,
+            PreviewPosition::Bottom => PreviewPosition::Right,
+        };
+    }
+
+    /// Show or hide the file type icons
+    pub fn toggle_icons(&mut self) {
+        self.show_icons = !self.show_icons;
+    }
+
+    /// Switch between the aligned column layout and `path:line content` results
+    pub fn toggle_column_layout(&mut self) {
+        self.column_layout = !self.column_layout;
+    }
+
+    /// File type icon of a result, `None` when icons are hidden
+    pub fn get_file_icon(&self, result: &SearchResult) -> Option<&'static str> {
+        self.show_icons
+            .then(|| FileTypeIcon::for_path(&result.file_path))
+    }
+
+    /// Size in bytes of the file of a result, `None` when it couldn't be read
+    pub fn get_file_size(&self, result: &SearchResult) -> Option<u64> {
+        self.sorter.cached_file_size(&result.file_path)
+    }
+
+    /// Open the export prompt with a timestamped default file name
+    pub fn open_export_prompt(&mut self) {
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        self.export_prompt = Some(format!("/tmp/search-rs-results-{}.json", timestamp));
+        self.export_target = ExportTarget::Results;
+    }
+
+    /// Open the export prompt for the annotations with a timestamped default file name
+    pub fn open_annotation_export_prompt(&mut self) {
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        self.export_prompt = Some(format!("/tmp/search-rs-annotations-{}.tsv", timestamp));
+        self.export_target = ExportTarget::Annotations;
+    }
+
+    /// Export to the file name in the prompt and report the outcome in the status bar
+    pub fn confirm_export(&mut self) {
+        let Some(file_name) = self.export_prompt.take() else {
+            return;
+        };
+        let path = Path::new(file_name.trim());
+        let count = match self.selected_results.len() {
+            0 => self.search_results.len(),
+            selected => selected,
+        };
+        if self.export_target == ExportTarget::Annotations {
+            self.status_message = Some(match self.export_annotations(path) {
+                Ok(()) => format!(
+                    "Exported {} annotations to {}",
+                    self.annotations.len(),
+                    path.display()
+                ),
+                Err(e) => format!("Export failed: {}", e),
+            });
+            return;
+        }
+        self.status_message = Some(
+            match self.export_results(path, ExportFormat::from_path(path)) {
+                Ok(()) => format!("Exported {} results to {}", count, path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+    }
+
+    /// Write the search results to a file, only the marked ones when any are marked
+    pub fn export_results(&self, path: &Path, format: ExportFormat) -> crate::Result<()> {
+        let file = File::create(path).map_err(|e| {
+            crate::SearchError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("could not write {}: {}", path.display(), e),
+            ))
+        })?;
+        let writer = BufWriter::new(file);
+        if self.selected_results.is_empty() {
+            output::write_results(writer, &self.search_results, format)
+        } else {
+            let marked: Vec<SearchResult> = self.marked_results().into_iter().cloned().collect();
+            output::write_results(writer, &marked, format)
+        }
+    }
+
+    /// Open the annotation prompt with the note of the selected result, if it has one
+    pub fn open_annotation_prompt(&mut self) {
+        if let Some(result) = self.selected_result() {
+            let note = self.annotation(result).unwrap_or_default().to_string();
+            self.annotation_prompt = Some(note);
+        }
+    }
+
+    /// Attach the note in the prompt to the selected result, an empty note removes it
+    pub fn confirm_annotation(&mut self) {
+        let Some(note) = self.annotation_prompt.take() else {
+            return;
+        };
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let key = (result.file_path.clone(), result.line_number);
+        let note = note.trim();
+        if note.is_empty() {
+            self.annotations.remove(&key);
+        } else {
+            self.annotations.insert(key, note.to_string());
+        }
+    }
+
+    /// Note attached to a result
+    pub fn annotation(&self, result: &SearchResult) -> Option<&str> {
+        self.annotations
+            .get(&(result.file_path.clone(), result.line_number))
+            .map(String::as_str)
+    }
+
+    /// Write the annotations as `file`, `line` and `annotation` columns separated by tabs
+    pub fn export_annotations(&self, path: &Path) -> crate::Result<()> {
+        let mut annotations: Vec<_> = self.annotations.iter().collect();
+        annotations.sort();
+        let mut content = String::new();
+        for ((file_path, line_number), note) in annotations {
+            // A tab or line break in the note would start a new column or row
+            let note = note.replace(['\t', '\n', '\r'], " ");
+            let _ = writeln!(content, "{}\t{}\t{}", file_path, line_number, note);
+        }
+        fs::write(path, content).map_err(|e| {
+            crate::SearchError::file_access_error(&path.to_string_lossy(), &e.to_string())
+        })
+    }
+
+    /// Load annotations written by `export_annotations`, returns how many were loaded
+    /// Lines that are not `file`, `line` and `annotation` columns are skipped
+    pub fn import_annotations(&mut self, path: &Path) -> crate::Result<usize> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            crate::SearchError::file_access_error(&path.to_string_lossy(), &e.to_string())
+        })?;
+        let mut count = 0;
+        for line in content.lines() {
+            let mut columns = line.splitn(3, '\t');
+            let (Some(file_path), Some(Ok(line_number)), Some(note)) = (
+                columns.next(),
+                columns.next().map(str::parse::<usize>),
+                columns.next(),
+            ) else {
+                continue;
+            };
+            self.annotations
+                .insert((file_path.to_string(), line_number), note.to_string());
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Copy the full file path of the selected result to the clipboard
+    pub fn copy_selected_path_to_clipboard(&mut self) -> crate::Result<()> {
+        match self.selected_result() {
+            Some(result) => {
+                let path = result.file_path.clone();
+                self.copy_to_clipboard(path)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Copy the selected result as `path:line content` to the clipboard
+    pub fn copy_selected_line_to_clipboard(&mut self) -> crate::Result<()> {
+        match self.selected_result() {
+            Some(result) => {
+                let line = result.format_for_display(false);
+                self.copy_to_clipboard(line)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// System clipboard, opened on first use
+    fn clipboard(&mut self) -> crate::Result<&mut arboard::Clipboard> {
+        let clipboard = match self.clipboard.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new().map_err(clipboard_error)?,
+        };
+        Ok(self.clipboard.insert(clipboard))
+    }
+
+    /// Write text to the system clipboard
+    fn copy_to_clipboard(&mut self, text: String) -> crate::Result<()> {
+        self.clipboard()?.set_text(text).map_err(clipboard_error)?;
+        self.clipboard_message = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Whether the "Copied!" message is still shown
+    pub fn is_clipboard_message_visible(&self) -> bool {
+        self.clipboard_message
+            .is_some_and(|copied| copied.elapsed() < Duration::from_secs(CLIPBOARD_MESSAGE_SECS))
+    }
+
+    /// Set quit flag
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    /// Update search pattern
+    pub fn update_pattern(&mut self, pattern: String) {
+        crate::logging::debug_log(&format!(
+            "Pattern updated to '{}' in {}",
+            pattern,
+            self.get_directories_label()
+        ));
+        self.current_pattern = pattern.clone();
+        self.previous_committed_pattern = pattern;
+        self.move_cursor_end();
+    }
+
+    /// Cursor offset clamped to the pattern and moved back to a character boundary
+    pub fn cursor(&self) -> usize {
+        let mut cursor = self.cursor_position.min(self.current_pattern.len());
+        while !self.current_pattern.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        cursor
+    }
+
+    /// Selected range of the pattern, `None` when nothing is selected
+    pub fn pattern_selection(&self) -> Option<Range<usize>> {
+        let start = self.selection_start?.min(self.current_pattern.len());
+        let cursor = self.cursor();
+        let range = start.min(cursor)..start.max(cursor);
+        (!range.is_empty() && self.current_pattern.is_char_boundary(range.start)).then_some(range)
+    }
+
+    /// Remove the selected text, returns whether anything was selected
+    fn delete_pattern_selection(&mut self) -> bool {
+        let selection = self.pattern_selection();
+        self.selection_start = None;
+        match selection {
+            Some(range) => {
+                self.cursor_position = range.start;
+                self.current_pattern.replace_range(range, "");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert a character in the pattern at the cursor, replacing the selection
+    pub fn insert_pattern_char(&mut self, c: char) {
+        self.delete_pattern_selection();
+        let cursor = self.cursor();
+        self.current_pattern.insert(cursor, c);
+        self.cursor_position = cursor + c.len_utf8();
+    }
+
+    /// Delete the selection, or the character before the cursor
+    pub fn delete_pattern_char(&mut self) {
+        if self.delete_pattern_selection() {
+            return;
+        }
+        let cursor = self.cursor();
+        if let Some(c) = self.current_pattern[..cursor].chars().next_back() {
+            let start = cursor - c.len_utf8();
+            self.current_pattern.replace_range(start..cursor, "");
+            self.cursor_position = start;
+        }
+    }
+
+    /// Move the cursor one character to the left
+    pub fn move_cursor_left(&mut self) {
+        self.selection_start = None;
+        let cursor = self.cursor();
+        self.cursor_position = self.current_pattern[..cursor]
+            .chars()
+            .next_back()
+            .map_or(cursor, |c| cursor - c.len_utf8());
+    }
+
+    /// Move the cursor one character to the right
+    pub fn move_cursor_right(&mut self) {
+        self.selection_start = None;
+        let cursor = self.cursor();
+        self.cursor_position = self.current_pattern[cursor..]
+            .chars()
+            .next()
+            .map_or(cursor, |c| cursor + c.len_utf8());
+    }
+
+    /// Move the cursor to the start of the pattern
+    pub fn move_cursor_home(&mut self) {
+        self.selection_start = None;
+        self.cursor_position = 0;
+    }
+
+    /// Move the cursor past the end of the pattern
+    pub fn move_cursor_end(&mut self) {
+        self.selection_start = None;
+        self.cursor_position = self.current_pattern.len();
+    }
+
+    /// Extend the selection one character to the left of the cursor
+    pub fn extend_selection_left(&mut self) {
+        let anchor = self.selection_start.unwrap_or(self.cursor());
+        self.move_cursor_left();
+        self.selection_start = Some(anchor);
+    }
+
+    /// Extend the selection one character to the right of the cursor
+    pub fn extend_selection_right(&mut self) {
+        let anchor = self.selection_start.unwrap_or(self.cursor());
+        self.move_cursor_right();
+        self.selection_start = Some(anchor);
+    }
+
+    /// Copy the selected part of the pattern to the clipboard
+    pub fn copy_pattern_selection(&mut self) -> crate::Result<()> {
+        match self.pattern_selection() {
+            Some(range) => {
+                let text = self.current_pattern[range].to_string();
+                self.copy_to_clipboard(text)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Copy the selected part of the pattern to the clipboard and remove it
+    pub fn cut_pattern_selection(&mut self) -> crate::Result<()> {
+        self.copy_pattern_selection()?;
+        self.delete_pattern_selection();
+        Ok(())
+    }
+
+    /// Insert the clipboard text at the cursor, replacing the selection
+    /// Line breaks are dropped since the pattern is a single line
+    pub fn paste_into_pattern(&mut self) -> crate::Result<()> {
+        let text = self.clipboard()?.get_text().map_err(clipboard_error)?;
+        self.delete_pattern_selection();
+        let text: String = text.chars().filter(|c| !matches!(c, '\n' | '\r')).collect();
+        let cursor = self.cursor();
+        self.current_pattern.insert_str(cursor, &text);
+        self.cursor_position = cursor + text.len();
+        Ok(())
+    }
+
+    /// Mark the current pattern as submitted
+    pub fn commit_pattern(&mut self) {
+        self.previous_committed_pattern = self.current_pattern.clone();
+    }
+
+    /// Checks if the pattern was edited since it was last submitted
+    pub fn is_pattern_edited(&self) -> bool {
+        self.current_pattern != self.previous_committed_pattern
+    }
+
+    /// Drop the edits to the pattern and cancel the search they scheduled
+    pub fn restore_committed_pattern(&mut self) {
+        self.current_pattern = self.previous_committed_pattern.clone();
+        self.pending_search = None;
+        self.history_index = None;
+        self.move_cursor_end();
+    }
+
+    /// Record a committed search pattern in the history
+    /// Consecutive duplicates are skipped and the oldest entries dropped past the limit
+    pub fn push_search_history(&mut self, pattern: &str) {
+        self.history_index = None;
+        if pattern.trim().is_empty()
+            || self.search_history.last().map(String::as_str) == Some(pattern)
+        {
+            return;
+        }
+
+        self.search_history.push(pattern.to_string());
+        if self.search_history.len() > SEARCH_HISTORY_LIMIT {
+            let overflow = self.search_history.len() - SEARCH_HISTORY_LIMIT;
+            self.search_history.drain(..overflow);
+        }
+        if let Some(path) = &self.history_file {
+            if let Err(e) = history::append(path, pattern) {
+                crate::logging::warn_log(&format!("Failed to save the search history: {}", e));
+            }
+        }
+    }
+
+    /// Load the history saved by earlier sessions and save new searches to the file
+    /// The history starts empty when the file can't be read
+    pub fn load_history_file(&mut self, path: PathBuf) {
+        match history::load(&path) {
+            Ok(mut patterns) => {
+                let overflow = patterns.len().saturating_sub(SEARCH_HISTORY_LIMIT);
+                patterns.drain(..overflow);
+                self.search_history = patterns;
+            }
+            Err(e) => {
+                crate::logging::warn_log(&format!("Failed to load the search history: {}", e))
+            }
+        }
+        self.history_file = Some(path);
+    }
+
+    /// Forget every searched pattern, deleting the history file
+    pub fn clear_history(&mut self) -> crate::Result<()> {
+        self.search_history.clear();
+        self.history_index = None;
+        match &self.history_file {
+            Some(path) => history::clear(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Recall the previous (older) pattern from the history
+    pub fn history_previous(&mut self) {
+        let index = match self.history_index {
+            Some(0) => 0,
+            Some(index) => index - 1,
+            None if self.search_history.is_empty() => return,
+            None => {
+                self.history_draft = self.current_pattern.clone();
+                self.search_history.len() - 1
+            }
+        };
+        self.history_index = Some(index);
+        self.current_pattern = self.search_history[index].clone();
+        self.move_cursor_end();
+    }
+
+    /// Recall the next (newer) pattern, restoring the typed pattern past the newest entry
+    pub fn history_next(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        if index + 1 < self.search_history.len() {
+            self.history_index = Some(index + 1);
+            self.current_pattern = self.search_history[index + 1].clone();
+        } else {
+            self.history_index = None;
+            self.current_pattern = std::mem::take(&mut self.history_draft);
+        }
+        self.move_cursor_end();
+    }
+
+    /// Set the directories being searched
+    pub fn set_search_directories(&mut self, directories: Vec<String>) {
+        self.search_directories = directories;
+    }
+
+    /// Get all active search directories as a display label
+    pub fn get_directories_label(&self) -> String {
+        self.search_directories.join(", ")
+    }
+
+    /// Set the search mode shown in the status bar
+    pub fn set_search_mode(&mut self, mode: SearchEngineMode) {
+        self.search_mode = mode;
+    }
+
+    /// Get the label of the search mode in effect for the current pattern
+    pub fn get_mode_label(&self) -> &'static str {
+        self.search_mode.label(self.active_pattern())
+    }
+
+    /// Get plain preview content for the currently selected result with optional terminal dimensions
+    pub fn get_preview_content_plain(&self, terminal_dimensions: Option<(usize, usize)>) -> String {
+        if self.preview_mode == PreviewMode::Hidden {
+            return String::new();
+        }
+        if let Some(result) = self.selected_result() {
+            match self.get_cached_preview(result, terminal_dimensions) {
+                Ok(preview) => preview,
+                Err(e) => format!("Error Loading Preview: {}", e),
+            }
+        } else {
+            "No file selected".to_string()
+        }
+    }
+
+    /// Get the table preview of the selected result when its file is a CSV or TSV file,
+    /// `None` for other files, files that don't parse as CSV and with a preview command
+    pub fn get_preview_table(
+        &self,
+        terminal_dimensions: Option<(usize, usize)>,
+    ) -> Option<CsvTable> {
+        if self.preview_mode == PreviewMode::Hidden || self.preview_handler.has_preview_command() {
+            return None;
+        }
+        let result = self.selected_result()?;
+        let path = Path::new(&result.file_path);
+        if !PreviewHandler::is_table(path) {
+            return None;
+        }
+        self.evict_preview_cache_for(&result.file_path);
+        let cache_key = Self::preview_cache_key(result, terminal_dimensions);
+        self.table_preview_cache
+            .borrow_mut()
+            .entry(cache_key)
+            .or_insert_with(|| {
+                self.preview_handler
+                    .preview_csv(path, Some(result.line_number), terminal_dimensions)
+                    .ok()
+            })
+            .clone()
+    }
+
+    /// Get syntax highlighted preview content for the currently selected result
+    pub fn get_preview_content_highlighted(
+        &self,
+        terminal_dimensions: Option<(usize, usize)>,
+        highlighter: &mut SyntaxHighlighter,
+    ) -> Text<'static> {
+        if self.preview_mode == PreviewMode::Hidden {
+            return Text::default();
+        }
+        if let Some(result) = self.selected_result() {
+            let cache_key = Self::preview_cache_key(result, terminal_dimensions);
+            if let Some(cached) = self.highlighted_preview_cache.borrow().get(&cache_key) {
+                return cached.clone();
+            }
+
+            // A configured preview command replaces the Markdown preview
+            let path = Path::new(&result.file_path);
+            let builtin = !self.preview_handler.has_preview_command();
+            let highlighted = if builtin && self.preview_handler.renders_markdown(path) {
+                self.evict_preview_cache_for(&result.file_path);
+                self.preview_handler.preview_markdown(
+                    &result.file_path,
+                    Some(result.line_number),
+                    terminal_dimensions,
+                    highlighter,
+                )
+            } else {
+                self.get_cached_preview(result, terminal_dimensions)
+                    .and_then(|preview| {
+                        self.preview_handler.highlight_preview(
+                            &result.file_path,
+                            &preview,
+                            Some(result.line_number),
+                            highlighter,
+                        )
+                    })
+            };
+            match highlighted {
+                Ok(preview) => {
+                    self.highlighted_preview_cache
+                        .borrow_mut()
+                        .insert(cache_key, preview.clone());
+                    preview
+                }
+                Err(e) => Text::from(format!("Error Loading Preview: {}", e)),
+            }
+        } else {
+            Text::from("No file selected")
+        }
+    }
+
+    /// Get the plain preview for a result, reading the file only on a cache miss
+    fn get_cached_preview(
+        &self,
+        result: &SearchResult,
+        terminal_dimensions: Option<(usize, usize)>,
+    ) -> crate::Result<String> {
+        self.evict_preview_cache_for(&result.file_path);
+
+        let cache_key = Self::preview_cache_key(result, terminal_dimensions);
+        if let Some(cached) = self.preview_cache.borrow().get(&cache_key) {
+            return Ok(cached.clone());
+        }
+
+        let preview = self.preview_handler.preview_file(
+            &result.file_path,
+            Some(result.line_number),
+            terminal_dimensions,
+        )?;
+        self.preview_cache
+            .borrow_mut()
+            .insert(cache_key, preview.clone());
+        Ok(preview)
+    }
+
+    /// Build the preview cache key for a result
+    fn preview_cache_key(
+        result: &SearchResult,
+        terminal_dimensions: Option<(usize, usize)>,
+    ) -> PreviewCacheKey {
+        let (width, height) = terminal_dimensions.unwrap_or((0, 0));
+        (result.file_path.clone(), result.line_number, width, height)
+    }
+
+    /// Evict the preview caches when the selection moves to a different file
+    fn evict_preview_cache_for(&self, file_path: &str) {
+        let mut cached_file = self.preview_cache_file.borrow_mut();
+        if cached_file.as_deref() != Some(file_path) {
+            self.preview_cache.borrow_mut().clear();
+            self.highlighted_preview_cache.borrow_mut().clear();
+            self.table_preview_cache.borrow_mut().clear();
+            *self.preview_line_count.borrow_mut() = None;
+            *cached_file = Some(file_path.to_string());
+        }
+    }
+
+    /// Clear all cached preview content
+    pub fn clear_preview_cache(&mut self) {
+        self.preview_cache.borrow_mut().clear();
+        self.highlighted_preview_cache.borrow_mut().clear();
+        self.table_preview_cache.borrow_mut().clear();
+        *self.preview_cache_file.borrow_mut() = None;
+        *self.preview_line_count.borrow_mut() = None;
+    }
+
+    /// Number of lines in the file of the selected result, counted once per previewed file
+    pub fn selected_file_line_count(&self) -> Option<usize> {
+        let result = self.selected_result()?;
+        self.evict_preview_cache_for(&result.file_path);
+
+        let mut line_count = self.preview_line_count.borrow_mut();
+        if line_count.is_none() {
+            *line_count = PreviewHandler::count_lines(&result.file_path).ok();
+        }
+        *line_count
+    }
+
+    /// Handle mouse click within the results list
+    /// `click_row` is a terminal row, 0 at the top of the screen. The list was last drawn
+    /// from the row `area.first_row` of `visible_rows` on, with the row heights in `area`
+    /// Returns true if the click selected a row, false for clicks outside the list or
+    /// below its last row, which leave the selection as it was
+    pub fn handle_results_click(&mut self, click_row: u16, area: &ResultsAreaInfo) -> bool {
+        if click_row < area.top || click_row >= area.top + area.height {
+            return false; // Click was outside of the results list
+        }
+
+        let mut line = click_row - area.top;
+        for (offset, &height) in area.row_heights.iter().enumerate() {
+            if line < height {
+                let index = area.first_row + offset;
+                if index >= self.visible_rows().len() {
+                    return false;
+                }
+                self.select_iindex(index);
+                return true;
+            }
+            line -= height;
+        }
+        false
+    }
+
+    /// Set selection to a specific index
+    pub fn select_iindex(&mut self, index: usize) {
+        if index < self.visible_rows().len() {
+            self.selected_index = index;
+        }
+    }
+
+    /// Get a reference to the search pattern
+    pub fn active_pattern(&self) -> &str {
+        &self.current_pattern
+    }
+
+    /// Get a reference to the search pattern
+    pub fn active_pattern_mut(&mut self) -> &mut String {
+        &mut self.current_pattern
+    }
+
+    /// Get progressive loading status for display
+    pub fn get_progressive_load_status(&self) -> Option<&ProgressiveLoadStatus> {
         self.progressive_load_status.as_ref()
     }
 
-    /// Override select_next to trigger progressive loading
+    /// Select the next result, drawing new results as they arrive when it nears the end
     pub fn select_next(&mut self) {
-        if !self.active_results().is_empty()
-            && self.selected_index < self.active_results().len() - 1
-        {
+        let result_count = self.visible_rows().len();
+        if result_count > 0 && self.selected_index < result_count - 1 {
             self.selected_index += 1;
-            // Request progressive loading check when navigating down
-            self.needs_progressive_load_check = true;
+            self.needs_progressive_load_check =
+                self.selected_index + self.progressive_load_threshold >= result_count;
         }
     }
 
-    /// Override select_prev to trigger progressive loading
+    /// Select the previous result
     pub fn select_previous(&mut self) {
         if self.selected_index > 0 {
             self.selected_index -= 1;
-            // Also check when navigating up
-            self.needs_progressive_load_check = true;
+            self.check_progressive_load();
+        }
+    }
+
+    /// Scroll the preview down
+    pub fn scroll_preview_down(&mut self, lines: usize) {
+        self.preview_scroll_offset += lines;
+    }
+
+    /// Adapt to a resized terminal without touching the results or the running search
+    /// Previews are rendered again at the new size and the target line stays in view
+    pub fn handle_resize(&mut self, columns: u16, rows: u16) {
+        if self.terminal_dimensions == (columns, rows) {
+            return;
+        }
+        self.terminal_dimensions = (columns, rows);
+        self.clear_preview_cache();
+
+        // The target line sits in the middle of the preview, scrolling more than half
+        // of the pane height moves it out of view
+        self.preview_scroll_offset = self.preview_scroll_offset.min(self.preview_height() / 2);
+    }
+
+    /// Rows of text in the preview pane at the current terminal size
+    fn preview_height(&self) -> usize {
+        // Header and borders in fullscreen, otherwise the search box, status bar,
+        // borders and the path filter box when it is shown
+        let fullscreen = self.preview_mode == PreviewMode::Fullscreen;
+        let chrome = if fullscreen {
+            3
+        } else if self.input_focus == InputFocus::PathFilter || self.path_filter.is_some() {
+            9
+        } else {
+            6
+        };
+        let height = (self.terminal_dimensions.1 as usize).saturating_sub(chrome);
+        // Below the results list the preview gets half of the rows, with its own borders
+        if self.preview_position == PreviewPosition::Bottom && !fullscreen {
+            (height / 2).saturating_sub(2)
+        } else {
+            height
+        }
+    }
+
+    /// Scroll the preview up, back towards the target line
+    pub fn scroll_preview_up(&mut self, lines: usize) {
+        self.preview_scroll_offset = self.preview_scroll_offset.saturating_sub(lines);
+    }
+
+    /// Select the first result
+    pub fn select_first(&mut self) {
+        self.selected_index = 0;
+        self.check_progressive_load();
+    }
+
+    /// Select the last result
+    pub fn select_last(&mut self) {
+        self.selected_index = self.visible_rows().len().saturating_sub(1);
+        self.needs_progressive_load_check = true;
+    }
+
+    /// Position of the selection as `current/total` for the status bar
+    pub fn get_position_label(&self) -> String {
+        let total = self.visible_rows().len();
+        let current = (self.selected_index + 1).min(total);
+        format!("{}/{}", current, total)
+    }
+
+    /// Move the selection down by a page, stopping at the last result
+    pub fn select_page_down(&mut self, page_size: usize) {
+        let result_count = self.visible_rows().len();
+        if result_count > 0 {
+            self.selected_index = (self.selected_index + page_size.max(1)).min(result_count - 1);
+            self.check_progressive_load();
         }
     }
 
+    /// Move the selection up by a page, stopping at the first result
+    pub fn select_page_up(&mut self, page_size: usize) {
+        self.selected_index = self.selected_index.saturating_sub(page_size.max(1));
+        self.check_progressive_load();
+    }
+
     /// Get loading progress message for display
     pub fn get_loading_message(&self) -> String {
-        if let Some(status) = &self.progressive_load_status {
+        let message = if let Some(status) = &self.progressive_load_status {
+            let counts = format!(
+                "{} matches in {} files",
+                status.total_matches, status.total_files_found
+            );
             if status.loading_complete {
-                format!(
-                    "Loaded {} results from {} files",
-                    status.total_loaded, status.total_files_found
-                )
+                format!("Found {}", counts)
+            } else if self.search_progress.is_cancelled {
+                format!("Search cancelled - {}", counts)
             } else {
-                format!(
-                    "Loading... {} results from {} files",
-                    status.total_loaded, status.total_files_found
-                )
+                format!("Loading... {}", counts)
             }
         } else if self.search_progress.is_searching {
             format!(
                 "Searching... {} files found",
                 self.search_progress.files_with_matches
             )
+        } else if self.search_progress.is_cancelled {
+            format!(
+                "Search cancelled - {} files",
+                self.search_progress.files_with_matches
+            )
         } else if self.search_progress.is_complete {
             format!(
                 "Search complete - {} files",
@@ -331,6 +2141,14 @@ impl App {
             )
         } else {
             "Ready to search".to_string()
+        };
+
+        if self.is_result_limit_reached() {
+            format!("{} (stopped at --max-results)", message)
+        } else if self.results_truncated {
+            format!("{} (truncated by --max-count)", message)
+        } else {
+            message
         }
     }
 
@@ -344,45 +2162,78 @@ impl App {
         use std::hash::{Hash, Hasher};
 
         // Create cache key from result data
+        // The matched text is part of the key because it is highlighted in the line
         let mut hasher = DefaultHasher::new();
         result.file_path.hash(&mut hasher);
         result.line_number.hash(&mut hasher);
         result.line_content.hash(&mut hasher);
+        result.matched_text.hash(&mut hasher);
         let cache_key = hasher.finish();
 
-        // Check cache first
-        if let Some(cached_line) = self.highlighted_cache.borrow().get(&cache_key) {
-            return cached_line.clone();
-        }
-
-        // Not in cache, compute and cache
-        let highlighted_line = result.format_for_tui_display(highlighter);
-
-        // Manage cache size and insert
+        // Check cache first, entries of files changed on disk since are stale
+        let modified = self.cached_file_modified(&result.file_path);
         {
             let mut cache = self.highlighted_cache.borrow_mut();
-
-            // Manage cache size before inserting
-            if cache.len() >= self.cache_size_limit {
-                // Remove oldest entries - not exactly LRU
-                let keys_to_remove: Vec<u64> = cache
-                    .keys()
-                    .take(self.cache_size_limit / 4)
-                    .cloned()
-                    .collect();
-                for key in keys_to_remove {
-                    cache.remove(&key);
+            match cache.get(&cache_key) {
+                Some(cached) if cached.modified == modified => {
+                    self.cache_hits.set(self.cache_hits.get() + 1);
+                    return cached.line.clone();
                 }
+                Some(_) => {
+                    cache.pop(&cache_key);
+                }
+                None => {}
             }
-
-            cache.insert(cache_key, highlighted_line.clone());
         }
+        self.cache_misses.set(self.cache_misses.get() + 1);
+
+        // Not in cache, compute and cache, evicting the least recently used line when full
+        let highlighted_line = result.format_for_tui_display(highlighter, true);
+        self.highlighted_cache.borrow_mut().put(
+            cache_key,
+            CachedLine {
+                line: highlighted_line.clone(),
+                modified,
+            },
+        );
         highlighted_line
     }
 
-    /// Clear the highlighting cache (called when starting a new search)
+    /// Modification time of a file, looked up again at most every `MTIME_CHECK_INTERVAL_MS`
+    /// so redraws don't read the metadata of every file on screen
+    fn cached_file_modified(&self, path: &str) -> Option<SystemTime> {
+        let now = Instant::now();
+        let mut mtimes = self.file_mtimes.borrow_mut();
+        match mtimes.get(path) {
+            Some(&(checked_at, modified))
+                if now.duration_since(checked_at)
+                    < Duration::from_millis(MTIME_CHECK_INTERVAL_MS) =>
+            {
+                modified
+            }
+            _ => {
+                let modified = file_modified(path);
+                mtimes.insert(path.to_string(), (now, modified));
+                modified
+            }
+        }
+    }
+
+    /// Clear the highlighting cache
     pub fn clear_highlighting_cache(&mut self) {
         self.highlighted_cache.borrow_mut().clear();
+        self.file_mtimes.borrow_mut().clear();
+    }
+
+    /// Share of highlighted line lookups served from the cache, 0 before the first lookup
+    pub fn cache_hit_rate(&self) -> f64 {
+        let hits = self.cache_hits.get();
+        let total = hits + self.cache_misses.get();
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
     }
 
     /// Get cache stats for debugging
@@ -391,8 +2242,591 @@ impl App {
     }
 }
 
+/// Clipboard errors are shown in the status bar
+fn clipboard_error(e: arboard::Error) -> crate::SearchError {
+    crate::SearchError::TuiError(format!("Clipboard unavailable: {}", e))
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::mock_backend::{collect_results, mock_result, MockBackend};
+    use crate::search::sorter::GitBlameInfo;
+
+    fn mock_results(pattern: &str) -> Vec<SearchResult> {
+        let engine = MockBackend::from_results(vec![
+            mock_result("a.rs", 1, "fn main() {"),
+            mock_result("a.rs", 3, "    main_loop();"),
+            mock_result("b.rs", 7, "// main entry point"),
+            mock_result("c.rs", 2, "let x = 1;"),
+        ])
+        .into_engine();
+        collect_results(&engine, pattern)
+    }
+
+    #[test]
+    fn test_add_sarch_results() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+        assert_eq!(app.search_results.len(), 3);
+
+        let status = app.progressive_load_status.as_ref().unwrap();
+        assert_eq!(status.total_matches, 3);
+        assert_eq!(status.total_files_found, 2);
+        assert_eq!(status.total_loaded, 3);
+
+        // Results streamed again are dropped as duplicates
+        app.add_sarch_results(mock_results("main"));
+        assert_eq!(app.search_results.len(), 3);
+        let status = app.progressive_load_status.as_ref().unwrap();
+        assert_eq!(status.deduplication_count, 3);
+    }
+
+    #[test]
+    fn test_progressive_load_check() {
+        let mut app = App::new();
+        app.progressive_load_threshold = 1;
+        app.start_new_search();
+        assert!(app.needs_progressive_load_check);
+
+        // Enough results are loaded past the selection
+        app.add_sarch_results(mock_results("main"));
+        assert!(!app.needs_progressive_load_check);
+
+        app.select_next();
+        assert!(!app.needs_progressive_load_check);
+        app.select_next();
+        assert!(app.needs_progressive_load_check);
+        app.select_previous();
+        assert!(!app.needs_progressive_load_check);
+        app.select_last();
+        assert!(app.needs_progressive_load_check);
+    }
+
+    #[test]
+    fn test_add_sarch_results_respects_max_results() {
+        let mut app = App::new();
+        app.max_results = Some(2);
+        app.add_sarch_results(mock_results("main"));
+        assert_eq!(app.search_results.len(), 2);
+        assert!(app.is_result_limit_reached());
+
+        app.add_sarch_results(mock_results("x"));
+        assert_eq!(app.search_results.len(), 2);
+    }
+
+    #[test]
+    fn test_jump_to_file() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+
+        assert!(app.jump_to_file("b.rs"));
+        assert_eq!(app.selected_index, 2);
+        assert!(!app.jump_to_file("b"));
+        assert_eq!(app.selected_index, 2);
+        assert!(app.jump_to_file("a.rs"));
+        assert_eq!(app.selected_index, 0);
+
+        app.jump_to_next_file();
+        assert_eq!(app.selected_index, 2);
+        app.jump_to_next_file();
+        assert_eq!(app.selected_index, 2);
+        app.jump_to_prev_file();
+        assert_eq!(app.selected_index, 0);
+
+        // File headers are rows of their file
+        app.grouped_view = true;
+        assert!(app.jump_to_file("b.rs"));
+        assert_eq!(app.selected_index, 4);
+        app.jump_to_prev_file();
+        assert_eq!(app.selected_index, 0);
+        app.jump_to_next_file();
+        assert_eq!(app.selected_index, 3);
+    }
+
+    #[test]
+    fn test_handle_results_click() {
+        let mut app = App::new();
+        app.add_sarch_results(vec![
+            mock_result("a.rs", 1, "main"),
+            mock_result("a.rs", 2, "main"),
+            mock_result("b.rs", 3, "main"),
+        ]);
+
+        // The list starts on row 5 and is 10 rows high
+        let area = ResultsAreaInfo {
+            top: 5,
+            height: 10,
+            left: 0,
+            width: 40,
+            page_size: 3,
+            first_row: 0,
+            row_heights: vec![1, 1, 1],
+            tree: None,
+            preview: ratatui::layout::Rect::default(),
+        };
+        assert!(app.handle_results_click(7, &area));
+        assert_eq!(app.selected_index, 2);
+        assert!(app.handle_results_click(5, &area));
+        assert_eq!(app.selected_index, 0);
+
+        // Rows below the last result and clicks outside the list keep the selection
+        assert!(!app.handle_results_click(8, &area));
+        assert!(!app.handle_results_click(4, &area));
+        assert!(!app.handle_results_click(15, &area));
+        assert_eq!(app.selected_index, 0);
+    }
+
+    #[test]
+    fn test_handle_results_click_scrolled_and_wrapped() {
+        let mut app = App::new();
+        app.add_sarch_results(
+            (1..=20)
+                .map(|line| mock_result("a.rs", line, &"main ".repeat(line % 3 * 10 + 1)))
+                .collect(),
+        );
+        app.word_wrap_results = true;
+        app.select_iindex(19);
+
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        let mut highlighter = SyntaxHighlighter::new();
+        let mut area = None;
+        terminal
+            .draw(|frame| area = Some(crate::tui::ui::draw(frame, &app, &mut highlighter)))
+            .unwrap();
+        let area = area.unwrap();
+        assert!(area.first_row > 0);
+        assert!(area.row_heights.iter().any(|&height| height > 1));
+
+        // Every line of a row selects that row, counted from the first row on screen
+        let mut top = area.top;
+        for (offset, &height) in area.row_heights.iter().enumerate() {
+            for line in top..(top + height).min(area.top + area.height) {
+                assert!(app.handle_results_click(line, &area));
+                assert_eq!(app.selected_index, area.first_row + offset);
+            }
+            top += height;
+        }
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let mut app = App::new();
+        app.add_sarch_results(vec![
+            mock_result("src/main.rs", 1, "main"),
+            mock_result("tests/Search_Test.rs", 2, "main"),
+            mock_result("src/tests/mod.rs", 3, "main"),
+        ]);
+        let paths = |app: &App| -> Vec<String> {
+            let mut paths: Vec<String> = app
+                .active_results_filtered()
+                .iter()
+                .map(|result| result.file_path.clone())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        // Without wildcards the filter matches anywhere, ignoring case
+        app.path_filter_mut().push_str("TEST");
+        assert_eq!(paths(&app), ["src/tests/mod.rs", "tests/Search_Test.rs"]);
+        assert!(app.is_filtered());
+
+        *app.path_filter_mut() = "tests/*".to_string();
+        assert_eq!(paths(&app), ["tests/Search_Test.rs"]);
+
+        // Combined with the content filter
+        *app.path_filter_mut() = "*.rs".to_string();
+        app.filter_pattern_mut().push_str("mod");
+        assert_eq!(paths(&app), ["src/tests/mod.rs"]);
+
+        // A half-typed glob matches literally
+        app.filter_pattern = None;
+        *app.path_filter_mut() = "src/[".to_string();
+        assert!(paths(&app).is_empty());
+
+        app.path_filter = None;
+        assert_eq!(paths(&app).len(), 3);
+        assert!(!app.is_filtered());
+    }
+
+    #[test]
+    fn test_history_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+        std::fs::write(&path, "fn main\nTODO\n").unwrap();
+
+        let mut app = App::new();
+        app.load_history_file(path.clone());
+        assert_eq!(app.search_history, vec!["fn main", "TODO"]);
+
+        app.push_search_history("unwrap");
+        assert_eq!(history::load(&path).unwrap().last().unwrap(), "unwrap");
+
+        app.clear_history().unwrap();
+        assert!(app.search_history.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+        app.open_annotation_prompt();
+        assert_eq!(app.annotation_prompt.as_deref(), Some(""));
+        app.annotation_prompt = Some("check\tthis ".to_string());
+        app.confirm_annotation();
+        assert!(app.annotation_prompt.is_none());
+        let first = app.search_results[0].clone();
+        assert_eq!(app.annotation(&first), Some("check\tthis"));
+        assert_eq!(app.annotation(&app.search_results[1]), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.tsv");
+        app.export_annotations(&path).unwrap();
+        let line = format!("{}\t{}\tcheck this\n", first.file_path, first.line_number);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), line);
+
+        std::fs::write(&path, format!("{}not an annotation\n", line)).unwrap();
+        let mut app = App::new();
+        assert_eq!(app.import_annotations(&path).unwrap(), 1);
+        assert_eq!(app.annotation(&first), Some("check this"));
+
+        // An empty note removes the annotation
+        app.add_sarch_results(mock_results("main"));
+        app.open_annotation_prompt();
+        assert_eq!(app.annotation_prompt.as_deref(), Some("check this"));
+        app.annotation_prompt = Some(String::new());
+        app.confirm_annotation();
+        assert!(app.annotations.is_empty());
+    }
+
+    #[test]
+    fn test_export_prompt_target() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+        app.annotation_prompt = Some("check".to_string());
+        app.confirm_annotation();
+        let dir = tempfile::tempdir().unwrap();
+
+        // The extension only picks the format, a `.tsv` results export still gets the results
+        let results = dir.path().join("results.tsv");
+        app.open_export_prompt();
+        app.export_prompt = Some(results.to_string_lossy().into_owned());
+        app.confirm_export();
+        let content = std::fs::read_to_string(&results).unwrap();
+        assert_eq!(content.lines().count(), app.search_results.len());
+
+        let notes = dir.path().join("notes.txt");
+        app.open_annotation_export_prompt();
+        assert_eq!(app.export_target, ExportTarget::Annotations);
+        app.export_prompt = Some(notes.to_string_lossy().into_owned());
+        app.confirm_export();
+        let first = &app.search_results[0];
+        let line = format!("{}\t{}\tcheck\n", first.file_path, first.line_number);
+        assert_eq!(std::fs::read_to_string(&notes).unwrap(), line);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some(format!("Exported 1 annotations to {}", notes.display()).as_str())
+        );
+    }
+
+    #[test]
+    fn test_restore_committed_pattern() {
+        let mut app = App::new();
+        app.update_pattern("main".to_string());
+        assert!(!app.is_pattern_edited());
+
+        app.active_pattern_mut().push_str("_loop");
+        app.schedule_search();
+        assert!(app.is_pattern_edited());
+
+        app.restore_committed_pattern();
+        assert_eq!(app.active_pattern(), "main");
+        assert!(app.pending_search.is_none());
+        assert!(!app.is_pattern_edited());
+
+        // Submitting the edit makes it the pattern to go back to
+        app.active_pattern_mut().push_str("_loop");
+        app.commit_pattern();
+        app.active_pattern_mut().clear();
+        app.restore_committed_pattern();
+        assert_eq!(app.active_pattern(), "main_loop");
+    }
+
+    #[test]
+    fn test_pattern_cursor() {
+        let mut app = App::new();
+        app.update_pattern("fn ma".to_string());
+        assert_eq!(app.cursor_position, 5);
+
+        app.move_cursor_left();
+        app.move_cursor_left();
+        app.insert_pattern_char('é');
+        assert_eq!(app.active_pattern(), "fn éma");
+        assert_eq!(app.cursor_position, 5);
+
+        app.move_cursor_right();
+        app.delete_pattern_char();
+        assert_eq!(app.active_pattern(), "fn éa");
+        assert_eq!(app.cursor_position, 5);
+        app.move_cursor_left();
+        assert_eq!(app.cursor_position, 3);
+
+        app.move_cursor_home();
+        app.move_cursor_left();
+        app.delete_pattern_char();
+        assert_eq!(app.active_pattern(), "fn éa");
+        assert_eq!(app.cursor_position, 0);
+
+        app.move_cursor_end();
+        app.move_cursor_right();
+        app.insert_pattern_char('x');
+        assert_eq!(app.active_pattern(), "fn éax");
+    }
+
+    #[test]
+    fn test_pattern_selection() {
+        let mut app = App::new();
+        app.update_pattern("fn main".to_string());
+        assert_eq!(app.pattern_selection(), None);
+
+        app.extend_selection_left();
+        app.extend_selection_left();
+        app.extend_selection_left();
+        assert_eq!(app.pattern_selection(), Some(4..7));
+        app.extend_selection_right();
+        assert_eq!(app.pattern_selection(), Some(5..7));
+
+        // Typing replaces the selection
+        app.insert_pattern_char('x');
+        assert_eq!(app.active_pattern(), "fn max");
+        assert_eq!(app.pattern_selection(), None);
+
+        app.move_cursor_home();
+        app.extend_selection_right();
+        app.extend_selection_right();
+        app.delete_pattern_char();
+        assert_eq!(app.active_pattern(), " max");
+        assert_eq!(app.cursor_position, 0);
+
+        // Moving the cursor drops the selection
+        app.extend_selection_right();
+        app.move_cursor_right();
+        assert_eq!(app.pattern_selection(), None);
+        assert_eq!(app.cursor_position, 2);
+    }
+
+    #[test]
+    fn test_handle_resize() {
+        let mut app = App::new();
+        app.handle_resize(120, 40);
+        app.start_new_search();
+        app.add_sarch_results(mock_results("main"));
+        app.select_next();
+        app.scroll_preview_down(30);
+
+        app.handle_resize(80, 20);
+        assert_eq!(app.terminal_dimensions, (80, 20));
+        // Results, selection and the running search are kept
+        assert_eq!(app.search_results.len(), 3);
+        assert_eq!(app.selected_index, 1);
+        assert!(app.search_progress.is_searching);
+        // 14 rows of preview, the target line stays in view
+        assert_eq!(app.preview_scroll_offset, 7);
+
+        app.scroll_preview_up(5);
+        app.handle_resize(80, 60);
+        assert_eq!(app.preview_scroll_offset, 2);
+    }
+
+    #[test]
+    fn test_mtime_label() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+        let result = app.search_results[0].clone();
+        assert_eq!(app.get_mtime_label(&result), None);
+
+        // Times are only looked up for results added while the column is shown
+        app.set_show_mtime(true);
+        app.update_search_results(mock_results("main"));
+        app.mtime_format = "%Y".to_string();
+        let label = app.get_mtime_label(&result).unwrap();
+        assert_eq!(label.len(), 4);
+
+        app.mtime_format = "%Q".to_string();
+        assert_eq!(app.get_mtime_label(&result), None);
+    }
+
+    #[test]
+    fn test_blame_label() {
+        let mut app = App::new();
+        let mut result = mock_results("main")[0].clone();
+        result.blame_info = Some(GitBlameInfo {
+            author: "Alice".to_string(),
+            short_hash: "abc1234".to_string(),
+            timestamp: std::time::SystemTime::UNIX_EPOCH,
+        });
+        assert_eq!(app.get_blame_label(&result), None);
+
+        app.set_show_git_blame(true);
+        assert_eq!(
+            app.get_blame_label(&result).as_deref(),
+            Some("abc1234 Alice")
+        );
+        result.blame_info = None;
+        assert_eq!(app.get_blame_label(&result), None);
+    }
+
+    #[test]
+    fn test_file_icon() {
+        let mut app = App::new();
+        let result = mock_result("src/main.rs", 1, "fn main() {");
+        assert_eq!(app.get_file_icon(&result), None);
+
+        app.toggle_icons();
+        assert_eq!(
+            app.get_file_icon(&result),
+            Some(FileTypeIcon::for_extension("rs"))
+        );
+    }
+
+    #[test]
+    fn test_toggle_preview_position() {
+        let mut app = App::new();
+        app.handle_resize(120, 46);
+        assert_eq!(app.preview_position, PreviewPosition::Right);
+        assert_eq!(app.preview_height(), 40);
+
+        app.toggle_preview_position();
+        assert_eq!(app.preview_position, PreviewPosition::Bottom);
+        assert_eq!(app.preview_height(), 18);
+
+        app.toggle_preview_position();
+        assert_eq!(app.preview_position, PreviewPosition::Right);
+    }
+
+    #[test]
+    fn test_cycle_preview_mode() {
+        let mut app = App::new();
+        app.add_sarch_results(mock_results("main"));
+        assert_eq!(app.preview_mode, PreviewMode::Normal);
+
+        app.cycle_preview_mode();
+        assert_eq!(app.preview_mode, PreviewMode::Fullscreen);
+        app.cycle_preview_mode();
+        assert_eq!(app.preview_mode, PreviewMode::Hidden);
+        assert!(app.get_preview_content_plain(None).is_empty());
+
+        app.cycle_preview_mode();
+        assert_eq!(app.preview_mode, PreviewMode::Normal);
+    }
+
+    #[test]
+    fn test_max_filesize() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = dir.path().join("small.rs");
+        let large = dir.path().join("large.rs");
+        std::fs::write(&small, "fn main() {}\n").unwrap();
+        std::fs::write(&large, "fn main() {}\n".repeat(100)).unwrap();
+        let small = small.to_string_lossy().into_owned();
+        let large = large.to_string_lossy().into_owned();
+
+        let mut app = App::new();
+        app.max_filesize = Some(100);
+        app.add_sarch_results(vec![
+            mock_result(&small, 1, "fn main() {}"),
+            mock_result(&large, 1, "fn main() {}"),
+            mock_result("missing.rs", 1, "fn main() {}"),
+        ]);
+
+        let paths: Vec<&str> = app
+            .search_results
+            .iter()
+            .map(|result| result.file_path.as_str())
+            .collect();
+        assert_eq!(paths, [small.as_str(), "missing.rs"]);
+        assert_eq!(app.get_file_size(&app.search_results[0]), Some(13));
+        assert_eq!(app.get_file_size(&app.search_results[1]), None);
+    }
+
+    #[test]
+    fn test_search_elapsed() {
+        let mut progress = SearchProgress::new();
+        assert_eq!(progress.elapsed(), Duration::ZERO);
+
+        progress.start_search();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(progress.elapsed() >= Duration::from_millis(5));
+
+        // The time stops when the search completes
+        progress.complete_search();
+        let elapsed = progress.elapsed();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(progress.elapsed(), elapsed);
+
+        progress.reset();
+        assert_eq!(progress.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_highlighted_cache_eviction() {
+        let mut app = App::new_with_cache_size(2);
+        let mut highlighter = SyntaxHighlighter::new();
+        let results = mock_results("main");
+
+        for result in &results {
+            app.get_cached_highlighted_line(result, &mut highlighter);
+        }
+        // The least recently used line was evicted
+        assert_eq!(app.get_cache_stats(), (2, 2));
+        app.get_cached_highlighted_line(&results[2], &mut highlighter);
+        assert_eq!(app.cache_hit_rate(), 0.25);
+        app.get_cached_highlighted_line(&results[0], &mut highlighter);
+        assert_eq!(app.cache_hit_rate(), 0.2);
+
+        app.set_cache_size_limit(1);
+        assert_eq!(app.get_cache_stats(), (1, 1));
+        app.clear_highlighting_cache();
+        assert_eq!(app.get_cache_stats().0, 0);
+    }
+
+    #[test]
+    fn test_highlighted_cache_checks_mtime_on_a_timer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+        let result = mock_result(&path.to_string_lossy(), 1, "fn main() {}");
+        let app = App::new();
+        let mut highlighter = SyntaxHighlighter::new();
+
+        app.get_cached_highlighted_line(&result, &mut highlighter);
+        let touch = |secs| {
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap();
+        };
+        // A change right after the lookup is noticed once the check interval passed
+        touch(1_000_000);
+        app.get_cached_highlighted_line(&result, &mut highlighter);
+        assert_eq!(app.cache_hit_rate(), 0.5);
+
+        for (checked_at, _) in app.file_mtimes.borrow_mut().values_mut() {
+            *checked_at -= Duration::from_millis(MTIME_CHECK_INTERVAL_MS);
+        }
+        app.get_cached_highlighted_line(&result, &mut highlighter);
+        assert_eq!(app.cache_hit_rate(), 1.0 / 3.0);
+        app.get_cached_highlighted_line(&result, &mut highlighter);
+        assert_eq!(app.cache_hit_rate(), 0.5);
+    }
+}
@@ -0,0 +1,99 @@
+//! Git diff of the hunk around a result, shown in place of the preview.
+//!
+//! Compares the working tree with the index, so only unstaged changes are shown.
+
+use git2::{DiffOptions, Patch, Repository};
+use std::fs;
+use std::path::Path;
+
+/// Lines of unchanged context around each hunk
+const DIFF_CONTEXT_LINES: u32 = 3;
+
+/// Unified diff of the hunk that contains the line, `None` when the file is not in a
+/// git repository or the line was not changed
+pub fn diff_context(file_path: &Path, line_number: usize) -> Option<String> {
+    let path = fs::canonicalize(file_path).ok()?;
+    let repo = Repository::discover(path.parent()?).ok()?;
+    let workdir = fs::canonicalize(repo.workdir()?).ok()?;
+    let relative = path.strip_prefix(&workdir).ok()?;
+
+    let mut options = DiffOptions::new();
+    options.pathspec(relative).context_lines(DIFF_CONTEXT_LINES);
+    let diff = repo.diff_index_to_workdir(None, Some(&mut options)).ok()?;
+    let patch = Patch::from_diff(&diff, 0).ok()??;
+
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index).ok()?;
+        let start = hunk.new_start() as usize;
+        // A hunk that only removes lines sits just before its start line
+        let end = start + (hunk.new_lines() as usize).max(1);
+        if line_number < start || line_number >= end {
+            continue;
+        }
+
+        let relative = relative.display();
+        let mut text = format!("--- a/{}\n+++ b/{}\n", relative, relative);
+        text.push_str(&String::from_utf8_lossy(hunk.header()));
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index).ok()?;
+            let prefix = match line.origin() {
+                origin @ ('+' | '-' | ' ') => origin,
+                _ => continue,
+            };
+            text.push(prefix);
+            text.push_str(&String::from_utf8_lossy(line.content()));
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        return Some(text);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_diff_context() {
+        let dir = tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let file = dir.path().join("main.rs");
+        let original: Vec<String> = (1..=20).map(|n| format!("line {}", n)).collect();
+        fs::write(&file, original.join("\n") + "\n").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("main.rs")).unwrap();
+        index.write().unwrap();
+
+        // Nothing changed since the file was staged
+        assert_eq!(diff_context(&file, 10), None);
+
+        let mut changed = original.clone();
+        changed[9] = "changed line".to_string();
+        fs::write(&file, changed.join("\n") + "\n").unwrap();
+
+        let diff = diff_context(&file, 10).unwrap();
+        assert!(diff.starts_with("--- a/main.rs\n+++ b/main.rs\n@@ -7,7 +7,7 @@"));
+        assert!(diff.contains("\n-line 10\n+changed line\n"));
+        assert!(diff.contains("\n line 7\n"));
+
+        // Lines outside the hunk have no diff
+        assert_eq!(diff_context(&file, 2), None);
+        assert_eq!(diff_context(&file, 9), Some(diff));
+    }
+
+    #[test]
+    fn test_diff_context_outside_repository() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "text\n").unwrap();
+
+        // tempdir is not inside a repository unless TMPDIR points into one
+        if Repository::discover(dir.path()).is_err() {
+            assert_eq!(diff_context(&file, 1), None);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Directory tree of the search root shown beside the results list.
+//!
+//! Directories are read with `std::fs::read_dir` the first time they are expanded.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Entry of the tree, directories keep their children once read
+#[derive(Debug, Clone)]
+struct TreeNode {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    expanded: bool,
+    /// `None` until the directory is expanded for the first time
+    children: Option<Vec<TreeNode>>,
+}
+
+impl TreeNode {
+    fn new(path: PathBuf, is_dir: bool) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self {
+            path,
+            name,
+            is_dir,
+            expanded: false,
+            children: None,
+        }
+    }
+
+    /// Read the directory entries, directories first and hidden entries skipped
+    fn load_children(&mut self) {
+        if self.children.is_some() {
+            return;
+        }
+
+        let mut children: Vec<TreeNode> = fs::read_dir(&self.path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+                    .map(|entry| {
+                        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                        TreeNode::new(entry.path(), is_dir)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        self.children = Some(children);
+    }
+
+    /// Append this node and its expanded descendants to the rows
+    fn collect_rows(&self, depth: usize, rows: &mut Vec<TreeRow>) {
+        rows.push(TreeRow {
+            path: self.path.clone(),
+            name: self.name.clone(),
+            depth,
+            is_dir: self.is_dir,
+            expanded: self.expanded,
+        });
+        if self.expanded {
+            for child in self.children.iter().flatten() {
+                child.collect_rows(depth + 1, rows);
+            }
+        }
+    }
+
+    /// Find the node with the path among this node and its read descendants
+    fn find_mut(&mut self, path: &Path) -> Option<&mut TreeNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .flatten()
+            .filter(|child| path.starts_with(&child.path))
+            .find_map(|child| child.find_mut(path))
+    }
+}
+
+/// Visible row of the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRow {
+    pub path: PathBuf,
+    pub name: String,
+    /// Nesting level, 0 for the root
+    pub depth: usize,
+    pub is_dir: bool,
+    pub expanded: bool,
+}
+
+/// Expandable directory tree with a selected row
+#[derive(Debug, Clone)]
+pub struct DirectoryTree {
+    root: TreeNode,
+    /// Index of the selected row among the visible rows
+    pub selected: usize,
+}
+
+impl DirectoryTree {
+    /// Create the tree with the root directory expanded
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let mut root = TreeNode::new(root.into(), true);
+        root.expanded = true;
+        root.load_children();
+        Self { root, selected: 0 }
+    }
+
+    /// Path of the root directory
+    pub fn root(&self) -> &Path {
+        &self.root.path
+    }
+
+    /// Rows of the root and every entry of the expanded directories
+    pub fn rows(&self) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        self.root.collect_rows(0, &mut rows);
+        rows
+    }
+
+    /// The selected row
+    pub fn selected_row(&self) -> Option<TreeRow> {
+        self.rows().into_iter().nth(self.selected)
+    }
+
+    /// Select the row, ignoring indices past the last row
+    pub fn select(&mut self, index: usize) {
+        if index < self.rows().len() {
+            self.selected = index;
+        }
+    }
+
+    /// Move the selection down
+    pub fn select_next(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    /// Move the selection up
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Expand or collapse the directory at the row, reading it when first expanded
+    /// Returns false when the row is not a directory
+    pub fn toggle(&mut self, index: usize) -> bool {
+        let Some(row) = self.rows().into_iter().nth(index) else {
+            return false;
+        };
+        match self.root.find_mut(&row.path) {
+            Some(node) if node.is_dir => {
+                node.expanded = !node.expanded;
+                node.load_children();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// First visible row when the pane is `height` rows tall, keeping the selection on screen
+    pub fn scroll_offset(&self, height: usize) -> usize {
+        self.selected.saturating_sub(height.saturating_sub(1))
+    }
+}
+
+/// Path without `.` components, so `./src/main.rs` and `src/main.rs` compare equal
+pub fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn names(tree: &DirectoryTree) -> Vec<(usize, String)> {
+        tree.rows()
+            .into_iter()
+            .map(|row| (row.depth, row.name))
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_rows_and_toggle() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/tui")).unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+
+        let mut tree = DirectoryTree::new(dir.path());
+        let root_name = tree.rows()[0].name.clone();
+        // Directories come first, hidden entries are skipped
+        assert_eq!(
+            names(&tree),
+            [
+                (0, root_name.clone()),
+                (1, "src".to_string()),
+                (1, "Cargo.toml".to_string())
+            ]
+        );
+
+        // Subdirectories are read when expanded
+        assert!(tree.toggle(1));
+        assert_eq!(
+            names(&tree),
+            [
+                (0, root_name.clone()),
+                (1, "src".to_string()),
+                (2, "tui".to_string()),
+                (2, "main.rs".to_string()),
+                (1, "Cargo.toml".to_string())
+            ]
+        );
+
+        // Files can't be expanded
+        assert!(!tree.toggle(3));
+
+        assert!(tree.toggle(1));
+        assert_eq!(tree.rows().len(), 3);
+        assert!(!tree.rows()[1].expanded);
+    }
+
+    #[test]
+    fn test_tree_selection() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "").unwrap();
+        fs::write(dir.path().join("b.rs"), "").unwrap();
+
+        let mut tree = DirectoryTree::new(dir.path());
+        tree.select_previous();
+        assert_eq!(tree.selected, 0);
+        tree.select_next();
+        tree.select_next();
+        tree.select_next();
+        assert_eq!(tree.selected, 2);
+        assert_eq!(tree.selected_row().unwrap().name, "b.rs");
+
+        assert_eq!(tree.scroll_offset(10), 0);
+        assert_eq!(tree.scroll_offset(2), 1);
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("./src/main.rs")),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(normalize_path(Path::new(".")), PathBuf::new());
+        assert!(normalize_path(Path::new("./src/tui/app.rs"))
+            .starts_with(normalize_path(Path::new("src"))));
+    }
+}
@@ -1,22 +1,72 @@
 //! Event handling for keyboard and mouse input
 
+use crate::constants::MOUSE_SCROLL_LINES;
+use crate::tui::app::InputFocus;
+use crate::tui::keybindings::KeyBindings;
 use crate::{Result, SearchError};
 use crossterm::event::{
     self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
+use std::cell::Cell;
 use std::time::Duration;
 
+/// Keys that can't be rebound in the config file, shown in the help overlay as (action, key)
+/// The results list keys only act while the results list has focus, elsewhere they are typed
+pub const FIXED_KEYS: &[(&str, &str)] = &[
+    ("Move selection (results)", "j / k"),
+    ("First / last result (results)", "g g / G"),
+    ("Next / previous bookmark (results)", "] b / [ b"),
+    ("Next / previous file (results)", "] f / [ f"),
+    ("Copy file path (results)", "y"),
+    ("Copy result line (results)", "Y"),
+    ("Toggle bookmark (results)", "m"),
+    ("Annotate result (results)", "a"),
+    ("Export annotations (results)", "A"),
+    ("Mark result to open / export (results)", "space"),
+    ("Number results (results)", "#"),
+    ("File type icons (results)", "i"),
+    ("Aligned path / line / content columns (results)", "c"),
+    ("Toggle this help (results)", "?"),
+    ("Collapse / expand file (results)", "enter on a file header"),
+    ("Narrow results to directory (tree)", "enter / click"),
+    ("Leave the fullscreen preview", "esc"),
+    ("Undo pattern edits (search box)", "esc"),
+    ("Cancel running search", "esc"),
+];
+
+/// Rows of the help overlay as (action, keys), the configured keys first
+pub fn help_rows(keybindings: &KeyBindings) -> Vec<(String, String)> {
+    keybindings
+        .help()
+        .into_iter()
+        .map(|(action, keys)| (action.to_string(), keys))
+        .chain(
+            FIXED_KEYS
+                .iter()
+                .map(|(action, key)| (action.to_string(), key.to_string())),
+        )
+        .collect()
+}
+
 /// Event handler for TUI input
-pub struct EventHandler;
+pub struct EventHandler {
+    keybindings: KeyBindings,
+    /// First key of a two key sequence such as `]b` or `gg`
+    pending_prefix: Cell<Option<char>>,
+}
 
 impl EventHandler {
-    /// Create a new event handler
-    pub fn new() -> Result<Self> {
-        Ok(Self)
+    /// Create a new event handler with the keybindings
+    pub fn new(keybindings: KeyBindings) -> Result<Self> {
+        Ok(Self {
+            keybindings,
+            pending_prefix: Cell::new(None),
+        })
     }
 
     /// Poll for the next event with timeout
-    pub fn next_event(&self, timeout: Duration) -> Result<Option<Event>> {
+    /// Blocks, the event loop runs it on a blocking task
+    pub fn next_event(timeout: Duration) -> Result<Option<Event>> {
         if event::poll(timeout)
             .map_err(|e| SearchError::TuiError(format!("Event polling failed: {}", e)))?
         {
@@ -34,75 +84,154 @@ impl EventHandler {
             MouseEventKind::Down(MouseButton::Left) => {
                 MouseAction::ClickAt(event.column, event.row)
             }
+            MouseEventKind::ScrollUp => MouseAction::ScrollUp(MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollDown => MouseAction::ScrollDown(MOUSE_SCROLL_LINES),
             _ => MouseAction::None,
         }
     }
 
     /// Handle a key event and return the action to take
+    /// Characters typed without Ctrl or Alt are input unless bound to an action
     pub fn handle_key_event(&self, event: KeyEvent) -> KeyAction {
-        match event {
-            KeyEvent {
-                code: KeyCode::Esc, ..
-            } => KeyAction::Quit,
-            KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => KeyAction::Quit,
-            KeyEvent {
-                code: KeyCode::Up, ..
-            } => KeyAction::MovePrevious,
-            KeyEvent {
-                code: KeyCode::Down,
-                ..
-            } => KeyAction::MoveNext,
-            KeyEvent {
-                code: KeyCode::Enter,
-                ..
-            } => KeyAction::OpenFile,
-            KeyEvent {
-                code: KeyCode::Tab, ..
-            } => KeyAction::CycleFocus,
-            KeyEvent {
-                code: KeyCode::Char('r'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => KeyAction::RefreshSearch,
-            KeyEvent {
-                code: KeyCode::Char('/'),
-                ..
+        if let Some(action) = self.keybindings.action_for(&event) {
+            return action;
+        }
+        match event.code {
+            // Terminals report upper case letters with the shift modifier
+            KeyCode::Char(c) if (event.modifiers - KeyModifiers::SHIFT).is_empty() => {
+                KeyAction::InputChar(c)
             }
-            | KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => KeyAction::FocusSearch,
-            KeyEvent {
-                code: KeyCode::Char(c),
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => KeyAction::InputChar(c),
-            KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => KeyAction::DeleteChar,
             _ => KeyAction::None,
         }
     }
+
+    /// Handle a key event, recalling search history with Up/Down and moving the cursor with
+    /// Home/End while the search box is focused, and mapping Vim and bookmark keys while the
+    /// results list is focused
+    pub fn handle_focused_key(&self, event: KeyEvent, focus: InputFocus) -> KeyAction {
+        let prefix = self.pending_prefix.take();
+        match (self.handle_key_event(event), focus) {
+            (KeyAction::MovePrevious, InputFocus::Primary) => KeyAction::HistoryPrevious,
+            (KeyAction::MoveNext, InputFocus::Primary) => KeyAction::HistoryNext,
+            (KeyAction::SelectFirst, InputFocus::Primary) => KeyAction::CursorHome,
+            (KeyAction::SelectLast, InputFocus::Primary) => KeyAction::CursorEnd,
+            (KeyAction::InputChar(c), InputFocus::Results) => self.handle_results_char(c, prefix),
+            (action, _) => action,
+        }
+    }
+
+    /// Handle a key event in the app, Escape cancels a running search instead of quitting
+    /// and the copy and cut keys act on the text selected in the search box
+    pub fn handle_app_key(
+        &self,
+        event: KeyEvent,
+        focus: InputFocus,
+        is_searching: bool,
+        has_selection: bool,
+    ) -> KeyAction {
+        if has_selection && focus == InputFocus::Primary {
+            if let Some(action) = self.keybindings.selection_action_for(&event) {
+                return action;
+            }
+        }
+        match self.handle_focused_key(event, focus) {
+            KeyAction::Quit if is_searching && event.code == KeyCode::Esc => {
+                KeyAction::CancelSearch
+            }
+            action => action,
+        }
+    }
+
+    /// Map a character typed in the results list, `prefix` is the previous key of a sequence
+    fn handle_results_char(&self, c: char, prefix: Option<char>) -> KeyAction {
+        match (prefix, c) {
+            (Some(']'), 'b') => KeyAction::JumpToNextBookmark,
+            (Some('['), 'b') => KeyAction::JumpToPrevBookmark,
+            (Some(']'), 'f') => KeyAction::JumpToNextFile,
+            (Some('['), 'f') => KeyAction::JumpToPrevFile,
+            (Some('g'), 'g') => KeyAction::SelectFirst,
+            (_, 'G') => KeyAction::SelectLast,
+            (_, '[' | ']' | 'g') => {
+                self.pending_prefix.set(Some(c));
+                KeyAction::None
+            }
+            (_, 'm') => KeyAction::ToggleBookmark,
+            (_, 'a') => KeyAction::AddAnnotation,
+            (_, 'A') => KeyAction::ExportAnnotations,
+            (_, ' ') => KeyAction::ToggleSelect,
+            (_, 'y') => KeyAction::YankPath,
+            (_, 'Y') => KeyAction::YankLine,
+            (_, '#') => KeyAction::ToggleResultNumbers,
+            (_, 'i') => KeyAction::ToggleIcons,
+            (_, 'c') => KeyAction::ToggleColumnLayout,
+            (_, '?') => KeyAction::ToggleHelp,
+            (_, 'j') => KeyAction::VimDown,
+            (_, 'k') => KeyAction::VimUp,
+            _ => KeyAction::InputChar(c),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum KeyAction {
     Quit,
+    CancelSearch,
     MovePrevious,
     MoveNext,
     OpenFile,
     CycleFocus,
     RefreshSearch,
+    RevealInFileManager,
     FocusSearch,
+    FocusFilter,
+    FocusPathFilter,
     InputChar(char),
     DeleteChar,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    ExtendSelectionLeft,
+    ExtendSelectionRight,
+    CopySelection,
+    CutSelection,
+    Paste,
+    HistoryPrevious,
+    HistoryNext,
+    ToggleBookmark,
+    ToggleSelect,
+    SelectAll,
+    ClearSelection,
+    JumpToNextBookmark,
+    JumpToPrevBookmark,
+    JumpToNextFile,
+    JumpToPrevFile,
+    VimDown,
+    VimUp,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    PreviewScrollUp,
+    PreviewScrollDown,
+    SelectFirst,
+    SelectLast,
+    ToggleHelp,
+    CyclePreviewMode,
+    ToggleWordWrap,
+    ToggleResultNumbers,
+    ToggleIcons,
+    ToggleColumnLayout,
+    ToggleDiffView,
+    TogglePreviewPosition,
+    ToggleGrouped,
+    ToggleDirectoryTree,
+    ToggleReverseSort,
+    ExportResults,
+    AddAnnotation,
+    ExportAnnotations,
+    YankPath,
+    YankLine,
     None,
 }
 
@@ -110,6 +239,8 @@ pub enum KeyAction {
 pub enum MouseAction {
     None,
     ClickAt(u16, u16),
+    ScrollUp(usize),
+    ScrollDown(usize),
 }
 
 #[cfg(test)]
@@ -117,7 +248,7 @@ mod tests {
     use super::*;
 
     fn test_handler() -> EventHandler {
-        EventHandler::new().unwrap()
+        EventHandler::new(KeyBindings::default()).unwrap()
     }
 
     fn assert_key_action(key_code: KeyCode, modifiers: KeyModifiers, expected: KeyAction) {
@@ -129,8 +260,8 @@ mod tests {
     fn create_mouse_event(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
         MouseEvent {
             kind,
-            column: column,
-            row: row,
+            column,
+            row,
             modifiers: KeyModifiers::NONE,
         }
     }
@@ -143,12 +274,81 @@ mod tests {
             (KeyCode::Up, KeyModifiers::NONE, KeyAction::MovePrevious),
             (KeyCode::Down, KeyModifiers::NONE, KeyAction::MoveNext),
             (KeyCode::Enter, KeyModifiers::NONE, KeyAction::OpenFile),
+            (KeyCode::Home, KeyModifiers::NONE, KeyAction::SelectFirst),
+            (KeyCode::End, KeyModifiers::NONE, KeyAction::SelectLast),
+            (KeyCode::Left, KeyModifiers::NONE, KeyAction::CursorLeft),
+            (KeyCode::Right, KeyModifiers::NONE, KeyAction::CursorRight),
+            (
+                KeyCode::Left,
+                KeyModifiers::SHIFT,
+                KeyAction::ExtendSelectionLeft,
+            ),
+            (
+                KeyCode::Right,
+                KeyModifiers::SHIFT,
+                KeyAction::ExtendSelectionRight,
+            ),
+            (KeyCode::Char('v'), KeyModifiers::CONTROL, KeyAction::Paste),
+            (
+                KeyCode::Char('G'),
+                KeyModifiers::SHIFT,
+                KeyAction::InputChar('G'),
+            ),
+            (KeyCode::PageUp, KeyModifiers::NONE, KeyAction::PageUp),
+            (KeyCode::PageDown, KeyModifiers::NONE, KeyAction::PageDown),
+            (
+                KeyCode::Char('u'),
+                KeyModifiers::CONTROL,
+                KeyAction::HalfPageUp,
+            ),
+            (
+                KeyCode::Char('d'),
+                KeyModifiers::CONTROL,
+                KeyAction::HalfPageDown,
+            ),
+            (
+                KeyCode::Char('k'),
+                KeyModifiers::CONTROL,
+                KeyAction::PreviewScrollUp,
+            ),
+            (
+                KeyCode::Char('j'),
+                KeyModifiers::CONTROL,
+                KeyAction::PreviewScrollDown,
+            ),
+            (KeyCode::Up, KeyModifiers::SHIFT, KeyAction::PreviewScrollUp),
+            (
+                KeyCode::Down,
+                KeyModifiers::SHIFT,
+                KeyAction::PreviewScrollDown,
+            ),
             (KeyCode::Tab, KeyModifiers::NONE, KeyAction::CycleFocus),
+            (
+                KeyCode::F(2),
+                KeyModifiers::NONE,
+                KeyAction::CyclePreviewMode,
+            ),
+            (
+                KeyCode::Char('w'),
+                KeyModifiers::CONTROL,
+                KeyAction::ToggleWordWrap,
+            ),
+            (KeyCode::F(3), KeyModifiers::NONE, KeyAction::ToggleDiffView),
+            (
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+                KeyAction::TogglePreviewPosition,
+            ),
             (
                 KeyCode::Char('r'),
                 KeyModifiers::CONTROL,
                 KeyAction::RefreshSearch,
             ),
+            (
+                KeyCode::Char('R'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                KeyAction::RevealInFileManager,
+            ),
             (
                 KeyCode::Char('/'),
                 KeyModifiers::NONE,
@@ -159,6 +359,46 @@ mod tests {
                 KeyModifiers::CONTROL,
                 KeyAction::FocusSearch,
             ),
+            (
+                KeyCode::Char('l'),
+                KeyModifiers::CONTROL,
+                KeyAction::FocusFilter,
+            ),
+            (
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL,
+                KeyAction::FocusPathFilter,
+            ),
+            (
+                KeyCode::Char('g'),
+                KeyModifiers::CONTROL,
+                KeyAction::ToggleGrouped,
+            ),
+            (
+                KeyCode::Char('t'),
+                KeyModifiers::CONTROL,
+                KeyAction::ToggleDirectoryTree,
+            ),
+            (
+                KeyCode::Char('o'),
+                KeyModifiers::CONTROL,
+                KeyAction::ToggleReverseSort,
+            ),
+            (
+                KeyCode::Char('s'),
+                KeyModifiers::CONTROL,
+                KeyAction::ExportResults,
+            ),
+            (
+                KeyCode::Char('a'),
+                KeyModifiers::CONTROL,
+                KeyAction::SelectAll,
+            ),
+            (
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL,
+                KeyAction::ClearSelection,
+            ),
             (
                 KeyCode::Char('a'),
                 KeyModifiers::NONE,
@@ -179,7 +419,8 @@ mod tests {
                 KeyModifiers::NONE,
                 KeyAction::InputChar('k'),
             ),
-            (KeyCode::F(1), KeyModifiers::NONE, KeyAction::None),
+            (KeyCode::F(1), KeyModifiers::NONE, KeyAction::ToggleHelp),
+            (KeyCode::F(4), KeyModifiers::NONE, KeyAction::None),
             (KeyCode::Char('a'), KeyModifiers::ALT, KeyAction::None),
         ];
 
@@ -197,6 +438,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_history_keys_follow_focus() {
+        let handler = test_handler();
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let home = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
+        let end = KeyEvent::new(KeyCode::End, KeyModifiers::NONE);
+
+        let cases = [
+            (up, InputFocus::Primary, KeyAction::HistoryPrevious),
+            (down, InputFocus::Primary, KeyAction::HistoryNext),
+            (up, InputFocus::Results, KeyAction::MovePrevious),
+            (down, InputFocus::Results, KeyAction::MoveNext),
+            (enter, InputFocus::Primary, KeyAction::OpenFile),
+            (up, InputFocus::Filter, KeyAction::MovePrevious),
+            (home, InputFocus::Primary, KeyAction::CursorHome),
+            (end, InputFocus::Primary, KeyAction::CursorEnd),
+            (home, InputFocus::Results, KeyAction::SelectFirst),
+            (end, InputFocus::Results, KeyAction::SelectLast),
+        ];
+
+        for (event, focus, expected) in cases {
+            assert_eq!(handler.handle_focused_key(event, focus), expected);
+        }
+    }
+
+    #[test]
+    fn test_vim_keys_follow_focus() {
+        let handler = test_handler();
+        let key = |c: char| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let cases = [
+            ('j', InputFocus::Results, KeyAction::VimDown),
+            ('k', InputFocus::Results, KeyAction::VimUp),
+            ('j', InputFocus::Primary, KeyAction::InputChar('j')),
+            ('k', InputFocus::Primary, KeyAction::InputChar('k')),
+            ('j', InputFocus::Filter, KeyAction::InputChar('j')),
+        ];
+        for (c, focus, expected) in cases {
+            assert_eq!(handler.handle_focused_key(key(c), focus), expected);
+        }
+
+        // Arrow keys still move the selection in the results list
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(
+            handler.handle_focused_key(down, InputFocus::Results),
+            KeyAction::MoveNext
+        );
+        assert_eq!(
+            handler.handle_focused_key(up, InputFocus::Results),
+            KeyAction::MovePrevious
+        );
+    }
+
+    #[test]
+    fn test_bookmark_keys() {
+        let handler = test_handler();
+        let key = |c: char| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let results = |c: char| handler.handle_focused_key(key(c), InputFocus::Results);
+
+        assert_eq!(results('m'), KeyAction::ToggleBookmark);
+        assert_eq!(results('a'), KeyAction::AddAnnotation);
+        assert_eq!(results('A'), KeyAction::ExportAnnotations);
+        assert_eq!(results(' '), KeyAction::ToggleSelect);
+        assert_eq!(results('?'), KeyAction::ToggleHelp);
+        assert_eq!(results('y'), KeyAction::YankPath);
+        assert_eq!(results('Y'), KeyAction::YankLine);
+        assert_eq!(results('#'), KeyAction::ToggleResultNumbers);
+        assert_eq!(results('i'), KeyAction::ToggleIcons);
+        assert_eq!(results('c'), KeyAction::ToggleColumnLayout);
+        assert_eq!(results(']'), KeyAction::None);
+        assert_eq!(results('b'), KeyAction::JumpToNextBookmark);
+        assert_eq!(results('['), KeyAction::None);
+        assert_eq!(results('b'), KeyAction::JumpToPrevBookmark);
+        assert_eq!(results(']'), KeyAction::None);
+        assert_eq!(results('f'), KeyAction::JumpToNextFile);
+        assert_eq!(results('['), KeyAction::None);
+        assert_eq!(results('f'), KeyAction::JumpToPrevFile);
+
+        // Without a prefix, or after another key, 'b' is plain input
+        assert_eq!(results('b'), KeyAction::InputChar('b'));
+        assert_eq!(results(']'), KeyAction::None);
+        assert_eq!(results('x'), KeyAction::InputChar('x'));
+        assert_eq!(results('b'), KeyAction::InputChar('b'));
+
+        // gg and G jump to the first and last result
+        assert_eq!(results('g'), KeyAction::None);
+        assert_eq!(results('g'), KeyAction::SelectFirst);
+        assert_eq!(results('G'), KeyAction::SelectLast);
+        assert_eq!(results('g'), KeyAction::None);
+        assert_eq!(results(']'), KeyAction::None);
+        assert_eq!(results('b'), KeyAction::JumpToNextBookmark);
+
+        // Typing in the search box is unaffected
+        let primary = handler.handle_focused_key(key('m'), InputFocus::Primary);
+        assert_eq!(primary, KeyAction::InputChar('m'));
+    }
+
     #[test]
     fn test_key_action_debug_trait() {
         let cases = [
@@ -206,8 +547,30 @@ mod tests {
             (KeyAction::OpenFile, "OpenFile"),
             (KeyAction::CycleFocus, "CycleFocus"),
             (KeyAction::RefreshSearch, "RefreshSearch"),
+            (KeyAction::RevealInFileManager, "RevealInFileManager"),
+            (KeyAction::TogglePreviewPosition, "TogglePreviewPosition"),
             (KeyAction::FocusSearch, "FocusSearch"),
+            (KeyAction::FocusFilter, "FocusFilter"),
+            (KeyAction::FocusPathFilter, "FocusPathFilter"),
             (KeyAction::DeleteChar, "DeleteChar"),
+            (KeyAction::CursorLeft, "CursorLeft"),
+            (KeyAction::CursorHome, "CursorHome"),
+            (KeyAction::ExtendSelectionRight, "ExtendSelectionRight"),
+            (KeyAction::CopySelection, "CopySelection"),
+            (KeyAction::HistoryPrevious, "HistoryPrevious"),
+            (KeyAction::HistoryNext, "HistoryNext"),
+            (KeyAction::ToggleBookmark, "ToggleBookmark"),
+            (KeyAction::VimDown, "VimDown"),
+            (KeyAction::VimUp, "VimUp"),
+            (KeyAction::PageDown, "PageDown"),
+            (KeyAction::SelectLast, "SelectLast"),
+            (KeyAction::ToggleHelp, "ToggleHelp"),
+            (KeyAction::ToggleGrouped, "ToggleGrouped"),
+            (KeyAction::ToggleReverseSort, "ToggleReverseSort"),
+            (KeyAction::ExportResults, "ExportResults"),
+            (KeyAction::AddAnnotation, "AddAnnotation"),
+            (KeyAction::ExportAnnotations, "ExportAnnotations"),
+            (KeyAction::YankPath, "YankPath"),
             (KeyAction::None, "None"),
         ];
 
@@ -218,6 +581,92 @@ mod tests {
         assert_eq!(format!("{:?}", KeyAction::InputChar('a')), "InputChar('a')");
     }
 
+    #[test]
+    fn test_escape_cancels_running_search() {
+        let handler = test_handler();
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+        assert_eq!(
+            handler.handle_app_key(esc, InputFocus::Results, true, false),
+            KeyAction::CancelSearch
+        );
+        assert_eq!(
+            handler.handle_app_key(esc, InputFocus::Primary, false, false),
+            KeyAction::Quit
+        );
+
+        // Ctrl+C quits unless text in the search box is selected
+        assert_eq!(
+            handler.handle_app_key(ctrl_c, InputFocus::Primary, true, false),
+            KeyAction::Quit
+        );
+    }
+
+    #[test]
+    fn test_selection_keys() {
+        let handler = test_handler();
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        let cases = [
+            ('c', InputFocus::Primary, true, KeyAction::CopySelection),
+            ('x', InputFocus::Primary, true, KeyAction::CutSelection),
+            ('v', InputFocus::Primary, true, KeyAction::Paste),
+            ('c', InputFocus::Primary, false, KeyAction::Quit),
+            ('x', InputFocus::Primary, false, KeyAction::ClearSelection),
+            ('c', InputFocus::Results, true, KeyAction::Quit),
+        ];
+        for (c, focus, has_selection, expected) in cases {
+            let action = handler.handle_app_key(ctrl(c), focus, false, has_selection);
+            assert_eq!(action, expected, "ctrl+{}", c);
+        }
+    }
+
+    #[test]
+    fn test_help_rows_follow_keybindings() {
+        let keybindings = KeyBindings {
+            quit: vec!["ctrl+q".parse().unwrap(), "F10".parse().unwrap()],
+            ..KeyBindings::default()
+        };
+        let rows = help_rows(&keybindings);
+        let keys_for = |action: &str| {
+            rows.iter()
+                .find(|(name, _)| name == action)
+                .map(|(_, keys)| keys.as_str())
+        };
+
+        assert_eq!(keys_for("Quit"), Some("ctrl+q / F10"));
+        assert_eq!(keys_for("Page up"), Some("pageup"));
+        assert_eq!(keys_for("Toggle bookmark (results)"), Some("m"));
+        assert_eq!(rows.len(), keybindings.help().len() + FIXED_KEYS.len());
+    }
+
+    #[test]
+    fn test_custom_keybindings() {
+        let keybindings = KeyBindings {
+            quit: vec!["ctrl+q".parse().unwrap()],
+            toggle_help: vec!["?".parse().unwrap()],
+            ..KeyBindings::default()
+        };
+        let handler = EventHandler::new(keybindings).unwrap();
+        let key = |code, modifiers| handler.handle_key_event(KeyEvent::new(code, modifiers));
+
+        assert_eq!(
+            key(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            KeyAction::Quit
+        );
+        assert_eq!(key(KeyCode::Esc, KeyModifiers::NONE), KeyAction::None);
+        // Bound characters are no longer typed
+        assert_eq!(
+            key(KeyCode::Char('?'), KeyModifiers::SHIFT),
+            KeyAction::ToggleHelp
+        );
+        assert_eq!(
+            key(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            KeyAction::RefreshSearch
+        );
+    }
+
     #[test]
     fn test_key_action_partial_eq_trait() {
         // Test InputChar equality
@@ -245,6 +694,18 @@ mod tests {
             MouseAction::ClickAt(1, 2)
         );
 
+        // Scroll wheel moves a few rows per tick
+        let event = create_mouse_event(MouseEventKind::ScrollUp, 1, 2);
+        assert_eq!(
+            handler.handle_mouse_event(event),
+            MouseAction::ScrollUp(MOUSE_SCROLL_LINES)
+        );
+        let event = create_mouse_event(MouseEventKind::ScrollDown, 1, 2);
+        assert_eq!(
+            handler.handle_mouse_event(event),
+            MouseAction::ScrollDown(MOUSE_SCROLL_LINES)
+        );
+
         // Negative cases: other events and buttons
         let negative_kinds = [
             MouseEventKind::Down(MouseButton::Right),
@@ -252,8 +713,8 @@ mod tests {
             MouseEventKind::Up(MouseButton::Left),
             MouseEventKind::Drag(MouseButton::Left),
             MouseEventKind::Moved,
-            MouseEventKind::ScrollUp,
-            MouseEventKind::ScrollDown,
+            MouseEventKind::ScrollLeft,
+            MouseEventKind::ScrollRight,
         ];
 
         for kind in negative_kinds {
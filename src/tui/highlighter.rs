@@ -2,45 +2,96 @@
 //!
 //! Uses syntect to provide fast post-processing syntax highlighting
 
+use crate::{Result, SearchError};
 use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::{Line, Span, Text};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::OnceLock;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Style as SyntectStyle, Theme, ThemeSet};
 use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
+use syntect::LoadingError;
 
 // Only load from single thread once
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
 static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
 
+/// Theme used when none is configured
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
 /// Fast syntax highlighting using syntect with caching optimization
 pub struct SyntaxHighlighter {
     /// Cache of file extension to syntax for performance
     syntax_cache: HashMap<String, &'static SyntaxReference>,
-    /// Pre-loaded syntect theme for performance
-    theme: &'static Theme,
+    /// Pre-loaded syntect theme for performance, owned when loaded from a file
+    theme: Cow<'static, Theme>,
 }
 
 impl SyntaxHighlighter {
     /// Create a new syntax highlighter with optimized global state
     pub fn new() -> Self {
-        // Load theme set once
-        let theme_set = THEME_SET.get_or_init(|| ThemeSet::load_defaults());
-
-        // TODO: Add support for user-defined themes
-        let theme = &theme_set.themes["base16-ocean.dark"];
-
         Self {
             syntax_cache: HashMap::new(),
-            theme,
+            theme: Cow::Borrowed(&Self::get_theme_set().themes[DEFAULT_THEME]),
         }
     }
 
+    /// Create a syntax highlighter using one of the bundled themes
+    pub fn with_theme(theme_name: &str) -> Result<Self> {
+        let theme = Self::get_theme_set()
+            .themes
+            .get(theme_name)
+            .ok_or_else(|| {
+                SearchError::InvalidArguments(format!(
+                    "Unknown theme '{}', available themes: {}",
+                    theme_name,
+                    Self::list_themes().join(", ")
+                ))
+            })?;
+
+        Ok(Self {
+            syntax_cache: HashMap::new(),
+            theme: Cow::Borrowed(theme),
+        })
+    }
+
+    /// Create a syntax highlighter using a `.tmTheme` file
+    pub fn load_theme_from_file(path: &Path) -> Result<Self> {
+        let display = path.display().to_string();
+        if !path.is_file() {
+            return Err(SearchError::file_access_error(
+                &display,
+                "theme file not found",
+            ));
+        }
+
+        let theme = ThemeSet::get_theme(path).map_err(|e| match e {
+            LoadingError::Io(e) => SearchError::file_access_error(&display, &e.to_string()),
+            e => SearchError::InvalidInput(format!("Invalid theme file {}: {}", display, e)),
+        })?;
+
+        Ok(Self {
+            syntax_cache: HashMap::new(),
+            theme: Cow::Owned(theme),
+        })
+    }
+
+    /// Names of the bundled themes, sorted
+    pub fn list_themes() -> Vec<String> {
+        Self::get_theme_set().themes.keys().cloned().collect()
+    }
+
+    /// Get the global theme set
+    fn get_theme_set() -> &'static ThemeSet {
+        THEME_SET.get_or_init(ThemeSet::load_defaults)
+    }
+
     /// Get the global syntax set
     fn get_syntax_set() -> &'static SyntaxSet {
-        SYNTAX_SET.get_or_init(|| SyntaxSet::load_defaults_newlines())
+        SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
     }
 
     /// Get cached syntax reference for a given file extension
@@ -61,6 +112,20 @@ impl SyntaxHighlighter {
         }
     }
 
+    /// Name of the language for a file extension, e.g. "Rust" for `rs`
+    pub fn language_name(extension: &str) -> Option<&'static str> {
+        Self::get_syntax_set()
+            .find_syntax_by_extension(extension)
+            .map(|syntax| syntax.name.as_str())
+    }
+
+    /// File extension of the language named by a code block tag, e.g. `rs` for `rust`
+    pub fn extension_for_token(token: &str) -> Option<String> {
+        Self::get_syntax_set()
+            .find_syntax_by_token(token)
+            .and_then(|syntax| syntax.file_extensions.first().cloned())
+    }
+
     /// Highlight plain text with syntax colors for file preview
     pub fn highlight_text(&mut self, content: &str, extension: Option<&str>) -> Text<'static> {
         let extension = match extension {
@@ -98,14 +163,29 @@ impl SyntaxHighlighter {
     }
 
     /// Apply syntax highlighting and highlight the target line with background color
-    fn highlight_preview_with_target_line(
+    /// The `  42>| ` line number gutter of the preview is kept out of the syntax highlighting
+    pub fn highlight_preview_with_target_line(
         &mut self,
         content: &str,
         extension: Option<&str>,
         target_line: Option<usize>,
     ) -> Text<'static> {
-        // First apply syntax highlighting to get the base highlighted text
-        let mut highlighted_text = self.highlight_text(content, extension);
+        // First apply syntax highlighting to the code only to get the base highlighted text
+        let (gutters, code): (Vec<&str>, Vec<&str>) =
+            content.lines().map(Self::split_preview_gutter).unzip();
+        let mut highlighted_text = self.highlight_text(&code.join("\n"), extension);
+
+        // Trailing empty lines produce no highlighted line
+        while highlighted_text.lines.len() < gutters.len() {
+            highlighted_text.lines.push(Line::default());
+        }
+        for (line, gutter) in highlighted_text.lines.iter_mut().zip(gutters) {
+            if !gutter.is_empty() {
+                let gutter_style = Style::default().fg(Color::DarkGray);
+                line.spans
+                    .insert(0, Span::styled(gutter.to_string(), gutter_style));
+            }
+        }
 
         // If we have a target line to highlight, apply the background color to it
         if let Some(target_line_num) = target_line {
@@ -134,6 +214,21 @@ impl SyntaxHighlighter {
         highlighted_text
     }
     
+    /// Split a preview line into its line number gutter and code
+    /// Lines without a gutter are returned as code only
+    fn split_preview_gutter(line: &str) -> (&str, &str) {
+        let idx = match line.find("| ") {
+            Some(idx) => idx,
+            None => return ("", line),
+        };
+        let number = line[..idx].trim().trim_end_matches('>');
+        if !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit()) {
+            line.split_at(idx + 2)
+        } else {
+            ("", line)
+        }
+    }
+
     /// Check if a line contains a given line number
     fn line_contains_line_number(line: &Line, target_str: &str) -> bool {
         // Early exit if line is empty
@@ -223,7 +318,13 @@ impl SyntaxHighlighter {
 
     /// Extract file extension from path
     pub fn get_extension(path: &str) -> Option<&str> {
-        path.split('.').last()
+        path.split('.').next_back()
+    }
+}
+
+impl Default for SyntaxHighlighter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -356,12 +457,132 @@ mod tests {
         assert_eq!(SyntaxHighlighter::get_extension(".file"), Some("file"));
     }
 
+    #[test]
+    fn test_highlight_preview_with_target_line() {
+        let mut highlighter = SyntaxHighlighter::new();
+        let preview = "   1 | fn main() {\n   2>|     run();\n   3 | }\n";
+
+        let text = highlighter.highlight_preview_with_target_line(preview, Some("rs"), Some(2));
+        assert_eq!(text.lines.len(), 3);
+
+        // Gutter is kept as its own dim span
+        assert_eq!(text.lines[0].spans[0].content, "   1 | ");
+        assert_eq!(text.lines[0].spans[0].style.fg, Some(Color::DarkGray));
+
+        // Only the target line gets a background
+        let backgrounds = |line: &Line| line.spans.iter().map(|span| span.style.bg).collect();
+        let target_bg = Some(Color::Rgb(64, 64, 64));
+        let line_backgrounds: Vec<Vec<Option<Color>>> =
+            text.lines.iter().map(backgrounds).collect();
+        assert!(line_backgrounds[1].iter().all(|bg| *bg == target_bg));
+        assert!(line_backgrounds[0].iter().all(|bg| bg.is_none()));
+        assert!(line_backgrounds[2].iter().all(|bg| bg.is_none()));
+
+        // Code content is preserved
+        let code: String = text.lines[1]
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert!(code.starts_with("   2>|     run();"));
+    }
+
+    #[test]
+    fn test_split_preview_gutter() {
+        assert_eq!(
+            SyntaxHighlighter::split_preview_gutter("  42>| let a = b | c;"),
+            ("  42>| ", "let a = b | c;")
+        );
+        assert_eq!(
+            SyntaxHighlighter::split_preview_gutter("   7 | "),
+            ("   7 | ", "")
+        );
+        assert_eq!(
+            SyntaxHighlighter::split_preview_gutter("a | b"),
+            ("", "a | b")
+        );
+        assert_eq!(SyntaxHighlighter::split_preview_gutter("| b"), ("", "| b"));
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(SyntaxHighlighter::language_name("rs"), Some("Rust"));
+        assert_eq!(SyntaxHighlighter::language_name("py"), Some("Python"));
+        assert_eq!(SyntaxHighlighter::language_name("no-such-ext"), None);
+    }
+
     #[test]
     fn test_theme_consistency() {
         let highlighter1 = SyntaxHighlighter::new();
         let highlighter2 = SyntaxHighlighter::new();
 
         // Both highlighters should have same theme
-        assert!(std::ptr::eq(highlighter1.theme, highlighter2.theme));
+        assert!(std::ptr::eq(&*highlighter1.theme, &*highlighter2.theme));
+    }
+
+    #[test]
+    fn test_with_theme() {
+        let themes = SyntaxHighlighter::list_themes();
+        assert!(themes.contains(&DEFAULT_THEME.to_string()));
+        assert!(themes.contains(&"InspiredGitHub".to_string()));
+
+        let default = SyntaxHighlighter::new();
+        let highlighter = SyntaxHighlighter::with_theme("InspiredGitHub").unwrap();
+        assert!(!std::ptr::eq(&*highlighter.theme, &*default.theme));
+
+        let result = SyntaxHighlighter::with_theme("no-such-theme");
+        let err = result.err().unwrap();
+        assert!(matches!(err, SearchError::InvalidArguments(_)));
+        assert!(err.to_string().contains("no-such-theme"));
+    }
+
+    #[test]
+    fn test_load_theme_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minimal.tmTheme");
+        std::fs::write(
+            &path,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key>
+    <string>Minimal</string>
+    <key>settings</key>
+    <array>
+        <dict>
+            <key>settings</key>
+            <dict>
+                <key>background</key>
+                <string>#000000</string>
+                <key>foreground</key>
+                <string>#FFFFFF</string>
+            </dict>
+        </dict>
+    </array>
+</dict>
+</plist>
+"#,
+        )
+        .unwrap();
+
+        let mut highlighter = SyntaxHighlighter::load_theme_from_file(&path).unwrap();
+        assert_eq!(highlighter.theme.name.as_deref(), Some("Minimal"));
+        let text = highlighter.highlight_text("fn main() {}", Some("rs"));
+        assert!(!text.lines.is_empty());
+
+        let missing = SyntaxHighlighter::load_theme_from_file(&dir.path().join("missing.tmTheme"));
+        assert!(matches!(
+            missing.err().unwrap(),
+            SearchError::FileAccessError { .. }
+        ));
+
+        let invalid = dir.path().join("invalid.tmTheme");
+        std::fs::write(&invalid, "not a theme").unwrap();
+        let result = SyntaxHighlighter::load_theme_from_file(&invalid);
+        assert!(matches!(
+            result.err().unwrap(),
+            SearchError::InvalidInput(_)
+        ));
     }
 }
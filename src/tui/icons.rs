@@ -0,0 +1,118 @@
+//! File type icons for the results list.
+//!
+//! Icons are Nerd Font glyphs, so they only render with a patched font. Files of an
+//! unknown type get a generic document icon.
+
+use std::path::Path;
+
+/// Icon shown for files of an unknown type
+const GENERIC_ICON: &str = "📄";
+
+/// Kind of file an icon is shown for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTypeIcon {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Html,
+    Css,
+    Json,
+    Markdown,
+    Toml,
+    Yaml,
+    Shell,
+    Go,
+    C,
+    Cpp,
+    Java,
+    Image,
+    Archive,
+    Binary,
+    Generic,
+}
+
+impl FileTypeIcon {
+    /// Kind of file for an extension, without the leading dot
+    pub fn from_extension(ext: &str) -> Self {
+        match ext.to_ascii_lowercase().as_str() {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "mjs" | "cjs" | "jsx" => Self::JavaScript,
+            "ts" | "tsx" => Self::TypeScript,
+            "html" | "htm" => Self::Html,
+            "css" | "scss" | "sass" | "less" => Self::Css,
+            "json" | "jsonc" => Self::Json,
+            "md" | "markdown" => Self::Markdown,
+            "toml" => Self::Toml,
+            "yml" | "yaml" => Self::Yaml,
+            "sh" | "bash" | "zsh" | "fish" => Self::Shell,
+            "go" => Self::Go,
+            "c" | "h" => Self::C,
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => Self::Cpp,
+            "java" => Self::Java,
+            "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "ico" | "bmp" => Self::Image,
+            "zip" | "tar" | "gz" | "xz" | "bz2" | "7z" => Self::Archive,
+            "exe" | "dll" | "so" | "dylib" | "o" | "a" | "bin" | "wasm" => Self::Binary,
+            _ => Self::Generic,
+        }
+    }
+
+    /// Icon for an extension, without the leading dot
+    pub fn for_extension(ext: &str) -> &'static str {
+        Self::from_extension(ext).glyph()
+    }
+
+    /// Icon for the extension of a file path
+    pub fn for_path(path: &str) -> &'static str {
+        Path::new(path).extension().map_or(GENERIC_ICON, |ext| {
+            Self::for_extension(&ext.to_string_lossy())
+        })
+    }
+
+    /// Nerd Font glyph of the kind of file
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Self::Rust => "\u{e7a8}",
+            Self::Python => "\u{e606}",
+            Self::JavaScript => "\u{e74e}",
+            Self::TypeScript => "\u{e628}",
+            Self::Html => "\u{e736}",
+            Self::Css => "\u{e749}",
+            Self::Json => "\u{e60b}",
+            Self::Markdown => "\u{e73e}",
+            Self::Toml => "\u{e6b2}",
+            Self::Yaml => "\u{e6a8}",
+            Self::Shell => "\u{e795}",
+            Self::Go => "\u{e627}",
+            Self::C => "\u{e61e}",
+            Self::Cpp => "\u{e61d}",
+            Self::Java => "\u{e738}",
+            Self::Image => "\u{f1c5}",
+            Self::Archive => "\u{f410}",
+            Self::Binary => "\u{f471}",
+            Self::Generic => GENERIC_ICON,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_type_icons() {
+        assert_eq!(FileTypeIcon::from_extension("rs"), FileTypeIcon::Rust);
+        assert_eq!(FileTypeIcon::from_extension("PY"), FileTypeIcon::Python);
+        assert_eq!(
+            FileTypeIcon::from_extension("tsx"),
+            FileTypeIcon::TypeScript
+        );
+        assert_eq!(FileTypeIcon::for_extension("rs"), "\u{e7a8}");
+        assert_eq!(FileTypeIcon::for_extension("unknown"), GENERIC_ICON);
+
+        assert_eq!(FileTypeIcon::for_path("src/main.rs"), "\u{e7a8}");
+        assert_eq!(FileTypeIcon::for_path("docs/logo.PNG"), "\u{f1c5}");
+        assert_eq!(FileTypeIcon::for_path("Makefile"), GENERIC_ICON);
+    }
+}
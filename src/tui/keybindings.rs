@@ -0,0 +1,469 @@
+//! Configurable keybindings.
+//!
+//! Keys are written as strings such as `"ctrl+r"`, `"F1"` or `"/"` and loaded from the
+//! `[keybindings]` table of the config file. Actions missing from the table keep their defaults.
+//!
+//! The single keys of the results list (`j`/`k`, `gg`/`G`, `]b`/`[b`, `]f`/`[f`, `y`/`Y`,
+//! `m`, `a`, space, `#`, `i`, `c` and `?`) are fixed, see `events::FIXED_KEYS`. Rebinding
+//! one of those characters to an action takes it over everywhere, including the results list.
+
+use crate::tui::events::KeyAction;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A key together with its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Key without modifiers
+    pub fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Character key with Ctrl held
+    pub fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// Whether the key event is this key
+    /// Shift is ignored for characters since terminals report `G` as shift+G
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let relevant = |modifiers: KeyModifiers| match self.code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        self.code == event.code && relevant(self.modifiers) == relevant(event.modifiers)
+    }
+}
+
+/// Names of the keys that are not single characters
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("esc", KeyCode::Esc),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("space", KeyCode::Char(' ')),
+];
+
+impl FromStr for KeySpec {
+    type Err = String;
+
+    /// Parse keys such as `ctrl+r`, `alt+shift+x`, `F1`, `PageUp` or `/`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid key '{}'", s);
+        // A trailing `++` is the plus key, as in `ctrl++`
+        let (modifier_part, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None if s == "+" => ("", "+"),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_part.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let lower = key.to_ascii_lowercase();
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(name, _)| *name == lower)
+                .map(|(_, code)| *code)
+                .or_else(|| {
+                    lower
+                        .strip_prefix('f')
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| (1..=12).contains(n))
+                        .map(KeyCode::F)
+                })
+                .ok_or_else(invalid)?,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => {
+                let name = KEY_NAMES
+                    .iter()
+                    .find(|(_, named)| *named == code)
+                    .map_or("unknown", |(name, _)| *name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+/// Keys of an action joined for display, e.g. "F2 / ctrl+p"
+pub fn keys_label(keys: &[KeySpec]) -> String {
+    let keys: Vec<String> = keys.iter().map(KeySpec::to_string).collect();
+    keys.join(" / ")
+}
+
+impl Serialize for KeySpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeySpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Keys bound to each action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub quit: Vec<KeySpec>,
+    pub move_previous: Vec<KeySpec>,
+    pub move_next: Vec<KeySpec>,
+    pub page_up: Vec<KeySpec>,
+    pub page_down: Vec<KeySpec>,
+    pub half_page_up: Vec<KeySpec>,
+    pub half_page_down: Vec<KeySpec>,
+    pub preview_scroll_up: Vec<KeySpec>,
+    pub preview_scroll_down: Vec<KeySpec>,
+    pub select_first: Vec<KeySpec>,
+    pub select_last: Vec<KeySpec>,
+    pub open_file: Vec<KeySpec>,
+    pub cycle_focus: Vec<KeySpec>,
+    pub refresh_search: Vec<KeySpec>,
+    pub reveal_in_file_manager: Vec<KeySpec>,
+    pub export_results: Vec<KeySpec>,
+    pub toggle_reverse_sort: Vec<KeySpec>,
+    pub focus_search: Vec<KeySpec>,
+    pub focus_filter: Vec<KeySpec>,
+    pub focus_path_filter: Vec<KeySpec>,
+    pub toggle_grouped: Vec<KeySpec>,
+    pub toggle_directory_tree: Vec<KeySpec>,
+    pub toggle_help: Vec<KeySpec>,
+    #[serde(alias = "toggle_fullscreen_preview")]
+    pub cycle_preview_mode: Vec<KeySpec>,
+    pub toggle_word_wrap: Vec<KeySpec>,
+    pub toggle_diff_view: Vec<KeySpec>,
+    pub toggle_preview_position: Vec<KeySpec>,
+    pub select_all: Vec<KeySpec>,
+    pub clear_selection: Vec<KeySpec>,
+    pub delete_char: Vec<KeySpec>,
+    pub cursor_left: Vec<KeySpec>,
+    pub cursor_right: Vec<KeySpec>,
+    pub extend_selection_left: Vec<KeySpec>,
+    pub extend_selection_right: Vec<KeySpec>,
+    pub paste: Vec<KeySpec>,
+    /// Copy and cut are only matched while text in the search box is selected,
+    /// so they can share keys with other actions
+    pub copy_selection: Vec<KeySpec>,
+    pub cut_selection: Vec<KeySpec>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: vec![KeySpec::plain(KeyCode::Esc), KeySpec::ctrl('c')],
+            move_previous: vec![KeySpec::plain(KeyCode::Up)],
+            move_next: vec![KeySpec::plain(KeyCode::Down)],
+            page_up: vec![KeySpec::plain(KeyCode::PageUp)],
+            page_down: vec![KeySpec::plain(KeyCode::PageDown)],
+            half_page_up: vec![KeySpec::ctrl('u')],
+            half_page_down: vec![KeySpec::ctrl('d')],
+            preview_scroll_up: vec![
+                KeySpec::ctrl('k'),
+                KeySpec::new(KeyCode::Up, KeyModifiers::SHIFT),
+            ],
+            preview_scroll_down: vec![
+                KeySpec::ctrl('j'),
+                KeySpec::new(KeyCode::Down, KeyModifiers::SHIFT),
+            ],
+            select_first: vec![KeySpec::plain(KeyCode::Home)],
+            select_last: vec![KeySpec::plain(KeyCode::End)],
+            open_file: vec![KeySpec::plain(KeyCode::Enter)],
+            cycle_focus: vec![KeySpec::plain(KeyCode::Tab)],
+            refresh_search: vec![KeySpec::ctrl('r')],
+            // Terminals that can tell Ctrl+Shift+R from Ctrl+R report an upper case R
+            reveal_in_file_manager: vec![KeySpec::new(
+                KeyCode::Char('R'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            )],
+            export_results: vec![KeySpec::ctrl('s')],
+            toggle_reverse_sort: vec![KeySpec::ctrl('o')],
+            focus_search: vec![KeySpec::plain(KeyCode::Char('/')), KeySpec::ctrl('f')],
+            focus_filter: vec![KeySpec::ctrl('l')],
+            focus_path_filter: vec![KeySpec::ctrl('p')],
+            toggle_grouped: vec![KeySpec::ctrl('g')],
+            toggle_directory_tree: vec![KeySpec::ctrl('t')],
+            toggle_help: vec![KeySpec::plain(KeyCode::F(1))],
+            cycle_preview_mode: vec![KeySpec::plain(KeyCode::F(2))],
+            toggle_word_wrap: vec![KeySpec::ctrl('w')],
+            // Ctrl+D is half page down, so the diff view moved to F3
+            toggle_diff_view: vec![KeySpec::plain(KeyCode::F(3))],
+            toggle_preview_position: vec![KeySpec::new(
+                KeyCode::Char('p'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT,
+            )],
+            select_all: vec![KeySpec::ctrl('a')],
+            // Ctrl+D is half page down, so clearing the marks moved to Ctrl+X
+            clear_selection: vec![KeySpec::ctrl('x')],
+            delete_char: vec![KeySpec::plain(KeyCode::Backspace)],
+            cursor_left: vec![KeySpec::plain(KeyCode::Left)],
+            cursor_right: vec![KeySpec::plain(KeyCode::Right)],
+            extend_selection_left: vec![KeySpec::new(KeyCode::Left, KeyModifiers::SHIFT)],
+            extend_selection_right: vec![KeySpec::new(KeyCode::Right, KeyModifiers::SHIFT)],
+            paste: vec![KeySpec::ctrl('v')],
+            copy_selection: vec![KeySpec::ctrl('c')],
+            cut_selection: vec![KeySpec::ctrl('x')],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Each action with its keys, in the order they are matched
+    fn table(&self) -> [(&[KeySpec], KeyAction); 35] {
+        [
+            (&self.quit, KeyAction::Quit),
+            (&self.move_previous, KeyAction::MovePrevious),
+            (&self.move_next, KeyAction::MoveNext),
+            (&self.page_up, KeyAction::PageUp),
+            (&self.page_down, KeyAction::PageDown),
+            (&self.half_page_up, KeyAction::HalfPageUp),
+            (&self.half_page_down, KeyAction::HalfPageDown),
+            (&self.preview_scroll_up, KeyAction::PreviewScrollUp),
+            (&self.preview_scroll_down, KeyAction::PreviewScrollDown),
+            (&self.select_first, KeyAction::SelectFirst),
+            (&self.select_last, KeyAction::SelectLast),
+            (&self.open_file, KeyAction::OpenFile),
+            (&self.cycle_focus, KeyAction::CycleFocus),
+            (&self.refresh_search, KeyAction::RefreshSearch),
+            (&self.reveal_in_file_manager, KeyAction::RevealInFileManager),
+            (&self.export_results, KeyAction::ExportResults),
+            (&self.toggle_reverse_sort, KeyAction::ToggleReverseSort),
+            (&self.focus_search, KeyAction::FocusSearch),
+            (&self.focus_filter, KeyAction::FocusFilter),
+            (&self.focus_path_filter, KeyAction::FocusPathFilter),
+            (&self.toggle_grouped, KeyAction::ToggleGrouped),
+            (&self.toggle_directory_tree, KeyAction::ToggleDirectoryTree),
+            (&self.toggle_help, KeyAction::ToggleHelp),
+            (&self.cycle_preview_mode, KeyAction::CyclePreviewMode),
+            (&self.toggle_word_wrap, KeyAction::ToggleWordWrap),
+            (&self.toggle_diff_view, KeyAction::ToggleDiffView),
+            (
+                &self.toggle_preview_position,
+                KeyAction::TogglePreviewPosition,
+            ),
+            (&self.select_all, KeyAction::SelectAll),
+            (&self.clear_selection, KeyAction::ClearSelection),
+            (&self.delete_char, KeyAction::DeleteChar),
+            (&self.cursor_left, KeyAction::CursorLeft),
+            (&self.cursor_right, KeyAction::CursorRight),
+            (&self.extend_selection_left, KeyAction::ExtendSelectionLeft),
+            (
+                &self.extend_selection_right,
+                KeyAction::ExtendSelectionRight,
+            ),
+            (&self.paste, KeyAction::Paste),
+        ]
+    }
+
+    /// Each action with its keys as (action, keys) for the help overlay
+    pub fn help(&self) -> Vec<(&'static str, String)> {
+        [
+            ("Run search / open result", &self.open_file),
+            (
+                "Previous result / older search (search box)",
+                &self.move_previous,
+            ),
+            ("Next result / newer search (search box)", &self.move_next),
+            ("Page up", &self.page_up),
+            ("Page down", &self.page_down),
+            ("Half page up", &self.half_page_up),
+            ("Half page down", &self.half_page_down),
+            ("Scroll preview up", &self.preview_scroll_up),
+            ("Scroll preview down", &self.preview_scroll_down),
+            (
+                "First result / cursor to start (search box)",
+                &self.select_first,
+            ),
+            (
+                "Last result / cursor to end (search box)",
+                &self.select_last,
+            ),
+            ("Move cursor left (search box)", &self.cursor_left),
+            ("Move cursor right (search box)", &self.cursor_right),
+            ("Select text left (search box)", &self.extend_selection_left),
+            (
+                "Select text right (search box)",
+                &self.extend_selection_right,
+            ),
+            ("Delete character (search box)", &self.delete_char),
+            ("Copy selected text (search box)", &self.copy_selection),
+            ("Cut selected text (search box)", &self.cut_selection),
+            ("Paste (search box)", &self.paste),
+            ("Cycle focus", &self.cycle_focus),
+            ("Focus search box", &self.focus_search),
+            ("Filter results", &self.focus_filter),
+            ("Filter results by path glob", &self.focus_path_filter),
+            ("Group results by file", &self.toggle_grouped),
+            ("Toggle directory tree", &self.toggle_directory_tree),
+            ("Re-run search", &self.refresh_search),
+            ("Show file in file manager", &self.reveal_in_file_manager),
+            ("Export results (.json for JSON)", &self.export_results),
+            ("Reverse sort order", &self.toggle_reverse_sort),
+            ("Mark all results", &self.select_all),
+            ("Clear marks", &self.clear_selection),
+            ("Toggle this help", &self.toggle_help),
+            (
+                "Preview split / fullscreen / hidden",
+                &self.cycle_preview_mode,
+            ),
+            ("Wrap long result lines", &self.toggle_word_wrap),
+            (
+                "Toggle git diff of the result in the preview",
+                &self.toggle_diff_view,
+            ),
+            (
+                "Preview on the right / below",
+                &self.toggle_preview_position,
+            ),
+            ("Quit", &self.quit),
+        ]
+        .into_iter()
+        .map(|(action, keys)| (action, keys_label(keys)))
+        .collect()
+    }
+
+    /// Action bound to the key event while text in the search box is selected
+    pub fn selection_action_for(&self, event: &KeyEvent) -> Option<KeyAction> {
+        [
+            (&self.copy_selection, KeyAction::CopySelection),
+            (&self.cut_selection, KeyAction::CutSelection),
+        ]
+        .into_iter()
+        .find(|(keys, _)| keys.iter().any(|key| key.matches(event)))
+        .map(|(_, action)| action)
+    }
+
+    /// Action bound to the key event, the first matching action wins
+    pub fn action_for(&self, event: &KeyEvent) -> Option<KeyAction> {
+        self.table()
+            .into_iter()
+            .find(|(keys, _)| keys.iter().any(|key| key.matches(event)))
+            .map(|(_, action)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_spec() {
+        let cases = [
+            ("ctrl+r", KeySpec::ctrl('r')),
+            (
+                "Ctrl+Shift+x",
+                KeySpec::new(
+                    KeyCode::Char('x'),
+                    KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+                ),
+            ),
+            ("F1", KeySpec::plain(KeyCode::F(1))),
+            ("PageUp", KeySpec::plain(KeyCode::PageUp)),
+            ("/", KeySpec::plain(KeyCode::Char('/'))),
+            ("alt++", KeySpec::new(KeyCode::Char('+'), KeyModifiers::ALT)),
+            ("space", KeySpec::plain(KeyCode::Char(' '))),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(s.parse::<KeySpec>().unwrap(), expected, "{}", s);
+        }
+
+        for invalid in ["", "hyper+x", "F13", "ctrl+", "escape"] {
+            assert!(invalid.parse::<KeySpec>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_key_spec_display_round_trip() {
+        for spec in [
+            "ctrl+r",
+            "alt+shift+x",
+            "F12",
+            "esc",
+            "pagedown",
+            "space",
+            "ctrl++",
+        ] {
+            let parsed: KeySpec = spec.parse().unwrap();
+            assert_eq!(parsed.to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn test_key_spec_matches() {
+        let event = |code, modifiers| KeyEvent::new(code, modifiers);
+
+        assert!(KeySpec::ctrl('r').matches(&event(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert!(!KeySpec::ctrl('r').matches(&event(KeyCode::Char('r'), KeyModifiers::NONE)));
+        // Shift only matters for keys other than characters
+        let upper_g = KeySpec::plain(KeyCode::Char('G'));
+        assert!(upper_g.matches(&event(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        let up = KeySpec::plain(KeyCode::Up);
+        assert!(!up.matches(&event(KeyCode::Up, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn test_action_for() {
+        let mut bindings = KeyBindings::default();
+        let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(bindings.action_for(&esc), Some(KeyAction::Quit));
+        assert_eq!(bindings.action_for(&ctrl_q), None);
+
+        bindings.quit = vec![KeySpec::ctrl('q')];
+        assert_eq!(bindings.action_for(&ctrl_q), Some(KeyAction::Quit));
+        assert_eq!(bindings.action_for(&esc), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Rendered Markdown for the preview.
+//!
+//! Markdown is parsed with pulldown-cmark and drawn as styled lines. Each line keeps
+//! the source line it starts on, so the preview can still show a line number gutter
+//! and center on the line of a result.
+
+use crate::tui::highlighter::SyntaxHighlighter;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::ops::Range;
+
+/// Width of a thematic break
+const RULE_WIDTH: usize = 40;
+
+/// Line of rendered Markdown
+#[derive(Debug, Clone)]
+pub struct RenderedLine {
+    /// Line of the source the rendered line starts on, `None` for blank lines between blocks
+    pub source_line: Option<usize>,
+    pub line: Line<'static>,
+}
+
+/// Render Markdown source as styled lines
+pub fn render_markdown(source: &str, highlighter: &mut SyntaxHighlighter) -> Vec<RenderedLine> {
+    let mut renderer = Renderer::new(source);
+    let parser = Parser::new_ext(source, Options::ENABLE_STRIKETHROUGH);
+    for (event, range) in parser.into_offset_iter() {
+        renderer.handle(event, range, highlighter);
+    }
+    renderer.flush();
+    renderer.lines
+}
+
+/// Code block being collected until its end, it is highlighted as a whole
+struct CodeBlock {
+    language: Option<String>,
+    code: String,
+    first_line: usize,
+}
+
+struct Renderer {
+    /// Byte offset where each source line starts
+    line_starts: Vec<usize>,
+    lines: Vec<RenderedLine>,
+    spans: Vec<Span<'static>>,
+    source_line: Option<usize>,
+    /// Styles of the inline tags that are open, innermost last
+    styles: Vec<Style>,
+    /// Next number of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    link: Option<String>,
+    code_block: Option<CodeBlock>,
+}
+
+impl Renderer {
+    fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            line_starts,
+            lines: Vec::new(),
+            spans: Vec::new(),
+            source_line: None,
+            styles: Vec::new(),
+            lists: Vec::new(),
+            quote_depth: 0,
+            link: None,
+            code_block: None,
+        }
+    }
+
+    /// 1-based source line of a byte offset
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |style, inner| style.patch(*inner))
+    }
+
+    fn handle(&mut self, event: Event, range: Range<usize>, highlighter: &mut SyntaxHighlighter) {
+        let line = self.line_of(range.start);
+        match event {
+            Event::Start(tag) => self.start(tag, line),
+            Event::End(tag) => self.end(tag, highlighter),
+            Event::Text(text) => match self.code_block.as_mut() {
+                Some(block) => block.code.push_str(&text),
+                None => self.push_text(&text, self.style(), line),
+            },
+            Event::Code(code) => {
+                let style = self.style().fg(Color::Yellow);
+                self.push(Span::styled(code.into_string(), style), line);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                self.push_text(&html, self.style(), line)
+            }
+            Event::SoftBreak | Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.start_block();
+                let rule =
+                    Span::styled("─".repeat(RULE_WIDTH), Style::default().fg(Color::DarkGray));
+                self.push(rule, line);
+                self.flush();
+            }
+            Event::TaskListMarker(checked) => {
+                self.push(Span::raw(if checked { "[x] " } else { "[ ] " }), line)
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag, line: usize) {
+        match tag {
+            // The first paragraph of a list item continues the line of its bullet
+            Tag::Paragraph if self.spans.is_empty() => self.start_block(),
+            Tag::Heading { .. } => {
+                self.start_block();
+                self.styles
+                    .push(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.start_block();
+                let (language, first_line) = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        let language = info.split_whitespace().next().map(str::to_string);
+                        (language, line + 1)
+                    }
+                    CodeBlockKind::Indented => (None, line),
+                };
+                self.code_block = Some(CodeBlock {
+                    language,
+                    code: String::new(),
+                    first_line,
+                });
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else {
+                    self.flush();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.push(Span::raw(format!("{}{}", "  ".repeat(depth), marker)), line);
+            }
+            Tag::Emphasis => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } => {
+                self.push(Span::styled("[", self.style()), line);
+                self.link = Some(dest_url.into_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd, highlighter: &mut SyntaxHighlighter) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Item => self.flush(),
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.flush();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                if let Some(block) = self.code_block.take() {
+                    self.push_code_block(block, highlighter);
+                }
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                if let Some(url) = self.link.take() {
+                    let source_line = self.source_line.unwrap_or_default();
+                    self.push(Span::styled("](", self.style()), source_line);
+                    self.push(
+                        Span::styled(url, Style::default().fg(Color::DarkGray)),
+                        source_line,
+                    );
+                    self.push(Span::styled(")", self.style()), source_line);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Add text that may span several lines
+    fn push_text(&mut self, text: &str, style: Style, line: usize) {
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                self.flush();
+            }
+            if !part.is_empty() {
+                self.push(Span::styled(part.to_string(), style), line + index);
+            }
+        }
+    }
+
+    /// Add a span to the current line, starting the line on the given source line
+    fn push(&mut self, span: Span<'static>, source_line: usize) {
+        if self.spans.is_empty() {
+            self.source_line = Some(source_line);
+            if self.quote_depth > 0 {
+                self.spans.push(Span::styled(
+                    "│ ".repeat(self.quote_depth),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+        }
+        self.spans.push(span);
+    }
+
+    fn push_code_block(&mut self, block: CodeBlock, highlighter: &mut SyntaxHighlighter) {
+        let extension = block
+            .language
+            .as_deref()
+            .and_then(SyntaxHighlighter::extension_for_token);
+        let code = block.code.strip_suffix('\n').unwrap_or(&block.code);
+        let highlighted = highlighter.highlight_text(code, extension.as_deref());
+        for (index, mut line) in highlighted.lines.into_iter().enumerate() {
+            // Highlighted lines keep their line ending
+            if let Some(last) = line.spans.last_mut() {
+                if last.content.ends_with('\n') {
+                    last.content.to_mut().pop();
+                }
+            }
+            for span in line.spans {
+                self.push(span, block.first_line + index);
+            }
+            self.flush();
+        }
+    }
+
+    /// Separate a block from the one before it with a blank line
+    fn start_block(&mut self) {
+        self.flush();
+        if self.lists.is_empty()
+            && self
+                .lines
+                .last()
+                .is_some_and(|last| last.source_line.is_some())
+        {
+            self.lines.push(RenderedLine {
+                source_line: None,
+                line: Line::default(),
+            });
+        }
+    }
+
+    /// Finish the current line
+    fn flush(&mut self) {
+        if self.spans.is_empty() {
+            return;
+        }
+        self.lines.push(RenderedLine {
+            source_line: self.source_line.take(),
+            line: Line::from(std::mem::take(&mut self.spans)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &RenderedLine) -> String {
+        line.line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let source = "# Title\n\nSome **bold** and *italic* with `code`.\nSee [docs](https://example.com).\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n";
+        let mut highlighter = SyntaxHighlighter::new();
+        let lines = render_markdown(source, &mut highlighter);
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            texts,
+            [
+                "Title",
+                "",
+                "Some bold and italic with code.",
+                "See [docs](https://example.com).",
+                "",
+                "• one",
+                "• two",
+                "",
+                "fn main() {}",
+            ]
+        );
+
+        let source_lines: Vec<Option<usize>> = lines.iter().map(|line| line.source_line).collect();
+        assert_eq!(
+            source_lines,
+            [
+                Some(1),
+                None,
+                Some(3),
+                Some(4),
+                None,
+                Some(6),
+                Some(7),
+                None,
+                Some(10)
+            ]
+        );
+
+        let heading = &lines[0].line.spans[0];
+        assert!(heading
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+        let bold = &lines[2].line.spans[1];
+        assert_eq!(bold.content, "bold");
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        let url = &lines[3].line.spans[4];
+        assert_eq!(url.content, "https://example.com");
+        assert_eq!(url.style.fg, Some(Color::DarkGray));
+    }
+}
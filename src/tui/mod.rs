@@ -3,8 +3,15 @@
 //! Handles split-pane TUI with search results and file preview
 
 pub mod highlighter;
+pub mod diff_view;
+pub mod directory_tree;
+pub mod icons;
+pub mod markdown;
 pub mod ui;
 pub mod app;
 pub mod events;
+pub mod keybindings;
+pub mod runner;
 
+pub use runner::run;
 pub use ui::ResultsAreaInfo;
@@ -0,0 +1,801 @@
+//! Terminal lifecycle and main event loop
+//!
+//! Sets up the terminal, streams ripgrep results into the app and
+//! dispatches keyboard and mouse input. The event loop selects over
+//! terminal input and the channel of streamed results
+
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::editor;
+use crate::history;
+use crate::preview::PreviewHandler;
+use crate::search::multi::MultiPatternEngine;
+use crate::search::progressive::ProgressiveLoader;
+use crate::search::rate_limit::RateLimiter;
+use crate::search::sorter::SortOrder;
+use crate::search::stream::{self, SearchTask};
+use crate::search::{SearchEngine, SearchResult};
+use crate::tui::app::{App, InputFocus, PreviewMode};
+use crate::tui::events::{self, EventHandler, KeyAction, MouseAction};
+use crate::tui::highlighter::SyntaxHighlighter;
+use crate::tui::ResultsAreaInfo;
+use crate::validation::InputValidator;
+use crate::{Result, SearchError};
+use crossterm::event::{
+    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Position, Rect};
+use ratatui::Terminal;
+use std::io::{self, Stdout};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// Time to wait for input before redrawing
+const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(16);
+
+/// Running search and the loader its parsed results arrive from
+struct ActiveSearch {
+    task: SearchTask,
+    loader: ProgressiveLoader,
+    /// `--search-timeout-ms` and when it runs out
+    timeout: Option<(Duration, Instant)>,
+    /// Per-file match limit passed to ripgrep
+    max_count: Option<usize>,
+}
+
+impl ActiveSearch {
+    /// Spawn ripgrep and a task that parses its output in the background
+    /// With `--and` or `--or` patterns the combined results arrive once every search finished
+    fn start(
+        engine: &SearchEngine,
+        multi: Option<&MultiPatternEngine>,
+        pattern: &str,
+        directories: &[String],
+    ) -> Result<Self> {
+        let (task, loader) = match multi {
+            Some(multi) => Self::spawn_multi(multi, pattern, directories)?,
+            None => stream::spawn_search(engine, pattern, directories)?,
+        };
+
+        Ok(Self {
+            task,
+            loader,
+            timeout: engine
+                .config
+                .timeout
+                .map(|timeout| (timeout, Instant::now() + timeout)),
+            max_count: engine.config.max_count,
+        })
+    }
+
+    /// Start a search for every pattern, the combined results arrive at the end
+    fn spawn_multi(
+        multi: &MultiPatternEngine,
+        pattern: &str,
+        directories: &[String],
+    ) -> Result<(SearchTask, ProgressiveLoader)> {
+        let mut streams = multi.start_searches(pattern, directories)?;
+        let processes = streams
+            .iter_mut()
+            .filter_map(|stream| stream.process.take())
+            .collect();
+
+        let multi = multi.clone();
+        Ok(stream::spawn_blocking_search(processes, move || {
+            multi.read_results(streams)
+        }))
+    }
+
+    /// Kill the ripgrep processes and reap them
+    fn stop(&mut self) {
+        self.task.stop();
+    }
+}
+
+/// Run the interactive TUI until the user quits
+pub async fn run(cli: &Cli, config: &Config) -> Result<()> {
+    let engine = SearchEngine::from_cli(cli)?;
+
+    let mut app = App::new_with_cache_size(config.cache_size_limit);
+    app.search_debounce_ms = config.search_debounce_ms;
+    app.search_rate_limiter = RateLimiter::new(config.min_search_interval_ms);
+    app.load_batch_size = config.load_batch_size.max(1);
+    app.progressive_load_threshold = config.progressive_load_threshold;
+    app.mtime_format = config.mtime_format.clone();
+    app.set_show_mtime(config.show_mtime);
+    app.set_show_git_blame(config.show_git_blame);
+    app.show_result_numbers = config.show_result_numbers;
+    app.show_icons = config.show_icons;
+    app.column_layout = config.column_layout;
+    app.path_column_percent = config.path_column_percent.min(100);
+    app.line_column_width = config.line_column_width;
+    app.preview_position = config.preview_position;
+    app.preview_mode = config.preview_mode;
+    app.max_results = cli.max_results;
+    app.max_line_length = cli.line_length_limit();
+    app.max_filesize = cli.max_filesize;
+    if let Some(path) = &cli.import_annotations {
+        app.import_annotations(path)?;
+    }
+    app.use_color = terminal_supports_color();
+    app.editor = editor::resolve_editor(cli.editor.as_deref());
+    let encoding = cli
+        .encoding
+        .as_deref()
+        .map(PreviewHandler::encoding_for_label)
+        .transpose()?;
+    app.preview_handler = PreviewHandler::new()
+        .with_hex_fallback(config.hex)
+        .with_render_markdown(config.render_markdown)
+        .with_preview_command(config.preview_command.clone())
+        .with_encoding(encoding)
+        .with_max_line_length(cli.line_length_limit());
+    app.set_search_directories(cli.search_dir());
+    app.set_search_mode(engine.config.mode.clone());
+    app.set_sort_by_score(engine.is_fuzzy());
+    app.set_deduplicate(!cli.no_dedup);
+    match cli.sort {
+        Some(sort_order) => app.set_sort_order(sort_order),
+        // Git line history makes the modification time order meaningful
+        None if app.has_git_repo() => app.set_sort_order(SortOrder::ModificationTime),
+        None => {}
+    }
+    app.update_pattern(cli.pattern.clone());
+    if let Some(path) = history::history_path() {
+        app.load_history_file(path);
+    }
+
+    let mut highlighter = match (&cli.theme_file, &config.theme) {
+        (Some(path), _) => SyntaxHighlighter::load_theme_from_file(path)?,
+        (None, Some(theme)) => SyntaxHighlighter::with_theme(theme)?,
+        (None, None) => SyntaxHighlighter::new(),
+    };
+    let multi = MultiPatternEngine::from_cli(cli, &engine);
+    app.help_rows = events::help_rows(&config.keybindings);
+    app.keybindings = config.keybindings.clone();
+    let events = EventHandler::new(config.keybindings.clone())?;
+    let mut terminal = setup_terminal()?;
+    if let Ok((columns, rows)) = crossterm::terminal::size() {
+        app.handle_resize(columns, rows);
+    }
+    let result = event_loop(
+        &mut terminal,
+        &mut app,
+        &events,
+        &engine,
+        multi.as_ref(),
+        &mut highlighter,
+    )
+    .await;
+    restore_terminal(&mut terminal)?;
+    // Written once here rather than on every toggle, to keep file I/O out of the event loop
+    save_preview_layout(&app, config);
+    crate::logging::debug_log(&format!(
+        "Highlight cache hit rate: {:.1}%",
+        app.cache_hit_rate() * 100.0
+    ));
+    result
+}
+
+/// Colors are disabled by `NO_COLOR` or a dumb terminal
+fn terminal_supports_color() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb_terminal = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    !no_color && !dumb_terminal
+}
+
+/// Put the terminal in raw mode on the alternate screen
+fn setup_terminal() -> Result<Tui> {
+    // Errors are shown in the status bar, where escape codes would come out as text
+    colored::control::set_override(false);
+    enable_raw_mode().map_err(|e| SearchError::terminal_error(&e.to_string()))?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+        .map_err(|e| SearchError::terminal_error(&e.to_string()))?;
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+        .map_err(|e| SearchError::terminal_error(&e.to_string()))
+}
+
+/// Restore the terminal to its original state
+fn restore_terminal(terminal: &mut Tui) -> Result<()> {
+    colored::control::unset_override();
+    disable_raw_mode().map_err(|e| SearchError::terminal_error(&e.to_string()))?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+        .map_err(|e| SearchError::terminal_error(&e.to_string()))?;
+    terminal
+        .show_cursor()
+        .map_err(|e| SearchError::terminal_error(&e.to_string()))
+}
+
+/// Input, results, the search timeout or a rate limited search the event loop woke up for
+enum LoopEvent {
+    Input(Option<Event>),
+    Results(Vec<SearchResult>),
+    SearchTimeout,
+    QueuedSearch,
+}
+
+/// Main loop: draw, then wait for input or the next batch of streamed results
+async fn event_loop(
+    terminal: &mut Tui,
+    app: &mut App,
+    events: &EventHandler,
+    engine: &SearchEngine,
+    multi: Option<&MultiPatternEngine>,
+    highlighter: &mut SyntaxHighlighter,
+) -> Result<()> {
+    let mut active_search = start_search(app, engine, multi, None);
+    let mut results_area = ResultsAreaInfo {
+        top: 0,
+        height: 0,
+        left: 0,
+        width: 0,
+        page_size: 0,
+        first_row: 0,
+        row_heights: Vec::new(),
+        tree: None,
+        preview: Rect::default(),
+    };
+
+    // Input is polled on a blocking task, a new poll only starts once the last event
+    // was handled so nothing reads the terminal while the editor is open
+    let mut input: Option<JoinHandle<Result<Option<Event>>>> = None;
+    let mut redraw = true;
+
+    while !app.should_quit {
+        if redraw {
+            terminal
+                .draw(|frame| results_area = crate::tui::ui::draw(frame, app, highlighter))
+                .map_err(|e| SearchError::TuiError(format!("Drawing failed: {}", e)))?;
+        }
+        redraw = true;
+
+        let input_poll = input.get_or_insert_with(|| {
+            tokio::task::spawn_blocking(|| EventHandler::next_event(EVENT_POLL_TIMEOUT))
+        });
+        let batch_size = app.load_batch_size;
+        let deadline = active_search
+            .as_ref()
+            .and_then(|search| search.timeout)
+            .map(|(_, deadline)| deadline);
+        // Results are always read so counts, sorting and the timeout stay right
+        let loading = active_search.as_mut();
+        let queued_search = app
+            .search_rate_limiter
+            .next_allowed_at()
+            .map(Instant::from_std);
+        let next = tokio::select! {
+            event = input_poll => LoopEvent::Input(
+                event.map_err(|e| SearchError::TuiError(format!("Event polling failed: {}", e)))??,
+            ),
+            batch = next_batch(loading, batch_size) => LoopEvent::Results(batch),
+            _ = search_deadline(deadline) => LoopEvent::SearchTimeout,
+            _ = search_deadline(queued_search) => LoopEvent::QueuedSearch,
+        };
+        let event = match next {
+            LoopEvent::Input(event) => {
+                input = None;
+                event
+            }
+            LoopEvent::Results(batch) => {
+                if let Some(search) = active_search.as_mut() {
+                    if add_results(app, search, batch) {
+                        active_search = None;
+                    }
+                }
+                // Results far past the selection are drawn with the next input poll
+                redraw = active_search.is_none() || app.needs_progressive_load_check;
+                None
+            }
+            LoopEvent::SearchTimeout => {
+                if let Some(search) = active_search.take() {
+                    time_out_search(app, search);
+                }
+                None
+            }
+            LoopEvent::QueuedSearch => {
+                if app.is_pattern_changed() {
+                    active_search = start_search(app, engine, multi, active_search);
+                } else {
+                    app.search_rate_limiter.cancel();
+                }
+                None
+            }
+        };
+
+        let previous_selection = app.selected_index;
+        let searching = app.search_progress.is_searching;
+        match event {
+            // Any key dismisses the help overlay
+            Some(Event::Key(_)) if app.show_help => app.show_help = false,
+            Some(Event::Key(key)) if app.export_prompt.is_some() => {
+                handle_export_prompt_key(app, key)
+            }
+            Some(Event::Key(key)) if app.annotation_prompt.is_some() => {
+                handle_annotation_prompt_key(app, key)
+            }
+            // Escape leaves the fullscreen preview before anything else
+            Some(Event::Key(key))
+                if app.preview_mode == PreviewMode::Fullscreen && key.code == KeyCode::Esc =>
+            {
+                app.preview_mode = PreviewMode::Normal;
+            }
+            // Escape drops an edit to the pattern before it cancels the search or quits
+            Some(Event::Key(key))
+                if key.code == KeyCode::Esc
+                    && app.input_focus == InputFocus::Primary
+                    && app.is_pattern_edited() =>
+            {
+                app.restore_committed_pattern();
+                // A live search already ran for the edit, show the results of the pattern again
+                if app.is_pattern_changed() {
+                    active_search = start_search(app, engine, multi, active_search);
+                }
+            }
+            Some(Event::Key(key)) => match events.handle_app_key(
+                key,
+                app.input_focus,
+                searching,
+                app.pattern_selection().is_some(),
+            ) {
+                KeyAction::ToggleHelp => app.toggle_help(),
+                KeyAction::CyclePreviewMode => app.cycle_preview_mode(),
+                KeyAction::ToggleWordWrap => app.toggle_word_wrap(),
+                KeyAction::ToggleResultNumbers => app.toggle_result_numbers(),
+                KeyAction::ToggleIcons => app.toggle_icons(),
+                KeyAction::ToggleColumnLayout => app.toggle_column_layout(),
+                KeyAction::ToggleDiffView => app.toggle_diff_view(),
+                KeyAction::TogglePreviewPosition => app.toggle_preview_position(),
+                KeyAction::ToggleGrouped => app.toggle_grouped_view(),
+                KeyAction::ToggleDirectoryTree => app.toggle_directory_tree(),
+                KeyAction::ToggleReverseSort => app.toggle_reverse_sort(),
+                KeyAction::ExportResults => app.open_export_prompt(),
+                KeyAction::AddAnnotation => app.open_annotation_prompt(),
+                KeyAction::ExportAnnotations => app.open_annotation_export_prompt(),
+                KeyAction::YankPath => {
+                    if let Err(e) = app.copy_selected_path_to_clipboard() {
+                        report_clipboard_error(app, e);
+                    }
+                }
+                KeyAction::YankLine => {
+                    if let Err(e) = app.copy_selected_line_to_clipboard() {
+                        report_clipboard_error(app, e);
+                    }
+                }
+                KeyAction::Quit => app.quit(),
+                KeyAction::CancelSearch => {
+                    if let Some(mut search) = active_search.take() {
+                        search.stop();
+                    }
+                    app.cancel_search();
+                }
+                KeyAction::MovePrevious if app.input_focus == InputFocus::Tree => {
+                    if let Some(tree) = app.directory_tree.as_mut() {
+                        tree.select_previous();
+                    }
+                }
+                KeyAction::MoveNext if app.input_focus == InputFocus::Tree => {
+                    if let Some(tree) = app.directory_tree.as_mut() {
+                        tree.select_next();
+                    }
+                }
+                KeyAction::MovePrevious | KeyAction::VimUp => app.select_previous(),
+                KeyAction::MoveNext | KeyAction::VimDown => app.select_next(),
+                KeyAction::SelectFirst => app.select_first(),
+                KeyAction::SelectLast => app.select_last(),
+                KeyAction::PageUp => app.select_page_up(results_area.page_size),
+                KeyAction::PageDown => app.select_page_down(results_area.page_size),
+                KeyAction::HalfPageUp => app.select_page_up(results_area.page_size / 2),
+                KeyAction::HalfPageDown => app.select_page_down(results_area.page_size / 2),
+                KeyAction::PreviewScrollUp => app.scroll_preview_up(1),
+                KeyAction::PreviewScrollDown => app.scroll_preview_down(1),
+                KeyAction::OpenFile => match app.input_focus {
+                    InputFocus::Primary => {
+                        let pattern = app.active_pattern().to_string();
+                        app.push_search_history(&pattern);
+                        app.commit_pattern();
+                        active_search = start_search(app, engine, multi, active_search);
+                    }
+                    InputFocus::Results => {
+                        if !app.selected_results.is_empty() {
+                            open_marked_results(terminal, app)?;
+                        } else if !app.toggle_selected_group() {
+                            open_selected_result(terminal, app)?;
+                        }
+                    }
+                    InputFocus::Filter | InputFocus::PathFilter => {
+                        app.input_focus = InputFocus::Results
+                    }
+                    InputFocus::Tree => app.activate_tree_selection(),
+                },
+                KeyAction::CycleFocus => app.toggle_focus(),
+                KeyAction::RefreshSearch => {
+                    active_search = start_search(app, engine, multi, active_search);
+                }
+                KeyAction::RevealInFileManager => reveal_selected_result(app),
+                KeyAction::FocusSearch => app.input_focus = InputFocus::Primary,
+                KeyAction::FocusFilter => app.toggle_filter_focus(),
+                KeyAction::FocusPathFilter => app.toggle_path_filter_focus(),
+                KeyAction::InputChar(c) => match app.input_focus {
+                    InputFocus::Primary => {
+                        app.insert_pattern_char(c);
+                        app.schedule_search();
+                    }
+                    InputFocus::Filter => app.filter_pattern_mut().push(c),
+                    InputFocus::PathFilter => app.path_filter_mut().push(c),
+                    InputFocus::Results | InputFocus::Tree => {}
+                },
+                KeyAction::DeleteChar => match app.input_focus {
+                    InputFocus::Primary => {
+                        app.delete_pattern_char();
+                        app.schedule_search();
+                    }
+                    InputFocus::Filter => {
+                        app.filter_pattern_mut().pop();
+                    }
+                    InputFocus::PathFilter => {
+                        app.path_filter_mut().pop();
+                    }
+                    InputFocus::Results | InputFocus::Tree => {}
+                },
+                KeyAction::CursorLeft => app.move_cursor_left(),
+                KeyAction::CursorRight => app.move_cursor_right(),
+                KeyAction::CursorHome => app.move_cursor_home(),
+                KeyAction::CursorEnd => app.move_cursor_end(),
+                KeyAction::ExtendSelectionLeft => app.extend_selection_left(),
+                KeyAction::ExtendSelectionRight => app.extend_selection_right(),
+                KeyAction::CopySelection => {
+                    if let Err(e) = app.copy_pattern_selection() {
+                        report_clipboard_error(app, e);
+                    }
+                }
+                KeyAction::CutSelection => match app.cut_pattern_selection() {
+                    Ok(()) => app.schedule_search(),
+                    Err(e) => report_clipboard_error(app, e),
+                },
+                KeyAction::Paste if app.input_focus == InputFocus::Primary => {
+                    match app.paste_into_pattern() {
+                        Ok(()) => app.schedule_search(),
+                        Err(e) => report_clipboard_error(app, e),
+                    }
+                }
+                KeyAction::Paste => {}
+                KeyAction::HistoryPrevious => app.history_previous(),
+                KeyAction::HistoryNext => app.history_next(),
+                KeyAction::ToggleBookmark => app.toggle_bookmark(),
+                KeyAction::ToggleSelect => app.toggle_select(),
+                KeyAction::SelectAll => app.select_all(),
+                KeyAction::ClearSelection => app.clear_selection(),
+                KeyAction::JumpToNextBookmark => app.jump_to_next_bookmark(),
+                KeyAction::JumpToNextFile => app.jump_to_next_file(),
+                KeyAction::JumpToPrevFile => app.jump_to_prev_file(),
+                KeyAction::JumpToPrevBookmark => app.jump_to_prev_bookmark(),
+                KeyAction::None => {}
+            },
+            // Only the size changes, the next draw lays out the panes again
+            Some(Event::Resize(columns, rows)) => app.handle_resize(columns, rows),
+            Some(Event::Mouse(mouse)) => {
+                let in_preview_columns = results_area
+                    .preview
+                    .contains(Position::new(mouse.column, mouse.row));
+                // The preview may sit below the results list, in the same columns
+                let in_results_columns = mouse.column >= results_area.left
+                    && mouse.column < results_area.left + results_area.width
+                    && !in_preview_columns;
+                let tree_area = results_area
+                    .tree
+                    .filter(|tree| mouse.column >= tree.x && mouse.column < tree.x + tree.width);
+                match events.handle_mouse_event(mouse) {
+                    MouseAction::ClickAt(_, row) if tree_area.is_some() => {
+                        if let Some(tree) = tree_area {
+                            app.handle_tree_click(row, tree.y, tree.height);
+                        }
+                    }
+                    MouseAction::ClickAt(_, row) if in_results_columns => {
+                        app.handle_results_click(row, &results_area);
+                    }
+                    MouseAction::ScrollUp(lines) if in_results_columns => {
+                        (0..lines).for_each(|_| app.select_previous());
+                    }
+                    MouseAction::ScrollDown(lines) if in_results_columns => {
+                        (0..lines).for_each(|_| app.select_next());
+                    }
+                    MouseAction::ScrollUp(lines) if in_preview_columns => {
+                        app.scroll_preview_up(lines)
+                    }
+                    MouseAction::ScrollDown(lines) if in_preview_columns => {
+                        app.scroll_preview_down(lines)
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+
+        // Search as you type once the pattern stopped changing, a search typed too soon
+        // after the last one is queued until the rate limit allows it
+        if app.is_search_due() {
+            if app.is_pattern_changed() {
+                match app.search_rate_limiter.request() {
+                    Ok(()) => active_search = start_search(app, engine, multi, active_search),
+                    Err(e) => {
+                        app.pending_search = None;
+                        crate::logging::debug_log(&format!("Queued search: {:?}", e));
+                    }
+                }
+            } else {
+                app.pending_search = None;
+            }
+        }
+
+        // A new selection shows its preview from the target line again
+        if app.selected_index != previous_selection {
+            app.preview_scroll_offset = 0;
+            app.check_progressive_load();
+        }
+    }
+
+    if let Some(mut search) = active_search {
+        search.stop();
+    }
+    Ok(())
+}
+
+/// Stop any running search and start a new one for the current pattern
+fn start_search(
+    app: &mut App,
+    engine: &SearchEngine,
+    multi: Option<&MultiPatternEngine>,
+    previous: Option<ActiveSearch>,
+) -> Option<ActiveSearch> {
+    app.pending_search = None;
+    app.search_rate_limiter.cancel();
+    if let Some(mut search) = previous {
+        search.stop();
+        crate::logging::debug_log(&format!("Replacing search: {:?}", SearchError::Cancelled));
+    }
+
+    // Fuzzy patterns are escaped before ripgrep sees them, other modes search for a regex
+    let validated = if engine.is_fuzzy() {
+        InputValidator::validate_search_pattern(app.active_pattern())
+    } else {
+        InputValidator::validate_regex_pattern(app.active_pattern())
+    };
+    let pattern = match validated {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            crate::logging::warn_log(&format!("Not searching: {}", e));
+            app.status_message = Some(e.to_string());
+            return None;
+        }
+    };
+
+    app.start_new_search();
+    app.search_rate_limiter.record_spawn();
+    match ActiveSearch::start(engine, multi, &pattern, &app.search_directories) {
+        Ok(search) => Some(search),
+        Err(e) => {
+            crate::logging::error_log(&format!("Search failed to start: {}", e));
+            app.complete_search();
+            None
+        }
+    }
+}
+
+/// Show a failed clipboard copy in the status bar
+fn report_clipboard_error(app: &mut App, error: SearchError) {
+    crate::logging::warn_log(&format!("Copy to clipboard failed: {}", error));
+    app.status_message = Some(match error {
+        SearchError::TuiError(message) => message,
+        other => format!("Copy failed: {}", other),
+    });
+}
+
+/// Edit the export file name, Enter exports and Esc cancels
+/// Keys are read directly so characters such as `/` are typed instead of mapped to actions
+fn handle_export_prompt_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.confirm_export(),
+        KeyCode::Esc => app.export_prompt = None,
+        KeyCode::Backspace => {
+            app.export_prompt.get_or_insert_with(String::new).pop();
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.export_prompt.get_or_insert_with(String::new).push(c)
+        }
+        _ => {}
+    }
+}
+
+/// Edit the note of the selected result, Enter attaches it and Esc cancels
+fn handle_annotation_prompt_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.confirm_annotation(),
+        KeyCode::Esc => app.annotation_prompt = None,
+        KeyCode::Backspace => {
+            app.annotation_prompt.get_or_insert_with(String::new).pop();
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.annotation_prompt
+                .get_or_insert_with(String::new)
+                .push(c);
+        }
+        _ => {}
+    }
+}
+
+/// Wait for the next batch of results of the search, forever when nothing is searching
+async fn next_batch(search: Option<&mut ActiveSearch>, batch_size: usize) -> Vec<SearchResult> {
+    match search {
+        Some(search) => search.loader.recv_batch(batch_size).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait until the search runs out of time or a queued search may start, forever
+/// when there is no deadline
+async fn search_deadline(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Kill a search that ran past its timeout and keep the results found so far
+fn time_out_search(app: &mut App, mut search: ActiveSearch) {
+    search.stop();
+    app.add_sarch_results(search.loader.next_batch(usize::MAX));
+    if let Some((timeout, _)) = search.timeout {
+        crate::logging::warn_log(&SearchError::Timeout(timeout).to_string());
+        app.time_out_search(timeout);
+    }
+}
+
+/// Move a batch of streamed results into the app
+/// Returns true once ripgrep has finished and every result was received
+fn add_results(app: &mut App, search: &mut ActiveSearch, batch: Vec<SearchResult>) -> bool {
+    let finished = search.loader.is_exhausted();
+
+    let files: Vec<String> = batch
+        .iter()
+        .map(|result| result.file_path.clone())
+        .collect();
+    app.add_sarch_results(batch);
+    // Ripgrep stops reading a file once it reaches the limit
+    if let Some(max_count) = search.max_count {
+        if files
+            .iter()
+            .any(|file| app.matches_for_file(file) >= max_count)
+        {
+            app.mark_results_truncated();
+        }
+    }
+
+    // Stop ripgrep once the total result limit is reached
+    if app.is_result_limit_reached() {
+        search.stop();
+        app.complete_search();
+        return true;
+    }
+
+    if finished {
+        search.task.finish();
+        app.complete_search();
+    }
+    finished
+}
+
+/// Suspend the TUI and open the selected result in the editor
+fn open_selected_result(terminal: &mut Tui, app: &mut App) -> Result<()> {
+    let results: Vec<SearchResult> = app.selected_result().cloned().into_iter().collect();
+    open_results(terminal, app, &results)
+}
+
+/// Open the directory of the selected result in the system file manager
+fn reveal_selected_result(app: &mut App) {
+    let Some(path) = app
+        .selected_result()
+        .map(|result| PathBuf::from(&result.file_path))
+    else {
+        return;
+    };
+    app.status_message = Some("Opening file manager…".to_string());
+    if let Err(e) = editor::reveal_in_file_manager(&path) {
+        crate::logging::error_log(&format!("Failed to open the file manager: {:?}", e));
+        if let SearchError::SearchProcessError(message) = e {
+            app.status_message = Some(message);
+        }
+    }
+}
+
+/// Remember the preview mode and position in the config file for later runs
+fn save_preview_layout(app: &App, config: &Config) {
+    if config.preview_mode == app.preview_mode && config.preview_position == app.preview_position {
+        return;
+    }
+    let keys = [
+        ("preview_mode", app.preview_mode.name()),
+        ("preview_position", app.preview_position.name()),
+    ];
+    if let Err(e) = Config::save_keys(&keys) {
+        crate::logging::error_log(&format!("Failed to save the preview layout: {}", e));
+    }
+}
+
+/// Suspend the TUI and open the marked results in the editor one after another
+fn open_marked_results(terminal: &mut Tui, app: &mut App) -> Result<()> {
+    let results: Vec<SearchResult> = app.marked_results().into_iter().cloned().collect();
+    open_results(terminal, app, &results)
+}
+
+/// Open each result in the editor, stopping at the first failure
+fn open_results(terminal: &mut Tui, app: &mut App, results: &[SearchResult]) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+
+    restore_terminal(terminal)?;
+    for result in results {
+        if let Err(e) = editor::open_in_editor(result, &app.editor) {
+            crate::logging::error_log(&format!("Failed to open editor: {:?}", e));
+            if let SearchError::EditorError { command, reason } = e {
+                app.status_message = Some(format!("Failed to open {}: {}", command, reason));
+            }
+            break;
+        }
+    }
+
+    *terminal = setup_terminal()?;
+    terminal
+        .clear()
+        .map_err(|e| SearchError::terminal_error(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::mock_backend::mock_result;
+
+    #[tokio::test]
+    async fn test_search_completes_without_scrolling() {
+        let results: Vec<SearchResult> = (1..=250)
+            .map(|line| mock_result("a.rs", line, "fn main() {"))
+            .collect();
+        let (task, loader) = stream::spawn_blocking_search(Vec::new(), move || results);
+        let mut search = ActiveSearch {
+            task,
+            loader,
+            timeout: None,
+            max_count: None,
+        };
+        let mut app = App::new();
+        app.load_batch_size = 10;
+        app.progressive_load_threshold = 5;
+        app.start_new_search();
+
+        // The selection stays on the first result the whole time
+        let mut paused_drawing = false;
+        loop {
+            let batch = next_batch(Some(&mut search), app.load_batch_size).await;
+            if add_results(&mut app, &mut search, batch) {
+                break;
+            }
+            paused_drawing |= !app.needs_progressive_load_check;
+        }
+
+        assert!(paused_drawing);
+        assert_eq!(app.selected_index, 0);
+        assert!(!app.search_progress.is_searching);
+        assert_eq!(app.search_results.len(), 250);
+        let status = app.get_progressive_load_status().unwrap();
+        assert_eq!(status.total_matches, 250);
+    }
+}
@@ -1,13 +1,25 @@
 //! UI rendering and layout module
 
+use crate::preview::{CsvTable, PreviewHandler};
+use crate::search::SearchResult;
+use crate::tui::app::{App, ExportTarget, InputFocus, PreviewMode, PreviewPosition, ResultRow};
+use crate::tui::directory_tree::{normalize_path, DirectoryTree};
 use crate::tui::highlighter::SyntaxHighlighter;
+use crate::tui::keybindings::{keys_label, KeySpec};
+use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table,
+    },
     Frame,
 };
+use std::path::Path;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Information about the results list area for mouse click handling
 #[derive(Debug, Clone)]
@@ -16,4 +28,1112 @@ pub struct ResultsAreaInfo {
     pub height: u16,
     pub left: u16,
     pub width: u16,
+    /// Number of rows of the list that fit on screen, fewer than `height` when rows
+    /// take up several lines with context lines or wrapping
+    pub page_size: usize,
+    /// Index in `visible_rows` of the row at the top of the list
+    pub first_row: usize,
+    /// Lines taken by each row on screen, from `first_row` on
+    pub row_heights: Vec<u16>,
+    /// Rows of the directory tree, `None` when the tree is hidden
+    pub tree: Option<Rect>,
+    /// Area of the preview pane, empty when the preview is hidden
+    pub preview: Rect,
+}
+
+/// Render the whole TUI and return the results list area for mouse handling
+pub fn draw(frame: &mut Frame, app: &App, highlighter: &mut SyntaxHighlighter) -> ResultsAreaInfo {
+    if app.preview_mode == PreviewMode::Fullscreen {
+        return draw_fullscreen_preview(frame, app, highlighter);
+    }
+
+    let show_path_filter = app.input_focus == InputFocus::PathFilter || app.path_filter.is_some();
+    let path_filter_height = if show_path_filter { 3 } else { 0 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),                  // search box
+            Constraint::Length(path_filter_height), // path filter box
+            Constraint::Min(1),                     // results and preview
+            Constraint::Length(1),                  // status bar
+        ])
+        .split(frame.area());
+
+    render_search_box(frame, chunks[0], app);
+    if show_path_filter {
+        render_path_filter_box(frame, chunks[1], app);
+    }
+
+    // The tree takes the left of the whole height, the results and preview share the rest
+    let (tree_area, main_area) = match &app.directory_tree {
+        Some(tree) => {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+                .split(chunks[2]);
+            (Some(render_tree(frame, columns[0], app, tree)), columns[1])
+        }
+        None => (None, chunks[2]),
+    };
+    let mut results_area = if app.preview_mode == PreviewMode::Hidden {
+        render_results(frame, main_area, app, highlighter)
+    } else {
+        let direction = match app.preview_position {
+            PreviewPosition::Right => Direction::Horizontal,
+            PreviewPosition::Bottom => Direction::Vertical,
+        };
+        let panes = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(main_area);
+        render_preview(frame, panes[1], app, highlighter);
+        let mut results_area = render_results(frame, panes[0], app, highlighter);
+        results_area.preview = panes[1];
+        results_area
+    };
+    results_area.tree = tree_area;
+    render_status_bar(frame, chunks[3], app);
+
+    if let Some(file_name) = &app.export_prompt {
+        render_export_prompt(frame, frame.area(), app.export_target, file_name);
+    }
+    if let Some(note) = &app.annotation_prompt {
+        render_annotation_prompt(frame, frame.area(), note);
+    }
+    if app.show_help {
+        render_help(frame, frame.area(), app);
+    }
+
+    results_area
+}
+
+/// Render only the preview below a header with the selected location
+/// The results list is hidden, so its area is empty
+fn draw_fullscreen_preview(
+    frame: &mut Frame,
+    app: &App,
+    highlighter: &mut SyntaxHighlighter,
+) -> ResultsAreaInfo {
+    let area = frame.area();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // header
+            Constraint::Min(1),    // preview
+        ])
+        .split(area);
+
+    let location = match app.selected_result() {
+        Some(result) => format!("{}:{}", result.get_display_path(), result.line_number),
+        None => "No file selected".to_string(),
+    };
+    // Escape always leaves the fullscreen preview, next to the preview mode keys
+    let mut keys = app.keybindings.cycle_preview_mode.clone();
+    let escape = KeySpec::plain(KeyCode::Esc);
+    if !keys.contains(&escape) {
+        keys.push(escape);
+    }
+    let header = Line::from(vec![
+        Span::styled(
+            format!(" {} ", location),
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+        ),
+        Span::styled(
+            format!(" {}: back", keys_label(&keys)),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(header), chunks[0]);
+    render_preview(frame, chunks[1], app, highlighter);
+
+    if let Some(file_name) = &app.export_prompt {
+        render_export_prompt(frame, area, app.export_target, file_name);
+    }
+    if let Some(note) = &app.annotation_prompt {
+        render_annotation_prompt(frame, area, note);
+    }
+    if app.show_help {
+        render_help(frame, area, app);
+    }
+
+    ResultsAreaInfo {
+        top: area.y,
+        height: 0,
+        left: area.x,
+        width: 0,
+        page_size: 0,
+        first_row: 0,
+        row_heights: Vec::new(),
+        tree: None,
+        preview: area,
+    }
+}
+
+/// Get a rectangle of the given percentage size centered in the area
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Render the export file name prompt on top of the other panes
+fn render_export_prompt(frame: &mut Frame, area: Rect, target: ExportTarget, file_name: &str) {
+    let title = match target {
+        ExportTarget::Results => "Export results to (Enter to save, Esc to cancel)",
+        ExportTarget::Annotations => "Export annotations to (Enter to save, Esc to cancel)",
+    };
+    render_prompt(frame, area, title, file_name);
+}
+
+/// Render the prompt for the note of the selected result
+fn render_annotation_prompt(frame: &mut Frame, area: Rect, note: &str) {
+    render_prompt(
+        frame,
+        area,
+        "Annotation (Enter to save, empty to remove, Esc to cancel)",
+        note,
+    );
+}
+
+/// Render a one line text input centered on top of the other panes
+fn render_prompt(frame: &mut Frame, area: Rect, title: &str, text: &str) {
+    let area = centered_rect(60, 100, area);
+    let area = Rect {
+        y: area.y + area.height.saturating_sub(3) / 2,
+        height: area.height.min(3),
+        ..area
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title.to_string())
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text.to_string()).block(block), area);
+}
+
+/// Render the keybinding help overlay on top of the other panes
+fn render_help(frame: &mut Frame, area: Rect, app: &App) {
+    let area = centered_rect(70, 80, area);
+    let rows = app
+        .help_rows
+        .iter()
+        .map(|(action, keys)| Row::new(vec![action.as_str(), keys.as_str()]));
+    let widths = [Constraint::Percentage(60), Constraint::Percentage(40)];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Help - press any key to close")
+        .border_style(Style::default().fg(Color::Cyan));
+    let table = Table::new(rows, widths)
+        .header(Row::new(vec!["Action", "Key"]).style(Style::default().fg(Color::Yellow)))
+        .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
+
+/// Get the border style for a pane depending on whether it has focus
+fn focus_style(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    }
+}
+
+/// Render the search input box, with the filter box beside it while filtering
+fn render_search_box(frame: &mut Frame, area: Rect, app: &App) {
+    let show_filter = app.input_focus == InputFocus::Filter || app.filter_pattern.is_some();
+    let boxes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(if show_filter {
+            [Constraint::Percentage(60), Constraint::Percentage(40)]
+        } else {
+            [Constraint::Percentage(100), Constraint::Percentage(0)]
+        })
+        .split(area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Search")
+        .border_style(focus_style(app.input_focus == InputFocus::Primary));
+
+    let input = Paragraph::new(pattern_line(app)).block(block);
+    frame.render_widget(input, boxes[0]);
+
+    if show_filter {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Filter")
+            .border_style(focus_style(app.input_focus == InputFocus::Filter));
+
+        let filter = Paragraph::new(app.filter_pattern.clone().unwrap_or_default()).block(block);
+        frame.render_widget(filter, boxes[1]);
+    }
+}
+
+/// Search pattern with the cursor drawn as a reversed block and the selection highlighted
+/// while the search box has focus
+fn pattern_line(app: &App) -> Line<'static> {
+    let pattern = app.active_pattern();
+    if app.input_focus != InputFocus::Primary {
+        return Line::from(pattern.to_string());
+    }
+
+    let cursor = app.cursor();
+    let selection = app.pattern_selection().unwrap_or_default();
+    // Past the end of the pattern the cursor is a reversed space
+    let chars = pattern
+        .char_indices()
+        .chain((cursor == pattern.len()).then_some((cursor, ' ')));
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (index, c) in chars {
+        let mut style = Style::default();
+        if selection.contains(&index) {
+            style = style.bg(Color::Blue);
+        }
+        if index == cursor {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line::from(spans)
+}
+
+/// Render the path filter box, a glob matched against the display path of each result
+fn render_path_filter_box(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Path filter (glob)")
+        .border_style(focus_style(app.input_focus == InputFocus::PathFilter));
+
+    let filter = Paragraph::new(app.path_filter.clone().unwrap_or_default()).block(block);
+    frame.render_widget(filter, area);
+}
+
+/// Render the directory tree, files with results and the directories holding them are highlighted
+/// Returns the area of the tree rows for mouse handling
+fn render_tree(frame: &mut Frame, area: Rect, app: &App, tree: &DirectoryTree) -> Rect {
+    let title = match &app.directory_scope {
+        Some(scope) => format!("Files: {}", scope.display()),
+        None => "Files".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(focus_style(app.input_focus == InputFocus::Tree));
+    let inner = block.inner(area);
+
+    let matched = app.matched_paths();
+    let height = inner.height as usize;
+    let items: Vec<ListItem> = tree
+        .rows()
+        .into_iter()
+        .enumerate()
+        .skip(tree.scroll_offset(height))
+        .take(height)
+        .map(|(index, row)| {
+            let path = normalize_path(&row.path);
+            let has_matches = if row.is_dir {
+                // Compared by component, so `src/ab.rs` doesn't count as inside `src/a`
+                matched
+                    .iter()
+                    .any(|file| Path::new(file).starts_with(&path))
+            } else {
+                matched.contains(&path)
+            };
+            let marker = match (row.is_dir, row.expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            };
+            let mut style = if has_matches {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            if index == tree.selected {
+                style = style.bg(Color::Rgb(64, 64, 64));
+            }
+            ListItem::new(Line::from(Span::styled(
+                format!("{}{}{}", "  ".repeat(row.depth), marker, row.name),
+                style,
+            )))
+        })
+        .collect();
+
+    frame.render_widget(List::new(items).block(block), area);
+    inner
+}
+
+/// Render the results list, keeping the selected result visible
+fn render_results(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    highlighter: &mut SyntaxHighlighter,
+) -> ResultsAreaInfo {
+    // Show how many results are left when filters hide some of them
+    let title = if app.is_filtered() {
+        format!(
+            "Results {} ({} of {} match)",
+            app.get_position_label(),
+            app.active_results_filtered().len(),
+            app.search_results.len()
+        )
+    } else {
+        format!("Results {}", app.get_position_label())
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(focus_style(app.input_focus == InputFocus::Results));
+    let inner = block.inner(area);
+
+    // The scrollbar takes up the rightmost column inside the border
+    let list_area = Rect {
+        width: inner.width.saturating_sub(1),
+        ..inner
+    };
+    let scrollbar_area = Rect {
+        x: list_area.x + list_area.width,
+        width: inner.width - list_area.width,
+        ..inner
+    };
+
+    // Scroll just enough to keep the selected result on screen
+    // Results with context lines or wrapped lines take up more than one row
+    let rows = app.visible_rows();
+    let list_width = list_area.width as usize;
+    // Numbers are padded to the widest one so rows stay aligned as results arrive
+    let number_width = app
+        .show_result_numbers
+        .then(|| app.search_results.len().to_string().len());
+    let visible_rows = inner.height.max(1) as usize;
+    let mut build_item =
+        |index: usize| row_item(app, &rows[index], highlighter, list_width, number_width);
+    // Each row is built once, walking up from the selected row and then down past it
+    let selected = app.selected_index.min(rows.len().saturating_sub(1));
+    let mut offset = selected;
+    let mut above: Vec<ListItem> = Vec::new();
+    let mut used_rows = 0;
+    if !rows.is_empty() {
+        let item = build_item(selected);
+        used_rows = item.height();
+        above.push(item);
+    }
+    while offset > 0 {
+        let item = build_item(offset - 1);
+        if used_rows + item.height() > visible_rows {
+            break;
+        }
+        offset -= 1;
+        used_rows += item.height();
+        above.push(item);
+    }
+
+    let mut page_size = 0;
+    let mut page_rows = 0;
+    let mut row_heights = Vec::new();
+    let below = visible_rows.saturating_sub(above.len());
+    let items: Vec<ListItem> = above
+        .into_iter()
+        .rev()
+        .chain((selected + 1..rows.len()).take(below).map(build_item))
+        .enumerate()
+        .map(|(position, item)| {
+            let index = offset + position;
+            if page_rows < visible_rows {
+                row_heights.push(item.height() as u16);
+            }
+            page_rows += item.height();
+            if page_rows <= visible_rows {
+                page_size += 1;
+            }
+            if index == app.selected_index {
+                item.style(Style::default().bg(Color::Rgb(64, 64, 64)))
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    frame.render_widget(block, area);
+    frame.render_widget(List::new(items), list_area);
+
+    let mut scrollbar_state = ScrollbarState::new(rows.len()).position(app.selected_index);
+    frame.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None),
+        scrollbar_area,
+        &mut scrollbar_state,
+    );
+
+    ResultsAreaInfo {
+        top: list_area.y,
+        height: list_area.height,
+        left: list_area.x,
+        width: list_area.width,
+        page_size: page_size.max(1),
+        first_row: offset,
+        row_heights,
+        tree: None,
+        preview: Rect::default(),
+    }
+}
+
+/// Build the list item of a row, with word wrap lines wider than `list_width` continue
+/// on the next rows. The selection highlight of an item covers all of its rows
+/// Results are numbered when `number_width` is set
+fn row_item(
+    app: &App,
+    row: &ResultRow,
+    highlighter: &mut SyntaxHighlighter,
+    list_width: usize,
+    number_width: Option<usize>,
+) -> ListItem<'static> {
+    let lines = match *row {
+        ResultRow::FileHeader {
+            first_result,
+            match_count,
+            collapsed,
+        } => {
+            let result = &app.search_results[first_result];
+            vec![file_header_line(
+                result,
+                match_count,
+                collapsed,
+                app.get_file_icon(result),
+                app.get_file_size(result),
+                list_width,
+            )]
+        }
+        ResultRow::Result(result_index) => {
+            let number = number_width.map(|width| format!("#{:>width$} ", result_index + 1));
+            result_lines(
+                app,
+                &app.search_results[result_index],
+                number,
+                highlighter,
+                list_width,
+            )
+        }
+    };
+    if app.word_wrap_results {
+        ListItem::new(Text::from(
+            lines
+                .into_iter()
+                .flat_map(|line| wrap_line(line, list_width))
+                .collect::<Vec<_>>(),
+        ))
+    } else {
+        ListItem::new(Text::from(lines))
+    }
+}
+
+/// Split a line into rows at most `width` columns wide, keeping the styles of its spans
+fn wrap_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 || line.width() <= width {
+        return vec![line];
+    }
+
+    let line_style = line.style;
+    let mut rows = Vec::new();
+    let mut row: Vec<Span<'static>> = Vec::new();
+    let mut row_width = 0;
+    for span in line.spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if row_width + char_width > width && row_width > 0 {
+                if !text.is_empty() {
+                    row.push(Span::styled(std::mem::take(&mut text), span.style));
+                }
+                rows.push(Line::from(std::mem::take(&mut row)).style(line_style));
+                row_width = 0;
+            }
+            text.push(c);
+            row_width += char_width;
+        }
+        if !text.is_empty() {
+            row.push(Span::styled(text, span.style));
+        }
+    }
+    if !row.is_empty() {
+        rows.push(Line::from(row).style(line_style));
+    }
+    rows
+}
+
+/// Build the header row of a file group, with the match count as a badge on the right
+/// The count comes from the rows built for this frame, so it grows as results stream in
+fn file_header_line(
+    result: &SearchResult,
+    match_count: usize,
+    collapsed: bool,
+    icon: Option<&str>,
+    size: Option<u64>,
+    list_width: usize,
+) -> Line<'static> {
+    let marker = if collapsed { "+" } else { "-" };
+    let matches = if match_count == 1 { "match" } else { "matches" };
+    let icon = icon.map(|icon| format!("{} ", icon)).unwrap_or_default();
+    let path = format!("{} {}{}", marker, icon, result.get_display_path());
+    let size = size
+        .map(|size| format!(" {}", format_file_size(size)))
+        .unwrap_or_default();
+    let badge = format!("({} {})", match_count, matches);
+    let padding = list_width
+        .saturating_sub(path.width() + size.width() + badge.width())
+        .max(1);
+    Line::from(vec![
+        Span::styled(path, Style::default().fg(Color::Blue)),
+        Span::styled(size, Style::default().fg(Color::DarkGray)),
+        Span::raw(" ".repeat(padding)),
+        Span::styled(badge, Style::default().fg(Color::Yellow)),
+    ])
+}
+
+/// Size in bytes as B, KB, MB or GB with one decimal above a kilobyte
+fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Search time in seconds, with two decimals under a second and one above
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    if secs < 1.0 {
+        format!("[{:.2}s]", secs)
+    } else {
+        format!("[{:.1}s]", secs)
+    }
+}
+
+/// Build the lines of a result with its context lines, `number` starts the result line
+fn result_lines(
+    app: &App,
+    result: &SearchResult,
+    number: Option<String>,
+    highlighter: &mut SyntaxHighlighter,
+    list_width: usize,
+) -> Vec<Line<'static>> {
+    let highlighted = app.get_cached_highlighted_line(result, highlighter);
+    let mut line = Line::default().style(highlighted.style);
+    if app.is_bookmarked(result) {
+        line.spans
+            .insert(0, Span::styled("* ", Style::default().fg(Color::Yellow)));
+    }
+    if app.is_selected(result) {
+        line.spans
+            .insert(0, Span::styled("✓ ", Style::default().fg(Color::Green)));
+    }
+    if let Some(icon) = app.get_file_icon(result) {
+        line.spans.insert(0, Span::raw(format!("{} ", icon)));
+    }
+    if let Some(number) = number {
+        line.spans.insert(
+            0,
+            Span::styled(number, Style::default().fg(Color::DarkGray)),
+        );
+    }
+    let mut column = String::new();
+    if let Some(mtime) = app.get_mtime_label(result) {
+        column = format!(" [{}]", mtime);
+    }
+    if app.annotation(result).is_some() {
+        column = format!(" 📝{}", column);
+    }
+    if let Some(blame) = app.get_blame_label(result) {
+        // The blame is cut short so the line keeps at least half of the list
+        let blame_width = (list_width / 2).saturating_sub(column.width() + 3);
+        column = format!(" [{}]{}", truncate_right(&blame, blame_width), column);
+    }
+
+    let (mut lines, after) = if app.column_layout {
+        // The columns fill the width left by the markers before and the column after
+        let width = list_width.saturating_sub(line.width() + column.width());
+        let renderer = ResultsListRenderer::new(app, width);
+        let row = ResultsRow::from_highlighted(result, highlighted);
+        line.spans.extend(renderer.line(row).spans);
+        let (before, after) = ResultsRow::context_rows(result);
+        let context_renderer = ResultsListRenderer::new(app, list_width);
+        let render = |rows: Vec<ResultsRow>| {
+            rows.into_iter()
+                .map(|row| context_renderer.line(row))
+                .collect::<Vec<_>>()
+        };
+        (render(before), render(after))
+    } else {
+        line.spans.extend(highlighted.spans);
+        result.format_context_for_tui_display()
+    };
+    if column.is_empty() {
+        lines.push(line);
+    } else {
+        lines.extend(with_right_column(
+            line,
+            column,
+            list_width,
+            app.word_wrap_results,
+        ));
+    }
+    lines.extend(after);
+    lines
+}
+
+/// Result line split into the columns of the column layout
+struct ResultsRow {
+    path: String,
+    line_num: String,
+    content: Line<'static>,
+}
+
+impl ResultsRow {
+    /// Columns of a result from its highlighted `path:line content` line
+    fn from_highlighted(result: &SearchResult, highlighted: Line<'static>) -> Self {
+        // The highlighted line ends with the trimmed content, after the location
+        let content_len = result.line_content.trim().len();
+        let prefix_len = highlighted
+            .spans
+            .iter()
+            .map(|span| span.content.len())
+            .sum::<usize>()
+            .saturating_sub(content_len);
+        Self {
+            path: result.get_display_path().to_string(),
+            line_num: result.line_number.to_string(),
+            content: skip_bytes(highlighted, prefix_len),
+        }
+    }
+
+    /// Columns of the context lines before and after a result, without a path
+    fn context_rows(result: &SearchResult) -> (Vec<Self>, Vec<Self>) {
+        let dim = Style::default().fg(Color::DarkGray);
+        let row = |line_number: usize, text: &String| Self {
+            path: String::new(),
+            line_num: line_number.to_string(),
+            content: Line::styled(text.trim_end().to_string(), dim),
+        };
+        let first_before = result
+            .line_number
+            .saturating_sub(result.context_before.len());
+        let before = (first_before..)
+            .zip(&result.context_before)
+            .map(|(line_number, text)| row(line_number, text))
+            .collect();
+        let after = (result.line_number + 1..)
+            .zip(&result.context_after)
+            .map(|(line_number, text)| row(line_number, text))
+            .collect();
+        (before, after)
+    }
+}
+
+/// Lays out result rows as a path column, a line number column and the content in
+/// the rest of the width, each cut short with `…` on its own
+struct ResultsListRenderer {
+    path_width: usize,
+    line_width: usize,
+    content_width: usize,
+}
+
+impl ResultsListRenderer {
+    fn new(app: &App, width: usize) -> Self {
+        let path_width = (width * app.path_column_percent / 100).min(width);
+        let line_width = app.line_column_width.min(width - path_width);
+        Self {
+            path_width,
+            line_width,
+            content_width: width - path_width - line_width,
+        }
+    }
+
+    fn line(&self, row: ResultsRow) -> Line<'static> {
+        // Each column keeps a space before the next one
+        let path = truncate_right(&row.path, self.path_width.saturating_sub(1));
+        let number_width = self.line_width.saturating_sub(1);
+        let line_num = truncate_right(&row.line_num, number_width);
+        let mut spans = vec![
+            Span::styled(
+                format!("{:<width$}", path, width = self.path_width),
+                Style::default().fg(Color::Blue),
+            ),
+            Span::styled(
+                format!("{:>number_width$} ", line_num),
+                Style::default().fg(Color::Green),
+            ),
+        ];
+        spans.extend(truncate_line(row.content, self.content_width).spans);
+        Line::from(spans)
+    }
+}
+
+/// Drop the first `bytes` bytes of a line, keeping the styles of the rest
+fn skip_bytes(line: Line<'static>, mut bytes: usize) -> Line<'static> {
+    let style = line.style;
+    let mut spans = Vec::with_capacity(line.spans.len());
+    for span in line.spans {
+        if bytes >= span.content.len() {
+            bytes -= span.content.len();
+            continue;
+        }
+        let text = span.content[bytes..].to_string();
+        bytes = 0;
+        spans.push(Span::styled(text, span.style));
+    }
+    Line::from(spans).style(style)
+}
+
+/// Cut a line short with a `…` so it fits the width
+fn truncate_line(line: Line<'static>, width: usize) -> Line<'static> {
+    if line.width() <= width {
+        return line;
+    }
+    match width {
+        0 => Line::default(),
+        1 => Line::raw("…"),
+        _ => {
+            let mut first = wrap_line(line, width - 1).swap_remove(0);
+            first.spans.push(Span::raw("…"));
+            first
+        }
+    }
+}
+
+/// Right-align a column, such as the modification time, after the line, cutting the
+/// line short or wrapping it so the column stays visible
+fn with_right_column(
+    line: Line<'static>,
+    column: String,
+    list_width: usize,
+    wrap: bool,
+) -> Vec<Line<'static>> {
+    let content_width = list_width.saturating_sub(column.width());
+    let mut rows = wrap_line(line, content_width);
+    if !wrap {
+        rows.truncate(1);
+    }
+    if let Some(first) = rows.first_mut() {
+        let padding = content_width.saturating_sub(first.width());
+        first.spans.push(Span::raw(" ".repeat(padding)));
+        first
+            .spans
+            .push(Span::styled(column, Style::default().fg(Color::DarkGray)));
+    }
+    rows
+}
+
+/// Render the preview of the selected result, syntax highlighted when colors are supported
+fn render_preview(frame: &mut Frame, area: Rect, app: &App, highlighter: &mut SyntaxHighlighter) {
+    if app.show_diff_view {
+        render_diff(frame, area, app);
+        return;
+    }
+
+    let block = Block::default().borders(Borders::ALL);
+    let inner = block.inner(area);
+
+    // The preview is centered on the target line, so render extra lines on both
+    // sides when scrolled and skip the ones above the scrolled window
+    let scroll = 2 * app.preview_scroll_offset;
+    let (width, height) = PreviewHandler::layout_dimensions(area);
+    let dimensions = Some((width, height + scroll));
+    if let Some(table) = app.get_preview_table(dimensions) {
+        render_preview_table(frame, area, app, &table, scroll);
+        return;
+    }
+    let content = if app.use_color {
+        app.get_preview_content_highlighted(dimensions, highlighter)
+    } else {
+        Text::from(app.get_preview_content_plain(dimensions))
+    };
+    let scroll = scroll.min(content.lines.len().saturating_sub(inner.height as usize));
+    let range = preview_title(&content, scroll, inner.height as usize);
+    let breadcrumb_width = (inner.width as usize).saturating_sub(range.chars().count() + 2);
+    let mut block = block.title(Line::from(range).right_aligned());
+    if let Some(breadcrumb) = preview_breadcrumb(app, breadcrumb_width) {
+        block = block.title(breadcrumb);
+    }
+    let preview = Paragraph::new(content)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    frame.render_widget(preview, area);
+}
+
+/// Render the table preview of a CSV or TSV file, the header stays on top when scrolled
+fn render_preview_table(frame: &mut Frame, area: Rect, app: &App, table: &CsvTable, scroll: usize) {
+    let height = area.height.saturating_sub(2) as usize;
+    // The header row takes a line of the pane
+    let body_rows = table.rows.len().saturating_sub(1);
+    let scroll = scroll.min(body_rows.saturating_sub(height.saturating_sub(1)));
+    let range = match table.line_range(scroll, height) {
+        Some((first, last)) => format!("Preview [{}–{}]", first, last),
+        None => "Preview".to_string(),
+    };
+    let breadcrumb_width =
+        (area.width.saturating_sub(2) as usize).saturating_sub(range.chars().count() + 2);
+    let mut block = Block::bordered().title(Line::from(range).right_aligned());
+    if let Some(breadcrumb) = preview_breadcrumb(app, breadcrumb_width) {
+        block = block.title(breadcrumb);
+    }
+    frame.render_widget(table.widget(scroll, app.use_color).block(block), area);
+}
+
+/// Render the unstaged git diff of the hunk around the selected result
+fn render_diff(frame: &mut Frame, area: Rect, app: &App) {
+    let keys = keys_label(&app.keybindings.toggle_diff_view);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Diff ({}: preview)", keys));
+    let Some(result) = app.selected_result() else {
+        frame.render_widget(Paragraph::new("No file selected").block(block), area);
+        return;
+    };
+
+    let content = match app.get_diff_context(result) {
+        Some(diff) => Text::from(
+            diff.lines()
+                .map(|line| {
+                    let style = match line.chars().next() {
+                        _ if line.starts_with("+++") || line.starts_with("---") => {
+                            Style::default().fg(Color::Gray)
+                        }
+                        Some('+') => Style::default().fg(Color::Green),
+                        Some('-') => Style::default().fg(Color::Red),
+                        Some('@') => Style::default().fg(Color::Cyan),
+                        _ => Style::default(),
+                    };
+                    Line::from(Span::styled(line.to_string(), style))
+                })
+                .collect::<Vec<_>>(),
+        ),
+        None => Text::from(Span::styled(
+            "No unstaged changes around this line",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    frame.render_widget(
+        Paragraph::new(content)
+            .block(block)
+            .scroll((app.preview_scroll_offset as u16, 0)),
+        area,
+    );
+}
+
+/// Breadcrumb of the selected result with its path, language and line position,
+/// e.g. "src/main.rs  [Rust]  42/238", the path is truncated from the left to fit
+fn preview_breadcrumb(app: &App, max_width: usize) -> Option<String> {
+    let result = app.selected_result()?;
+    let path = result.get_display_path();
+
+    let mut details = String::new();
+    if let Some(language) =
+        SyntaxHighlighter::get_extension(path).and_then(SyntaxHighlighter::language_name)
+    {
+        details.push_str(&format!("  [{}]", language));
+    }
+    match app.selected_file_line_count() {
+        Some(total) => details.push_str(&format!("  {}/{}", result.line_number, total)),
+        None => details.push_str(&format!("  {}", result.line_number)),
+    }
+
+    let path_width = max_width.saturating_sub(details.chars().count());
+    Some(format!("{}{}", truncate_left(path, path_width), details))
+}
+
+/// Truncate text from the left with a `…` prefix so it fits the width
+fn truncate_left(text: &str, max_width: usize) -> String {
+    let length = text.chars().count();
+    if length <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let tail: String = text.chars().skip(length - (max_width - 1)).collect();
+    format!("…{}", tail)
+}
+
+/// Truncate text from the right with a `…` suffix so it fits the width
+fn truncate_right(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let head: String = text.chars().take(max_width - 1).collect();
+    format!("{}…", head)
+}
+
+/// Preview title with the range of file lines visible in the pane
+fn preview_title(content: &Text, scroll: usize, height: usize) -> String {
+    let numbers: Vec<usize> = content
+        .lines
+        .iter()
+        .skip(scroll)
+        .take(height)
+        .filter_map(|line| {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            PreviewHandler::gutter_line_number(&text)
+        })
+        .collect();
+    match (numbers.first(), numbers.last()) {
+        (Some(first), Some(last)) => format!("Preview [{}–{}]", first, last),
+        _ => "Preview".to_string(),
+    }
+}
+
+/// Contents of the status bar, rendered as labeled segments separated by `│`
+pub struct StatusBar {
+    /// Loading progress and match counts
+    pub progress: String,
+    /// The pattern was edited and the search waits for typing to stop
+    pub search_pending: bool,
+    /// Message shown for a short while, e.g. "Copied!" or the result of an export
+    pub message: Option<String>,
+    /// Position of the selection among the results
+    pub result_count: String,
+    /// Number of results marked to open or export together
+    pub marked: usize,
+    /// Number of duplicate results dropped in the current search
+    pub duplicates: usize,
+    pub search_mode: &'static str,
+    pub sort_order: String,
+    /// Filter applied to the results, `None` when the results are not filtered
+    pub filter: Option<String>,
+    /// Time the search has been running, or the last search took
+    pub elapsed: Option<Duration>,
+    pub directories: String,
+}
+
+impl StatusBar {
+    /// Collect the status bar contents from the app state
+    pub fn from_app(app: &App) -> Self {
+        // Error messages can span several lines, the status bar has one
+        let message = if app.is_clipboard_message_visible() {
+            Some("Copied!".to_string())
+        } else {
+            app.status_message
+                .as_ref()
+                .map(|message| message.lines().map(str::trim).collect::<Vec<_>>().join(" "))
+        };
+        Self {
+            progress: app.get_loading_message(),
+            search_pending: app.pending_search.is_some(),
+            message,
+            result_count: app.get_position_label(),
+            marked: app.selected_results.len(),
+            duplicates: app
+                .get_progressive_load_status()
+                .map_or(0, |status| status.deduplication_count),
+            search_mode: app.get_mode_label(),
+            sort_order: app.get_sort_label(),
+            filter: app
+                .filter_pattern
+                .clone()
+                .filter(|filter| !filter.is_empty()),
+            elapsed: app.search_elapsed(),
+            directories: app.get_directories_label(),
+        }
+    }
+
+    /// The segments to show, each with its color
+    fn segments(&self) -> Vec<(String, Style)> {
+        let pending = if self.search_pending { " …" } else { "" };
+        let mut segments = vec![(
+            format!(" {}{} ", self.progress, pending),
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+        )];
+        if let Some(message) = &self.message {
+            segments.push((
+                format!(" {} ", message),
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
+        }
+        segments.push((
+            format!("Result: {}", self.result_count),
+            Style::default().fg(Color::Green),
+        ));
+        if self.marked > 0 {
+            segments.push((
+                format!("Marked: {}", self.marked),
+                Style::default().fg(Color::Green),
+            ));
+        }
+        if self.duplicates > 0 {
+            segments.push((
+                format!("{} duplicates removed", self.duplicates),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        segments.extend([
+            (
+                format!("Mode: {}", self.search_mode),
+                Style::default().fg(Color::Yellow),
+            ),
+            (
+                format!("Sort: {}", self.sort_order),
+                Style::default().fg(Color::Magenta),
+            ),
+        ]);
+        if let Some(filter) = &self.filter {
+            segments.push((
+                format!("Filter: {}", filter),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if let Some(elapsed) = self.elapsed {
+            segments.push((format_elapsed(elapsed), Style::default().fg(Color::Blue)));
+        }
+        segments.push((
+            format!("Dirs: {}", self.directories),
+            Style::default().fg(Color::Gray),
+        ));
+        segments
+    }
+
+    /// Build the status bar line
+    pub fn to_line(&self) -> Line<'static> {
+        let separator = Span::styled(" │ ", Style::default().fg(Color::DarkGray));
+        let mut spans = Vec::new();
+        for (index, (text, style)) in self.segments().into_iter().enumerate() {
+            if index > 0 {
+                spans.push(separator.clone());
+            }
+            spans.push(Span::styled(text, style));
+        }
+        Line::from(spans)
+    }
+}
+
+/// Render the status bar with loading progress, status message, selection position,
+/// search mode, sort order, filter, search time and active directories
+fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
+    let status_bar = StatusBar::from_app(app);
+    frame.render_widget(Paragraph::new(status_bar.to_line()), area);
 }
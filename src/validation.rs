@@ -33,7 +33,7 @@ impl InputValidator {
         }
 
         // Check for potentially problematic regex characters
-        if let Err(_) = Regex::new(trimmed) {
+        if Regex::new(trimmed).is_err() {
             // If its not a valid regex, that's ok for literal search
             // but we should check for common problematic patterns
             let problematic_patterns = ['*', '?', '[', ']', '{', '}', '(', ')', '+', '|']; // fixed size array
@@ -67,6 +67,96 @@ impl InputValidator {
         Ok(sanitized)
     }
 
+    /// Validates a pattern that ripgrep interprets as a regex
+    /// Repeated groups that repeat an element, such as `(a+)+`, are rejected
+    pub fn validate_regex_pattern(pattern: &str) -> Result<String> {
+        let sanitized = Self::validate_search_pattern(pattern)?;
+        if Self::has_nested_quantifier(&sanitized) {
+            return Err(SearchError::InvalidPattern {
+                pattern: pattern.to_string(),
+                reason: format!(
+                    "Pattern repeats a group that already repeats, which could cause catastrophic backtracking (see {})",
+                    CATASTROPHIC_BACKTRACKING_URL
+                ),
+            });
+        }
+        Ok(sanitized)
+    }
+
+    /// Checks for a quantified group containing a quantified element, such as `(a+)+` or `(x|y*)*`
+    fn has_nested_quantifier(pattern: &str) -> bool {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut pos = 0;
+        while pos < chars.len() {
+            if Self::scan_group(&chars, &mut pos).is_none() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Scan a group up to and including its closing parenthesis
+    /// Returns whether the group contains a quantifier, `None` when a quantified group does
+    fn scan_group(chars: &[char], pos: &mut usize) -> Option<bool> {
+        let mut quantified = false;
+        while let Some(&ch) = chars.get(*pos) {
+            *pos += 1;
+            match ch {
+                '\\' => *pos += 1,
+                '[' => Self::skip_class(chars, pos),
+                '(' => {
+                    let inner = Self::scan_group(chars, pos)?;
+                    if inner && Self::is_quantifier_at(chars, *pos) {
+                        return None;
+                    }
+                    quantified |= inner;
+                }
+                ')' => return Some(quantified),
+                _ if Self::is_quantifier_at(chars, *pos - 1) => quantified = true,
+                _ => {}
+            }
+        }
+        Some(quantified)
+    }
+
+    /// Checks for a repeating quantifier, `*`, `+` or `{n,m}`, at the position
+    fn is_quantifier_at(chars: &[char], pos: usize) -> bool {
+        match chars.get(pos) {
+            Some('*' | '+') => true,
+            Some('{') => chars[pos + 1..]
+                .iter()
+                .take_while(|&&ch| ch != '}')
+                .any(|&ch| ch == ','),
+            _ => false,
+        }
+    }
+
+    /// Skip a character class, `pos` is just past its opening bracket
+    fn skip_class(chars: &[char], pos: &mut usize) {
+        if chars.get(*pos) == Some(&'^') {
+            *pos += 1;
+        }
+        // A closing bracket first in the class is a literal
+        if chars.get(*pos) == Some(&']') {
+            *pos += 1;
+        }
+        let mut depth = 1;
+        while let Some(&ch) = chars.get(*pos) {
+            *pos += 1;
+            match ch {
+                '\\' => *pos += 1,
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Validates file path
     pub fn validate_file_path(path: &str) -> Result<String> {
         let trimmed = path.trim();
@@ -123,6 +213,41 @@ mod tests {
         assert!(InputValidator::validate_search_pattern(&problematic).is_err());
     }
     
+    #[test]
+    fn test_validate_regex_pattern() {
+        for pattern in [
+            "(a+)+",
+            "(\\w+\\s?)*",
+            "(x|y*)*",
+            "((ab)*c){2,}",
+            "(?:[a-z]+)+$",
+        ] {
+            let err = InputValidator::validate_regex_pattern(pattern).unwrap_err();
+            assert!(
+                err.to_string().contains("catastrophic backtracking"),
+                "{}",
+                pattern
+            );
+        }
+
+        let fine = [
+            "(ab)+",
+            "(a+)?",
+            "(a+){2}",
+            "\\(a+\\)+",
+            "[(a+)]+",
+            "fn\\s+\\w+\\(",
+            "(a|b)+c*",
+        ];
+        for pattern in fine {
+            assert!(
+                InputValidator::validate_regex_pattern(pattern).is_ok(),
+                "{}",
+                pattern
+            );
+        }
+    }
+
     #[test]
     fn test_validate_file_path() {
         assert!(InputValidator::validate_file_path("/absolute/path").is_ok());